@@ -4,14 +4,25 @@ extern crate criterion;
 #[cfg(target_arch = "x86_64")]
 extern crate libc;
 extern crate memchr;
+#[cfg(feature = "compare")]
+extern crate regex;
 
 use criterion::{Bencher, Benchmark, Criterion, Throughput};
 
 use imp::{
+    memchr0_count, fallback0_count,
+    memchr_not_trim, fallback_not_trim,
     memchr1_count, memchr2_count, memchr3_count,
-    memrchr1_count, memrchr2_count, memrchr3_count,
+    memrchr1_count, memrchr2_count, memrchr3_count, memrchr1_iter_count,
     fallback1_count, fallback2_count, fallback3_count,
+    fallback1_unroll4_count,
     naive1_count, naive2_count, naive3_count,
+    std1_count, std_rev1_count,
+    memchr1_popcount, fallback1_popcount,
+    memchr1_find, memchr1_contains, std1_find, std1_contains,
+    memmem_lines_count, finder_lines_count,
+    finder_count, naive_memmem_count,
+    memchr1_find_all, memchr_early_find_all,
 };
 use inputs::{
     Input, Search1, Search2, Search3,
@@ -21,6 +32,9 @@ use inputs::{
 #[cfg(target_arch = "x86_64")]
 #[path = "../../src/c.rs"]
 mod c;
+#[cfg(all(target_arch = "x86_64", memchr_runtime_avx512))]
+#[path = "../../src/x86/avx512.rs"]
+mod avx512;
 #[path = "../../src/fallback.rs"]
 #[allow(dead_code)]
 mod fallback;
@@ -99,6 +113,89 @@ fn all(c: &mut Criterion) {
         });
     }
 
+    // These call the AVX-512 routine directly, for every haystack size.
+    // `memchr` itself only uses it for haystacks of at least 64 KiB.
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_avx512))]
+    {
+        if is_x86_feature_detected!("avx512f")
+            && is_x86_feature_detected!("avx512bw")
+        {
+            define_input1(c, "memchr1/avx512/huge", HUGE, move |search, b| {
+                b.iter(|| {
+                    assert_eq!(
+                        search.byte1.count,
+                        imp::memchr1_avx512_count(
+                            search.byte1.byte,
+                            search.corpus,
+                        ),
+                    );
+                });
+            });
+            define_input1(c, "memchr1/avx512/small", SMALL, move |search, b| {
+                b.iter(|| {
+                    assert_eq!(
+                        search.byte1.count,
+                        imp::memchr1_avx512_count(
+                            search.byte1.byte,
+                            search.corpus,
+                        ),
+                    );
+                });
+            });
+            define_input1(c, "memchr1/avx512/tiny", TINY, move |search, b| {
+                b.iter(|| {
+                    assert_eq!(
+                        search.byte1.count,
+                        imp::memchr1_avx512_count(
+                            search.byte1.byte,
+                            search.corpus,
+                        ),
+                    );
+                });
+            });
+        }
+    }
+
+    // A haystack that is much larger than the threshold at which the AVX-512
+    // routine is used, but small enough to stay in L3 on most machines.
+    let large: Vec<u8> =
+        HUGE.corpus.iter().cloned().cycle().take(1 << 22).collect();
+    let corpus = large.clone();
+    define(c, "memchr1/rust/4mib", "never", &large, move |b| {
+        b.iter(|| {
+            assert_eq!(0, memchr1_count(0, &corpus));
+        });
+    });
+    let corpus = large.clone();
+    define(c, "memchr1/fallback/4mib", "never", &large, move |b| {
+        b.iter(|| {
+            assert_eq!(0, fallback1_count(0, &corpus));
+        });
+    });
+
+    // A haystack where nearly every byte matches, so that the cost of each
+    // call dominates the cost of scanning.
+    let dense: Vec<u8> =
+        (0..1 << 16).map(|i| if i % 16 == 15 { b'b' } else { b'a' }).collect();
+    let corpus = dense.clone();
+    define(c, "memchr1/rust/dense", "dense", &dense, move |b| {
+        b.iter(|| {
+            assert_eq!(15 << 12, memchr1_count(b'a', &corpus));
+        });
+    });
+    let corpus = dense.clone();
+    define(c, "memrchr1/rust/dense", "dense", &dense, move |b| {
+        b.iter(|| {
+            assert_eq!(15 << 12, memrchr1_count(b'a', &corpus));
+        });
+    });
+    let corpus = dense.clone();
+    define(c, "memrchr1/iter/dense", "dense", &dense, move |b| {
+        b.iter(|| {
+            assert_eq!(15 << 12, memrchr1_iter_count(b'a', &corpus));
+        });
+    });
+
     define_input1(c, "memchr1/fallback/huge", HUGE, move |search, b| {
         b.iter(|| {
             assert_eq!(
@@ -132,6 +229,47 @@ fn all(c: &mut Criterion) {
         });
     });
 
+    // These compare the 4-way unrolled fallback against the default 2-way
+    // one above. On an x86_64 machine (criterion, median throughput):
+    //
+    //   input              fallback (2-way)    fallback4 (4-way)
+    //   huge/never         8.15 GiB/s          6.69 GiB/s
+    //   huge/rare          7.74 GiB/s          7.68 GiB/s
+    //   huge/uncommon      1.78 GiB/s          1.82 GiB/s
+    //   huge/common        459 MiB/s           389 MiB/s
+    //   huge/verycommon    226 MiB/s           203 MiB/s
+    //   small/never        7.45 GiB/s          7.45 GiB/s
+    //   small/common       977 MiB/s           1.06 GiB/s
+    //   tiny/uncommon      956 MiB/s           689 MiB/s
+    //
+    // Since 4-way is no faster in the cases that matter most (long runs
+    // without a match), 2-way remains the default and 4-way is only used
+    // with the `fallback_unroll4` feature.
+    define_input1(c, "memchr1/fallback4/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_unroll4_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/fallback4/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_unroll4_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/fallback4/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_unroll4_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
     define_input1(c, "memchr1/naive/huge", HUGE, move |search, b| {
         b.iter(|| {
             assert_eq!(
@@ -165,6 +303,352 @@ fn all(c: &mut Criterion) {
         });
     });
 
+    define_input1(c, "count/rust/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                memchr1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "count/rust/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                memchr1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "count/rust/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                memchr1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "count/rust/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                memchr1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
+    define_input1(c, "count/fallback/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "count/fallback/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "count/fallback/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "count/fallback/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                fallback1_popcount(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
+    // Counting on a buffer much larger than any cache shows how the
+    // vectorized count fares when it is bound by memory bandwidth.
+    let mut gib: Option<(Vec<u8>, usize)> = None;
+    let benchmark = Benchmark::new("1gib", move |b| {
+        let (ref corpus, expected) = *gib.get_or_insert_with(|| {
+            let corpus: Vec<u8> =
+                HUGE.corpus.iter().cloned().cycle().take(1 << 30).collect();
+            let expected = fallback1_popcount(b'a', &corpus);
+            (corpus, expected)
+        });
+        b.iter(|| {
+            assert_eq!(expected, memchr1_popcount(b'a', corpus));
+        });
+    });
+    c.bench(
+        "count/rust",
+        benchmark.throughput(Throughput::Bytes(1 << 30)).sample_size(10),
+    );
+
+    define(c, "memchr0/rust/huge", "never", HUGE.corpus, move |b| {
+        let corpus = HUGE.corpus;
+        b.iter(|| {
+            assert_eq!(0, memchr0_count(corpus));
+        });
+    });
+    define(c, "memchr0/rust/small", "never", SMALL.corpus, move |b| {
+        let corpus = SMALL.corpus;
+        b.iter(|| {
+            assert_eq!(0, memchr0_count(corpus));
+        });
+    });
+    define(c, "memchr0/rust/tiny", "never", TINY.corpus, move |b| {
+        let corpus = TINY.corpus;
+        b.iter(|| {
+            assert_eq!(0, memchr0_count(corpus));
+        });
+    });
+
+    define(c, "memchr0/memchr1/huge", "never", HUGE.corpus, move |b| {
+        let corpus = HUGE.corpus;
+        b.iter(|| {
+            assert_eq!(0, memchr1_count(0, corpus));
+        });
+    });
+    define(c, "memchr0/memchr1/small", "never", SMALL.corpus, move |b| {
+        let corpus = SMALL.corpus;
+        b.iter(|| {
+            assert_eq!(0, memchr1_count(0, corpus));
+        });
+    });
+    define(c, "memchr0/memchr1/tiny", "never", TINY.corpus, move |b| {
+        let corpus = TINY.corpus;
+        b.iter(|| {
+            assert_eq!(0, memchr1_count(0, corpus));
+        });
+    });
+
+    define(c, "memchr0/fallback/huge", "never", HUGE.corpus, move |b| {
+        let corpus = HUGE.corpus;
+        b.iter(|| {
+            assert_eq!(0, fallback0_count(corpus));
+        });
+    });
+    define(c, "memchr0/fallback/small", "never", SMALL.corpus, move |b| {
+        let corpus = SMALL.corpus;
+        b.iter(|| {
+            assert_eq!(0, fallback0_count(corpus));
+        });
+    });
+    define(c, "memchr0/fallback/tiny", "never", TINY.corpus, move |b| {
+        let corpus = TINY.corpus;
+        b.iter(|| {
+            assert_eq!(0, fallback0_count(corpus));
+        });
+    });
+
+    // Skipping over a long run of NUL padding, as when trimming a fixed
+    // width record.
+    for &(name, len) in &[("1kib", 1 << 10), ("64kib", 1 << 16)] {
+        let mut padding = vec![0; len];
+        padding.push(b'x');
+        let corpus = padding.clone();
+        define(c, "memchr_not/rust/padding", name, &padding, move |b| {
+            b.iter(|| {
+                assert_eq!(len, memchr_not_trim(0, &corpus));
+            });
+        });
+        let corpus = padding.clone();
+        define(c, "memchr_not/fallback/padding", name, &padding, move |b| {
+            b.iter(|| {
+                assert_eq!(len, fallback_not_trim(0, &corpus));
+            });
+        });
+    }
+
+    define_input1(c, "memchr1/std/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std1_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/std/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std1_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/std/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std1_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/std/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std1_count(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
+    define_input1(c, "memchr1/first/rust/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                memchr1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/rust/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                memchr1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/rust/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                memchr1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/rust/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                memchr1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
+    define_input1(c, "memchr1/first/std/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/std/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/std/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/std/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_find(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
+    define_input1(c, "memchr1/first/contains/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_contains(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/contains/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_contains(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/contains/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_contains(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+    define_input1(c, "memchr1/first/contains/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count > 0,
+                std1_contains(search.byte1.byte, search.corpus),
+            );
+        });
+    });
+
+    define_input1(
+        c,
+        "memchr1/first/contains_byte/huge",
+        HUGE,
+        move |search, b| {
+            b.iter(|| {
+                assert_eq!(
+                    search.byte1.count > 0,
+                    memchr1_contains(search.byte1.byte, search.corpus),
+                );
+            });
+        },
+    );
+    define_input1(
+        c,
+        "memchr1/first/contains_byte/small",
+        SMALL,
+        move |search, b| {
+            b.iter(|| {
+                assert_eq!(
+                    search.byte1.count > 0,
+                    memchr1_contains(search.byte1.byte, search.corpus),
+                );
+            });
+        },
+    );
+    define_input1(
+        c,
+        "memchr1/first/contains_byte/tiny",
+        TINY,
+        move |search, b| {
+            b.iter(|| {
+                assert_eq!(
+                    search.byte1.count > 0,
+                    memchr1_contains(search.byte1.byte, search.corpus),
+                );
+            });
+        },
+    );
+    define_input1(
+        c,
+        "memchr1/first/contains_byte/empty",
+        EMPTY,
+        move |search, b| {
+            b.iter(|| {
+                assert_eq!(
+                    search.byte1.count > 0,
+                    memchr1_contains(search.byte1.byte, search.corpus),
+                );
+            });
+        },
+    );
+
     define_input2(c, "memchr2/rust/huge", HUGE, move |search, b| {
         b.iter(|| {
             assert_eq!(
@@ -479,6 +963,14 @@ fn all(c: &mut Criterion) {
             );
         });
     });
+    define_input1(c, "memrchr1/iter/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                memrchr1_iter_count(search.byte1.byte, search.corpus)
+            );
+        });
+    });
     define_input1(c, "memrchr1/rust/small", SMALL, move |search, b| {
         b.iter(|| {
             assert_eq!(
@@ -540,6 +1032,39 @@ fn all(c: &mut Criterion) {
         });
     }
 
+    define_input1(c, "memrchr1/std/huge", HUGE, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std_rev1_count(search.byte1.byte, search.corpus)
+            );
+        });
+    });
+    define_input1(c, "memrchr1/std/small", SMALL, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std_rev1_count(search.byte1.byte, search.corpus)
+            );
+        });
+    });
+    define_input1(c, "memrchr1/std/tiny", TINY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std_rev1_count(search.byte1.byte, search.corpus)
+            );
+        });
+    });
+    define_input1(c, "memrchr1/std/empty", EMPTY, move |search, b| {
+        b.iter(|| {
+            assert_eq!(
+                search.byte1.count,
+                std_rev1_count(search.byte1.byte, search.corpus)
+            );
+        });
+    });
+
     define_input2(c, "memrchr2/rust/huge", HUGE, move |search, b| {
         b.iter(|| {
             assert_eq!(
@@ -641,6 +1166,176 @@ fn all(c: &mut Criterion) {
             );
         });
     });
+
+    // These search every line of the corpus for the same needle, which
+    // compares building a `Finder` once against rebuilding it per line.
+    define(c, "memmem/lines/memmem/huge", "sherlock", HUGE.corpus, move |b| {
+        let corpus = HUGE.corpus;
+        let expected = finder_lines_count(b"Sherlock", corpus);
+        b.iter(|| {
+            assert_eq!(expected, memmem_lines_count(b"Sherlock", corpus));
+        });
+    });
+    define(c, "memmem/lines/finder/huge", "sherlock", HUGE.corpus, move |b| {
+        let corpus = HUGE.corpus;
+        let expected = memmem_lines_count(b"Sherlock", corpus);
+        b.iter(|| {
+            assert_eq!(expected, finder_lines_count(b"Sherlock", corpus));
+        });
+    });
+
+    // Short needles in HTML-like markup, where the first byte of every
+    // needle (`<`) is very common.
+    let html = html_corpus();
+    let needles = [("close-a", &b"</a>"[..]), ("never", b"</table>")];
+    for &(name, needle) in &needles {
+        let expected = naive_memmem_count(needle, &html);
+        let corpus = html.clone();
+        define(c, "memmem/finder/html", name, &html, move |b| {
+            b.iter(|| {
+                assert_eq!(expected, finder_count(needle, &corpus));
+            });
+        });
+        let corpus = html.clone();
+        define(c, "memmem/naive/html", name, &html, move |b| {
+            b.iter(|| {
+                assert_eq!(expected, naive_memmem_count(needle, &corpus));
+            });
+        });
+    }
+
+    // Repeated searches where the next match is usually within a few bytes
+    // (the quotes in flat JSON), and where it's usually further away (the
+    // line terminators in the huge corpus).
+    let json: Vec<u8> = (0..20_000)
+        .map(|i| format!("{{\"k{}\":\"v\",\"n\":{}}},", i % 97, i))
+        .flat_map(|s| s.into_bytes())
+        .collect();
+    let distributions = [
+        ("near", b'"', json.clone()),
+        ("far", b'\n', HUGE.corpus.to_vec()),
+    ];
+    for &(name, needle, ref corpus) in &distributions {
+        let expected = memchr1_find_all(needle, corpus);
+        let haystack = corpus.clone();
+        define(c, "memchr_early/rust", name, corpus, move |b| {
+            b.iter(|| {
+                assert_eq!(expected, memchr1_find_all(needle, &haystack));
+            });
+        });
+        let haystack = corpus.clone();
+        define(c, "memchr_early/early", name, corpus, move |b| {
+            b.iter(|| {
+                assert_eq!(expected, memchr_early_find_all(needle, &haystack));
+            });
+        });
+    }
+
+    // A search that never matches, starting at every offset from a word
+    // boundary. The fallback handles the bytes before the first aligned word
+    // on their own, so a regression in that head handling shows up as one
+    // offset that is much slower than the others.
+    let word = ::core::mem::size_of::<usize>();
+    for align in 0..word {
+        define_aligned(c, "memchr1/fallback/align", align, |haystack| {
+            fallback::memchr(b'z', haystack)
+        });
+        define_aligned(c, "memchr1/rust/align", align, |haystack| {
+            memchr::memchr(b'z', haystack)
+        });
+    }
+
+    // Counting the lines of a log file, with mostly short lines of mostly
+    // ASCII text and the occasional line that is much longer.
+    let logs = log_corpus();
+    let expected = logs.iter().filter(|&&b| b == b'\n').count();
+    let haystack = logs.clone();
+    define(c, "count_lines/rust", "logs", &logs, move |b| {
+        b.iter(|| {
+            assert_eq!(expected, memchr::count_lines(&haystack));
+        });
+    });
+    let haystack = logs.clone();
+    define(c, "count_lines/fallback", "logs", &logs, move |b| {
+        b.iter(|| {
+            assert_eq!(expected, fallback::count(b'\n', &haystack));
+        });
+    });
+
+    // A strided search that never matches, such as scanning one column of
+    // an array of fixed size records. The scalar loop is what a strided
+    // search does without SIMD.
+    let records = vec![b'a'; 1 << 20];
+    for &stride in &[2, 4, 8, 16, 64, 256, 4096] {
+        let haystack = records.clone();
+        let name = stride.to_string();
+        define(c, "memchr_strided/rust", &name, &records, move |b| {
+            b.iter(|| {
+                let got = memchr::memchr_strided(b'z', &haystack, stride);
+                assert_eq!(None, got);
+            });
+        });
+        let haystack = records.clone();
+        define(c, "memchr_strided/scalar", &name, &records, move |b| {
+            b.iter(|| {
+                let mut strided = haystack.iter().step_by(stride);
+                assert_eq!(None, strided.position(|&b| b == b'z'));
+            });
+        });
+    }
+
+    #[cfg(feature = "compare")]
+    compare(c);
+}
+
+/// Compares this crate's specialized routines against the literal
+/// optimizations in the `regex` crate, on the same searches. `bstr` isn't
+/// compared here yet; see the `compare` feature in `Cargo.toml`.
+#[cfg(feature = "compare")]
+fn compare(c: &mut Criterion) {
+    use memchr::Finder;
+    use regex::bytes::Regex;
+
+    let corpora = [("huge", HUGE.corpus), ("small", SMALL.corpus)];
+    let needles = [("byte", &b"z"[..]), ("literal", b"Sherlock")];
+    for &(name, corpus) in &corpora {
+        for &(kind, needle) in &needles {
+            let expected = finder_count(needle, corpus);
+            // Both searchers are built up front, so that only searching is
+            // measured.
+            let finder = Finder::new(needle);
+            let group = format!("compare/memchr/{}/{}", kind, name);
+            define(c, &group, "sherlock", corpus, move |b| {
+                b.iter(|| {
+                    assert_eq!(expected, finder.find_iter(corpus).count());
+                });
+            });
+
+            let literal = ::std::str::from_utf8(needle).unwrap();
+            let re = Regex::new(&regex::escape(literal)).unwrap();
+            let group = format!("compare/regex/{}/{}", kind, name);
+            define(c, &group, "sherlock", corpus, move |b| {
+                b.iter(|| {
+                    assert_eq!(expected, imp::regex_count(&re, corpus));
+                });
+            });
+        }
+    }
+}
+
+/// Wraps every line of the huge corpus in markup, with a link on every
+/// eighth line.
+fn html_corpus() -> Vec<u8> {
+    let mut html = vec![];
+    for (i, line) in HUGE.corpus.split(|&b| b == b'\n').enumerate() {
+        html.extend_from_slice(b"<p class=\"line\">");
+        html.extend_from_slice(line);
+        if i % 8 == 0 {
+            html.extend_from_slice(b" <a href=\"#note\">note</a>");
+        }
+        html.extend_from_slice(b"</p>\n");
+    }
+    html
 }
 
 fn define_input1<'i>(
@@ -775,6 +1470,74 @@ fn define_input3<'i>(
     }
 }
 
+/// Defines a benchmark of `find` on a haystack of 1000 `a`s that starts
+/// `align` bytes after a word boundary, where `find` must return `None`.
+fn define_aligned(
+    c: &mut Criterion,
+    group_name: &str,
+    align: usize,
+    find: fn(&[u8]) -> Option<usize>,
+) {
+    const LEN: usize = 1000;
+
+    let word = ::core::mem::size_of::<usize>();
+    let buf = vec![b'a'; LEN + 2 * word];
+    let start = (word - buf.as_ptr() as usize % word) % word + align;
+    let tput = Throughput::Bytes(LEN as u32);
+    let benchmark = Benchmark::new(align.to_string(), move |b| {
+        let haystack = &buf[start..start + LEN];
+        assert_eq!(align, haystack.as_ptr() as usize % word);
+        b.iter(|| {
+            assert_eq!(None, find(haystack));
+        });
+    });
+    c.bench(group_name, benchmark.throughput(tput));
+}
+
+/// Generate about 4 MiB of log lines, the same on every run.
+///
+/// Most lines are between 40 and 200 bytes long, but one in 64 is a few
+/// kilobytes long, like a line holding a stack trace or a request body. One
+/// in 8 lines contains multi-byte UTF-8.
+fn log_corpus() -> Vec<u8> {
+    const LEN: usize = 4 << 20;
+
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % n as u64) as usize
+    };
+    let words: &[&str] =
+        &["GET", "/api/v1/users", "200", "OK", "latency_ms=", "user=", "ok"];
+    let mut corpus = Vec::with_capacity(LEN + 8192);
+    let mut line = 0;
+    while corpus.len() < LEN {
+        let timestamp = format!(
+            "2019-01-{:02}T12:{:02}:{:02}Z INFO ",
+            line % 28 + 1,
+            line % 60,
+            (line * 7) % 60,
+        );
+        corpus.extend_from_slice(timestamp.as_bytes());
+        let len =
+            if next(64) == 0 { 2048 + next(4096) } else { 40 + next(160) };
+        let end = corpus.len() + len;
+        while corpus.len() < end {
+            corpus.extend_from_slice(words[next(words.len())].as_bytes());
+            if next(8) == 0 {
+                let utf8 = "caf\u{e9} \u{65e5}\u{672c}";
+                corpus.extend_from_slice(utf8.as_bytes());
+            }
+            corpus.push(b' ');
+        }
+        corpus.push(b'\n');
+        line += 1;
+    }
+    corpus
+}
+
 fn define(
     c: &mut Criterion,
     group_name: &str,
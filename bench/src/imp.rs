@@ -1,13 +1,34 @@
+#[cfg(all(target_arch = "x86_64", memchr_runtime_avx512))]
+use avx512;
 #[cfg(target_arch = "x86_64")]
 use c;
 use fallback;
-use memchr::{Memchr, Memchr2, Memchr3, memrchr, memrchr2, memrchr3};
+use memchr::{
+    Finder, Memchr, Memchr2, Memchr3,
+    contains_byte, count, memchr, memchr0, memchr_early, memchr_not, memmem,
+    memrchr, memrchr2, memrchr3,
+};
 use naive;
+#[cfg(feature = "compare")]
+use regex::bytes::Regex;
 
 pub fn memchr1_count(b1: u8, haystack: &[u8]) -> usize {
     Memchr::new(b1, haystack).count()
 }
 
+#[cfg(all(target_arch = "x86_64", memchr_runtime_avx512))]
+pub fn memchr1_avx512_count(b1: u8, haystack: &[u8]) -> usize {
+    assert!(is_x86_feature_detected!("avx512f"));
+    assert!(is_x86_feature_detected!("avx512bw"));
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(i) = unsafe { avx512::memchr(b1, &haystack[start..]) } {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
 #[cfg(target_arch = "x86_64")]
 pub fn memchr1_libc_count(b1: u8, haystack: &[u8]) -> usize {
     let mut count = 0;
@@ -19,6 +40,24 @@ pub fn memchr1_libc_count(b1: u8, haystack: &[u8]) -> usize {
     count
 }
 
+pub fn memchr1_popcount(b1: u8, haystack: &[u8]) -> usize {
+    count(b1, haystack)
+}
+
+pub fn fallback1_popcount(b1: u8, haystack: &[u8]) -> usize {
+    fallback::count(b1, haystack)
+}
+
+pub fn fallback1_unroll4_count(b1: u8, haystack: &[u8]) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(i) = fallback::memchr_unroll4(b1, &haystack[start..]) {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
 pub fn fallback1_count(b1: u8, haystack: &[u8]) -> usize {
     let mut count = 0;
     let mut start = 0;
@@ -39,6 +78,60 @@ pub fn naive1_count(b1: u8, haystack: &[u8]) -> usize {
     count
 }
 
+pub fn memchr0_count(haystack: &[u8]) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(i) = memchr0(&haystack[start..]) {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
+pub fn fallback0_count(haystack: &[u8]) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(i) = fallback::memchr0(&haystack[start..]) {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
+pub fn memchr_not_trim(b1: u8, haystack: &[u8]) -> usize {
+    memchr_not(b1, haystack).unwrap_or(haystack.len())
+}
+
+pub fn fallback_not_trim(b1: u8, haystack: &[u8]) -> usize {
+    fallback::memchr_not(b1, haystack).unwrap_or(haystack.len())
+}
+
+pub fn std1_count(b1: u8, haystack: &[u8]) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(i) = haystack[start..].iter().position(|&b| b == b1) {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
+pub fn memchr1_find(b1: u8, haystack: &[u8]) -> bool {
+    memchr(b1, haystack).is_some()
+}
+
+pub fn memchr1_contains(b1: u8, haystack: &[u8]) -> bool {
+    contains_byte(b1, haystack)
+}
+
+pub fn std1_find(b1: u8, haystack: &[u8]) -> bool {
+    haystack.iter().position(|&b| b == b1).is_some()
+}
+
+pub fn std1_contains(b1: u8, haystack: &[u8]) -> bool {
+    haystack.contains(&b1)
+}
+
 pub fn memchr2_count(b1: u8, b2: u8, haystack: &[u8]) -> usize {
     Memchr2::new(b1, b2, haystack).count()
 }
@@ -97,6 +190,20 @@ pub fn memrchr1_count(b1: u8, haystack: &[u8]) -> usize {
     count
 }
 
+pub fn memrchr1_iter_count(b1: u8, haystack: &[u8]) -> usize {
+    Memchr::new(b1, haystack).rev().count()
+}
+
+pub fn std_rev1_count(b1: u8, haystack: &[u8]) -> usize {
+    let mut count = 0;
+    let mut end = haystack.len();
+    while let Some(i) = haystack[..end].iter().rposition(|&b| b == b1) {
+        count += 1;
+        end = i;
+    }
+    count
+}
+
 #[cfg(all(target_arch = "x86_64", target_os = "linux"))]
 pub fn memrchr1_libc_count(b1: u8, haystack: &[u8]) -> usize {
     let mut count = 0;
@@ -127,3 +234,57 @@ pub fn memrchr3_count(b1: u8, b2: u8, b3: u8, haystack: &[u8]) -> usize {
     }
     count
 }
+
+pub fn memmem_lines_count(needle: &[u8], haystack: &[u8]) -> usize {
+    haystack
+        .split(|&b| b == b'\n')
+        .filter(|line| memmem(needle, line).is_some())
+        .count()
+}
+
+pub fn finder_lines_count(needle: &[u8], haystack: &[u8]) -> usize {
+    let finder = Finder::new(needle);
+    haystack
+        .split(|&b| b == b'\n')
+        .filter(|line| finder.find(line).is_some())
+        .count()
+}
+
+pub fn finder_count(needle: &[u8], haystack: &[u8]) -> usize {
+    Finder::new(needle).find_iter(haystack).count()
+}
+
+pub fn naive_memmem_count(needle: &[u8], haystack: &[u8]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while let Some(j) =
+        haystack[i..].windows(needle.len()).position(|w| w == needle)
+    {
+        count += 1;
+        i += j + needle.len();
+    }
+    count
+}
+
+#[cfg(feature = "compare")]
+pub fn regex_count(re: &Regex, haystack: &[u8]) -> usize {
+    re.find_iter(haystack).count()
+}
+
+pub fn memchr1_find_all(b1: u8, haystack: &[u8]) -> usize {
+    let (mut count, mut start) = (0, 0);
+    while let Some(i) = memchr(b1, &haystack[start..]) {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
+pub fn memchr_early_find_all(b1: u8, haystack: &[u8]) -> usize {
+    let (mut count, mut start) = (0, 0);
+    while let Some(i) = memchr_early(b1, &haystack[start..]) {
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
@@ -6,6 +6,7 @@ use version_check::is_min_version;
 
 fn main() {
     enable_simd_optimizations();
+    enable_maybe_uninit();
 }
 
 fn enable_simd_optimizations() {
@@ -18,8 +19,22 @@ fn enable_simd_optimizations() {
 
     println!("cargo:rustc-cfg=memchr_runtime_simd");
     println!("cargo:rustc-cfg=memchr_runtime_sse2");
+    println!("cargo:rustc-cfg=memchr_runtime_ssse3");
     println!("cargo:rustc-cfg=memchr_runtime_sse42");
     println!("cargo:rustc-cfg=memchr_runtime_avx");
+
+    // The AVX-512 intrinsics were stabilized much later than the others.
+    if is_min_version("1.89.0").map(|(yes, _)| yes).unwrap_or(false) {
+        println!("cargo:rustc-cfg=memchr_runtime_avx512");
+    }
+}
+
+// `MaybeUninit` was stabilized in Rust 1.36, after the minimum version that
+// this crate supports.
+fn enable_maybe_uninit() {
+    if is_min_version("1.36.0").map(|(yes, _)| yes).unwrap_or(false) {
+        println!("cargo:rustc-cfg=memchr_maybe_uninit");
+    }
 }
 
 fn is_env_set(name: &str) -> bool {
@@ -43,7 +43,7 @@ cargo doc --target "$TARGET" --verbose
 # more frequently, and therefore might require a newer version of Rust.
 #
 # This isn't ideal. It's a compromise.
-if [ "$TRAVIS_RUST_VERSION" = "1.13.0" ]; then
+if [ "$TRAVIS_RUST_VERSION" = "1.34.0" ]; then
   exit
 fi
 
@@ -62,9 +62,27 @@ if is_x86_64; then
   # Force use of sse2 only
   RUSTFLAGS="$preamble --cfg memchr_runtime_sse2" \
     cargo test --target "$TARGET" --verbose
+  # Force use of ssse3 only
+  RUSTFLAGS="$preamble --cfg memchr_runtime_ssse3" \
+    cargo test --target "$TARGET" --verbose
   # Force use of avx only
   RUSTFLAGS="$preamble --cfg memchr_runtime_avx" \
     cargo test --target "$TARGET" --verbose
+
+  # Enable AVX2 at compile time, which skips runtime detection. This is only
+  # possible when the CPU running the tests supports AVX2.
+  if grep -q avx2 /proc/cpuinfo 2> /dev/null; then
+    RUSTFLAGS="-C target-feature=+avx2" \
+      cargo test --target "$TARGET" --verbose
+  fi
+fi
+if [[ "$TRAVIS_RUST_VERSION" = "nightly" ]]; then
+  cargo test --target "$TARGET" --verbose --features portable_simd
+fi
+# cargo fuzz needs nightly, but checking that the fuzz targets still compile
+# doesn't require running them.
+if [[ "$TRAVIS_RUST_VERSION" = "nightly" ]] && [[ "$TRAVIS_OS_NAME" = "linux" ]]; then
+  cargo check --manifest-path fuzz/Cargo.toml --verbose
 fi
 if [[ "$TRAVIS_RUST_VERSION" = "nightly" ]] && is_x86_64 && [[ "$TRAVIS_OS_NAME" = "linux" ]]; then
   cargo bench \
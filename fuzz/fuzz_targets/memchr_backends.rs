@@ -0,0 +1,62 @@
+// This runs every memchr kernel that is available on the fuzzing host over
+// the same input, and checks that they all agree with each other and with a
+// naive search. Comparing the kernels with each other, and not just with the
+// naive search, catches a kernel that is wrong in a way that the naive
+// search happens to share. The other searches (memchr2, memchr3, memrchr,
+// memrchr2 and memrchr3) are checked against a naive search too, using
+// whichever kernel they dispatch to on the fuzzing host.
+//
+// The first three bytes of the input are the needles, and the fourth picks
+// how far into the rest of the input the haystack starts, so that every
+// alignment is exercised.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate memchr;
+
+use memchr::{
+    KernelId, kernel_available, memchr2, memchr3, memchr_kernel, memrchr,
+    memrchr2, memrchr3,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+    let (n1, n2, n3) = (data[0], data[1], data[2]);
+    let rest = &data[4..];
+    let haystack = &rest[(data[3] as usize % 64).min(rest.len())..];
+
+    let expected = haystack.iter().position(|&b| b == n1);
+    let reference = memchr_kernel(KernelId::Swar, n1, haystack);
+    assert_eq!(expected, reference, "Swar disagrees with the naive search");
+    for &kernel in KernelId::ALL {
+        if !kernel_available(kernel) {
+            continue;
+        }
+        let got = memchr_kernel(kernel, n1, haystack);
+        assert_eq!(reference, got, "{:?} disagrees with Swar", kernel);
+    }
+    assert_eq!(reference, memchr::memchr(n1, haystack));
+
+    let is2 = |b: u8| b == n1 || b == n2;
+    let is3 = |b: u8| b == n1 || b == n2 || b == n3;
+    assert_eq!(
+        haystack.iter().position(|&b| is2(b)),
+        memchr2(n1, n2, haystack),
+    );
+    assert_eq!(
+        haystack.iter().position(|&b| is3(b)),
+        memchr3(n1, n2, n3, haystack),
+    );
+    assert_eq!(haystack.iter().rposition(|&b| b == n1), memrchr(n1, haystack));
+    assert_eq!(
+        haystack.iter().rposition(|&b| is2(b)),
+        memrchr2(n1, n2, haystack),
+    );
+    assert_eq!(
+        haystack.iter().rposition(|&b| is3(b)),
+        memrchr3(n1, n2, n3, haystack),
+    );
+});
@@ -0,0 +1,212 @@
+// This module defines a set of bytes and routines for searching for any byte
+// in such a set. This generalizes memchr2 and memchr3 to an arbitrary number
+// of needle bytes.
+
+use fallback::{HI_USIZE, LO_USIZE};
+use memchr_pred;
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+use x86;
+
+/// A set of bytes, for use with `memchr_byteset`.
+///
+/// A `ByteSet` is a 256-bit bitmap and is cheap to copy. Building a set is
+/// done byte by byte, so it is a good idea to build it once and reuse it
+/// across many searches.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, memchr_byteset};
+///
+/// let set = ByteSet::from_bytes(b" \t\r\n");
+/// assert!(set.contains(b'\t'));
+/// assert_eq!(memchr_byteset(&set, b"foo\tbar baz"), Some(3));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ByteSet {
+    // The membership bit for a byte `b` is stored in the byte at index
+    // `(b & 0x0F) | 16 * (b >> 7)`, at bit position `(b >> 4) & 7`. In other
+    // words, the low nibble of a byte selects one of 16 rows and the high
+    // nibble selects a bit in that row, with the rows for high nibbles 8-15
+    // stored in the second half.
+    //
+    // This looks a bit odd, but it is precisely the layout that's needed by
+    // the SIMD membership test (see the `x86::ssse3` module), which can look
+    // up 16 rows at once with a single shuffle. The scalar membership test
+    // doesn't care one way or the other.
+    bits: [u8; 32],
+}
+
+impl ByteSet {
+    /// Create a new empty set of bytes.
+    #[inline]
+    pub fn new() -> ByteSet {
+        ByteSet { bits: [0; 32] }
+    }
+
+    /// Create a new set containing each of the given bytes.
+    pub fn from_bytes(bytes: &[u8]) -> ByteSet {
+        let mut set = ByteSet::new();
+        for &b in bytes {
+            set.add(b);
+        }
+        set
+    }
+
+    /// Add a byte to this set.
+    #[inline]
+    pub fn add(&mut self, byte: u8) {
+        let (i, bit) = ByteSet::position(byte);
+        self.bits[i] |= bit;
+    }
+
+    /// Remove a byte from this set.
+    #[inline]
+    pub fn remove(&mut self, byte: u8) {
+        let (i, bit) = ByteSet::position(byte);
+        self.bits[i] &= !bit;
+    }
+
+    /// Returns true if and only if the given byte is in this set.
+    #[inline]
+    pub fn contains(&self, byte: u8) -> bool {
+        table_contains(&self.bits, byte)
+    }
+
+    /// Returns the number of bytes in this set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Returns true if and only if this set contains no bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&b| b == 0)
+    }
+
+    /// Returns the index into `bits` and the bit in the byte at that index
+    /// that represent membership of `byte`.
+    #[inline(always)]
+    fn position(byte: u8) -> (usize, u8) {
+        let i = (byte & 0x0F) as usize | ((byte >> 7) as usize) << 4;
+        (i, 1 << ((byte >> 4) & 7))
+    }
+}
+
+/// Returns true if and only if `byte` is in the set represented by the given
+/// bitmap, which must use the same layout as `ByteSet`.
+#[inline(always)]
+pub fn table_contains(bits: &[u8; 32], byte: u8) -> bool {
+    let (i, bit) = ByteSet::position(byte);
+    bits[i] & bit != 0
+}
+
+/// Search for the first occurrence of any byte in a set.
+///
+/// This returns the index corresponding to the first byte in `haystack` that
+/// is contained in `set`, or `None` if one is not found.
+///
+/// On `x86_64` CPUs that support SSSE3, membership of 16 bytes is tested at
+/// once, independent of how many bytes are in the set.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, memchr_byteset};
+///
+/// let set = ByteSet::from_bytes(b"aeiou");
+/// assert_eq!(memchr_byteset(&set, b"rhythm and blues"), Some(7));
+/// ```
+#[inline]
+pub fn memchr_byteset(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(bits: &[u8; 32], haystack: &[u8]) -> Option<usize> {
+                x86::memchr_byteset(bits, haystack)
+            }
+        } else {
+            #[inline(always)]
+            fn imp(bits: &[u8; 32], haystack: &[u8]) -> Option<usize> {
+                memchr_table_fallback(bits, haystack)
+            }
+        }
+    }
+    imp(&set.bits, haystack)
+}
+
+/// Search for the first byte that is not in a set.
+///
+/// This returns the index corresponding to the first byte in `haystack` that
+/// is not contained in `set`, or `None` if every byte is in `set`. This
+/// generalizes `memchr_not` to sets and is useful for skipping over a prefix
+/// made up of bytes from a set, like leading whitespace.
+///
+/// Since every byte is either in a set or in its complement, this is the same
+/// as `memchr_byteset` on the complement of `set`, and has the same
+/// performance characteristics.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, memchr_not_byteset};
+///
+/// let set = ByteSet::from_bytes(b" \t");
+/// assert_eq!(memchr_not_byteset(&set, b" \t foo"), Some(3));
+/// assert_eq!(memchr_not_byteset(&set, b"\t\t"), None);
+/// ```
+#[inline]
+pub fn memchr_not_byteset(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    // Every byte is represented by exactly one bit in the bitmap, so flipping
+    // every bit flips the membership of every byte.
+    let mut complement = set.bits;
+    for row in complement.iter_mut() {
+        *row = !*row;
+    }
+    memchr_byteset(&ByteSet { bits: complement }, haystack)
+}
+
+/// Search for the first ASCII control byte that isn't allowed.
+///
+/// This returns the index of the first byte in `haystack` that is less than
+/// `0x20` and not contained in `allow`, or `None` if there is no such byte.
+/// This is useful for rejecting untrusted input that contains control
+/// characters other than a few expected ones, like tab and newline. Note
+/// that `0x7F` (DEL) is not considered a control byte here.
+///
+/// Each word of `haystack` is first checked for any byte less than `0x20`,
+/// so only words that contain a control byte are checked against `allow`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, first_forbidden_control};
+///
+/// let allow = ByteSet::from_bytes(b"\t\n");
+/// assert_eq!(first_forbidden_control(&allow, b"a\tb\nc"), None);
+/// assert_eq!(first_forbidden_control(&allow, b"a\tb\x07c"), Some(3));
+/// ```
+#[inline]
+pub fn first_forbidden_control(
+    allow: &ByteSet,
+    haystack: &[u8],
+) -> Option<usize> {
+    // Subtracting `0x20` from a byte less than `0x20` borrows, which sets
+    // its high bit, while `!x` rules out bytes that had their high bit set
+    // to begin with. A borrow can also set the high bit of the byte after a
+    // control byte, but there's never a borrow without one, so this never
+    // misses a word that has one.
+    let has_control =
+        |x: usize| x.wrapping_sub(LO_USIZE * 0x20) & !x & HI_USIZE != 0;
+    memchr_pred(has_control, |b| b < 0x20 && !allow.contains(b), haystack)
+}
+
+/// Search for the first occurrence of any byte in the set represented by the
+/// given bitmap, one byte at a time.
+pub fn memchr_table_fallback(
+    bits: &[u8; 32],
+    haystack: &[u8],
+) -> Option<usize> {
+    haystack.iter().position(|&b| table_contains(bits, b))
+}
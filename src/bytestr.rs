@@ -0,0 +1,151 @@
+use {contains_byte, memchr, memrchr};
+
+/// A byte string with searching methods that mirror those on `str`.
+///
+/// This is a thin wrapper around a byte slice. Each method delegates to the
+/// free functions in this crate, which remain the canonical way to search;
+/// `ByteStr` exists to make code that was written against `str` easier to
+/// port to bytes that may not be valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// use memchr::ByteStr;
+///
+/// assert_eq!(ByteStr(b"a,b,c").split(b',').count(), 3);
+/// assert_eq!(ByteStr(b"a,b,c").rfind(b','), Some(3));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ByteStr<'a>(pub &'a [u8]);
+
+impl<'a> ByteStr<'a> {
+    /// Return the index of the first occurrence of `needle`.
+    ///
+    /// This is the same as `memchr(needle, self.0)`.
+    #[inline]
+    pub fn find(self, needle: u8) -> Option<usize> {
+        memchr(needle, self.0)
+    }
+
+    /// Return the index of the last occurrence of `needle`.
+    ///
+    /// This is the same as `memrchr(needle, self.0)`.
+    #[inline]
+    pub fn rfind(self, needle: u8) -> Option<usize> {
+        memrchr(needle, self.0)
+    }
+
+    /// Returns true if and only if `needle` occurs in this byte string.
+    ///
+    /// This is the same as `contains_byte(needle, self.0)`.
+    #[inline]
+    pub fn contains(self, needle: u8) -> bool {
+        contains_byte(needle, self.0)
+    }
+
+    /// Return an iterator over the pieces of this byte string that are
+    /// separated by `needle`.
+    ///
+    /// Like `str::split`, this yields `n + 1` pieces when `needle` occurs `n`
+    /// times, so an empty byte string yields a single empty piece.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::ByteStr;
+    ///
+    /// let pieces: Vec<&[u8]> = ByteStr(b"a,,b,").split(b',').collect();
+    /// assert_eq!(pieces, vec![&b"a"[..], b"", b"b", b""]);
+    /// ```
+    #[inline]
+    pub fn split(self, needle: u8) -> Split<'a> {
+        Split { needle, haystack: self.0, finished: false }
+    }
+
+    /// Return an iterator over the lines of this byte string.
+    ///
+    /// Like `str::lines`, lines are terminated by either `\n` or `\r\n`,
+    /// the terminator is not included in each line and a trailing empty line
+    /// is not yielded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::ByteStr;
+    ///
+    /// let text = ByteStr(b"foo\r\nbar\n\nbaz\n");
+    /// let lines: Vec<&[u8]> = text.lines().collect();
+    /// assert_eq!(lines, vec![&b"foo"[..], b"bar", b"", b"baz"]);
+    /// ```
+    #[inline]
+    pub fn lines(self) -> Lines<'a> {
+        Lines { haystack: self.0 }
+    }
+}
+
+/// An iterator over the pieces of a byte string separated by a byte.
+///
+/// This is created by `ByteStr::split`.
+#[derive(Clone, Debug)]
+pub struct Split<'a> {
+    needle: u8,
+    haystack: &'a [u8],
+    finished: bool,
+}
+
+impl<'a> Iterator for Split<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.finished {
+            return None;
+        }
+        match memchr(self.needle, self.haystack) {
+            None => {
+                self.finished = true;
+                Some(self.haystack)
+            }
+            Some(i) => {
+                let piece = &self.haystack[..i];
+                self.haystack = &self.haystack[i + 1..];
+                Some(piece)
+            }
+        }
+    }
+}
+
+/// An iterator over the lines of a byte string.
+///
+/// This is created by `ByteStr::lines`.
+#[derive(Clone, Debug)]
+pub struct Lines<'a> {
+    haystack: &'a [u8],
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.haystack.is_empty() {
+            return None;
+        }
+        let line = match memchr(b'\n', self.haystack) {
+            None => {
+                let line = self.haystack;
+                self.haystack = &[];
+                return Some(line);
+            }
+            Some(i) => {
+                let line = &self.haystack[..i];
+                self.haystack = &self.haystack[i + 1..];
+                line
+            }
+        };
+        match line.split_last() {
+            Some((&b'\r', init)) => Some(init),
+            _ => Some(line),
+        }
+    }
+}
@@ -8,6 +8,9 @@ extern crate libc;
 use self::libc::{c_int, c_void, size_t};
 
 pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    // `size_t` has the same width as `usize` on every platform we support,
+    // so neither the length passed in nor the offset computed from the
+    // returned pointer can be truncated, even for haystacks over 4 GiB.
     let p = unsafe {
         libc::memchr(
             haystack.as_ptr() as *const c_void,
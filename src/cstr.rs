@@ -0,0 +1,43 @@
+// This module defines helpers for inspecting NUL terminated C strings that
+// live inside of byte buffers.
+
+use core::slice;
+
+use memchr0;
+
+/// Return the length of the C string at the start of `haystack`.
+///
+/// The length returned is the index of the first NUL byte in `haystack`,
+/// which does not include the NUL terminator itself. If `haystack` does not
+/// contain a NUL byte, then the C string is unterminated within the buffer
+/// and `None` is returned.
+///
+/// # Example
+///
+/// ```
+/// use memchr::cstr_len;
+///
+/// assert_eq!(cstr_len(b"foo\x00bar"), Some(3));
+/// assert_eq!(cstr_len(b"foo"), None);
+/// ```
+#[inline]
+pub fn cstr_len(haystack: &[u8]) -> Option<usize> {
+    memchr0(haystack)
+}
+
+/// Return the length of the C string pointed to by `ptr`, looking at no more
+/// than `max` bytes.
+///
+/// This is like `libc::strlen`, except that it never reads past `max` bytes
+/// and returns `None` if no NUL terminator is found within that bound.
+///
+/// # Safety
+///
+/// `ptr` must be non-null and valid for reads of `max` bytes, even if the
+/// NUL terminator occurs before `max`. Those bytes must not be mutated for
+/// the duration of the call. `max` must be no greater than `isize::MAX`.
+#[inline]
+pub unsafe fn cstr_len_raw(ptr: *const u8, max: usize) -> Option<usize> {
+    debug_assert!(!ptr.is_null());
+    cstr_len(slice::from_raw_parts(ptr, max))
+}
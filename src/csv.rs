@@ -0,0 +1,90 @@
+// This module defines a minimal CSV field splitter, which shows how memchr2
+// can be used to jump between the bytes that are significant to a parser.
+
+use {memchr, memchr2};
+
+/// Return an iterator over the fields of a single CSV record.
+///
+/// Fields are separated by commas, except for commas that occur inside of
+/// double quotes. A field that both starts and ends with a double quote has
+/// those quotes removed, but an escaped quote (`""`) inside of it is
+/// yielded as is, since a slice of `haystack` can't unescape it. Like
+/// `ByteStr::split`, a record with `n` separators always yields `n + 1`
+/// fields, so an empty record yields a single empty field.
+///
+/// This is not a full CSV parser. In particular, `haystack` is treated as a
+/// single record, so newlines are not special. A quote that is never
+/// closed extends to the end of the record.
+///
+/// # Example
+///
+/// ```
+/// use memchr::csv_fields;
+///
+/// let fields: Vec<&[u8]> =
+///     csv_fields(br#"a,"b,c","say ""hi""",d"#).collect();
+/// assert_eq!(fields, vec![&b"a"[..], b"b,c", br#"say ""hi"""#, b"d"]);
+/// ```
+#[inline]
+pub fn csv_fields<'a>(haystack: &'a [u8]) -> CsvFields<'a> {
+    CsvFields { haystack, finished: false }
+}
+
+/// An iterator over the fields of a single CSV record.
+///
+/// This is created by `csv_fields`.
+#[derive(Clone, Debug)]
+pub struct CsvFields<'a> {
+    haystack: &'a [u8],
+    finished: bool,
+}
+
+impl<'a> CsvFields<'a> {
+    /// Return the length of the field at the start of the haystack, i.e.,
+    /// the index of the first comma that isn't quoted, or the length of the
+    /// haystack if there isn't one.
+    fn field_len(&self) -> usize {
+        let haystack = self.haystack;
+        let mut i = 0;
+        loop {
+            match memchr2(b',', b'"', &haystack[i..]) {
+                None => return haystack.len(),
+                Some(j) if haystack[i + j] == b',' => return i + j,
+                Some(j) => i += j + 1,
+            }
+            // The quote just passed opens a quoted section, so skip to the
+            // quote that closes it. Escaped quotes need no special handling,
+            // since each `""` closes a quoted section and opens another.
+            match memchr(b'"', &haystack[i..]) {
+                None => return haystack.len(),
+                Some(j) => i += j + 1,
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for CsvFields<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.finished {
+            return None;
+        }
+        let len = self.field_len();
+        let field = &self.haystack[..len];
+        if len == self.haystack.len() {
+            self.finished = true;
+        } else {
+            self.haystack = &self.haystack[len + 1..];
+        }
+        if field.len() >= 2
+            && field[0] == b'"'
+            && field[field.len() - 1] == b'"'
+        {
+            Some(&field[1..field.len() - 1])
+        } else {
+            Some(field)
+        }
+    }
+}
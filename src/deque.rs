@@ -0,0 +1,37 @@
+// This module defines conveniences for searching ring buffers, whose
+// contents are split across two slices.
+
+use std::collections::VecDeque;
+
+use memchr;
+
+/// Search for the first occurrence of a byte in a `VecDeque`.
+///
+/// This returns the logical index of the first occurrence of `needle` in
+/// `deque`, that is, an index that can be passed to `deque[i]`, or `None` if
+/// one is not found. The two slices returned by `VecDeque::as_slices` are
+/// searched in order, and a match in the second slice is offset by the
+/// length of the first. Since the needle is a single byte, a match can't
+/// straddle the boundary between the two.
+///
+/// This is only available with the `use_std` feature.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::VecDeque;
+/// use memchr::memchr_deque;
+///
+/// let mut deque: VecDeque<u8> = b"\nbar".iter().cloned().collect();
+/// // These may wrap around to the end of the ring buffer.
+/// deque.push_front(b'o');
+/// deque.push_front(b'o');
+/// deque.push_front(b'f');
+/// assert_eq!(memchr_deque(b'\n', &deque), Some(3));
+/// ```
+#[inline]
+pub fn memchr_deque(needle: u8, deque: &VecDeque<u8>) -> Option<usize> {
+    let (front, back) = deque.as_slices();
+    memchr(needle, front)
+        .or_else(|| memchr(needle, back).map(|i| front.len() + i))
+}
@@ -0,0 +1,41 @@
+use {memchr, memrchr};
+
+/// An extension trait that adds byte searching methods to byte slices.
+///
+/// This is implemented for `[u8]`, which makes its methods available on
+/// anything that dereferences to a byte slice, such as `Vec<u8>`. Each method
+/// delegates to the corresponding free function in this crate.
+///
+/// # Example
+///
+/// ```
+/// use memchr::MemchrExt;
+///
+/// let buf = b"foo\nbar\n".to_vec();
+/// assert_eq!(buf.find_byte(b'\n'), Some(3));
+/// assert_eq!(buf.rfind_byte(b'\n'), Some(7));
+/// assert_eq!(buf.find_byte(b'z'), None);
+/// ```
+pub trait MemchrExt {
+    /// Return the index of the first occurrence of `b`.
+    ///
+    /// This is the same as `memchr(b, self)`.
+    fn find_byte(&self, b: u8) -> Option<usize>;
+
+    /// Return the index of the last occurrence of `b`.
+    ///
+    /// This is the same as `memrchr(b, self)`.
+    fn rfind_byte(&self, b: u8) -> Option<usize>;
+}
+
+impl MemchrExt for [u8] {
+    #[inline]
+    fn find_byte(&self, b: u8) -> Option<usize> {
+        memchr(b, self)
+    }
+
+    #[inline]
+    fn rfind_byte(&self, b: u8) -> Option<usize> {
+        memrchr(b, self)
+    }
+}
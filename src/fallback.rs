@@ -15,6 +15,17 @@ const USIZE_BYTES: usize = 8;
 // The number of bytes to loop at in one iteration of memchr/memrchr.
 const LOOP_SIZE: usize = 2 * USIZE_BYTES;
 
+// The number of bytes to loop at in one iteration of memchr_unroll4.
+const LOOP_SIZE4: usize = 4 * USIZE_BYTES;
+
+const LO_U64: u64 = 0x0101010101010101;
+const HI_U64: u64 = 0x8080808080808080;
+
+// A word with the least significant bit of every byte set.
+pub(crate) const LO_USIZE: usize = LO_U64 as usize;
+// A word with the most significant bit of every byte set.
+pub(crate) const HI_USIZE: usize = HI_U64 as usize;
+
 /// Return `true` if `x` contains any zero byte.
 ///
 /// From *Matters Computational*, J. Arndt
@@ -24,13 +35,32 @@ const LOOP_SIZE: usize = 2 * USIZE_BYTES;
 /// bit."
 #[inline(always)]
 fn contains_zero_byte(x: usize) -> bool {
-    const LO_U64: u64 = 0x0101010101010101;
-    const HI_U64: u64 = 0x8080808080808080;
+    x.wrapping_sub(LO_USIZE) & !x & HI_USIZE != 0
+}
 
-    const LO_USIZE: usize = LO_U64 as usize;
-    const HI_USIZE: usize = HI_U64 as usize;
+/// Return a word with the most significant bit of a byte set if and only if
+/// the corresponding byte in `x` is zero.
+///
+/// Unlike `contains_zero_byte`, this is exact for every byte in `x`, since
+/// clearing the most significant bit of each byte first means no carry can
+/// propagate from one byte into the next.
+#[inline(always)]
+fn zero_byte_mask(x: usize) -> usize {
+    let lo7 = !HI_USIZE;
+    !((x & lo7).wrapping_add(lo7) | x | lo7)
+}
 
-    x.wrapping_sub(LO_USIZE) & !x & HI_USIZE != 0
+/// Return `true` if every byte in `x` is ASCII whitespace, i.e., one of
+/// `\t`, `\n`, `\r` or ` `.
+///
+/// Since `zero_byte_mask` is exact, so is this.
+#[inline(always)]
+pub fn all_ascii_whitespace(x: usize) -> bool {
+    let ws = zero_byte_mask(x ^ repeat_byte(b' '))
+        | zero_byte_mask(x ^ repeat_byte(b'\t'))
+        | zero_byte_mask(x ^ repeat_byte(b'\n'))
+        | zero_byte_mask(x ^ repeat_byte(b'\r'));
+    ws == HI_USIZE
 }
 
 /// Repeat the given byte into a word size number. That is, every 8 bits
@@ -43,9 +73,158 @@ fn repeat_byte(b: u8) -> usize {
 }
 
 pub fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    memchr_tallied(n1, haystack, &mut ())
+}
+
+/// The work done by `memchr_counted`.
+#[cfg(feature = "profiling")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Counts {
+    /// The bytes examined one at a time before the first aligned word.
+    pub head_bytes: usize,
+    /// The aligned words examined by the main loop.
+    pub words: usize,
+    /// The bytes examined one at a time after the main loop.
+    pub tail_bytes: usize,
+}
+
+/// Like `memchr`, but also counts the bytes and words that it examines.
+#[cfg(feature = "profiling")]
+pub fn memchr_counted(n1: u8, haystack: &[u8]) -> (Option<usize>, Counts) {
+    let mut counts = Counts::default();
+    let found = memchr_tallied(n1, haystack, &mut counts);
+    (found, counts)
+}
+
+// Receives the work done in each phase of `memchr_tallied`. `memchr` passes
+// `()`, for which every call compiles to nothing.
+trait Tally {
+    fn head(&mut self, bytes: usize);
+    fn words(&mut self, words: usize);
+    fn tail(&mut self, bytes: usize);
+}
+
+impl Tally for () {
+    #[inline(always)]
+    fn head(&mut self, _: usize) {}
+    #[inline(always)]
+    fn words(&mut self, _: usize) {}
+    #[inline(always)]
+    fn tail(&mut self, _: usize) {}
+}
+
+#[cfg(feature = "profiling")]
+impl Tally for Counts {
+    #[inline(always)]
+    fn head(&mut self, bytes: usize) {
+        self.head_bytes += bytes;
+    }
+    #[inline(always)]
+    fn words(&mut self, words: usize) {
+        self.words += words;
+    }
+    #[inline(always)]
+    fn tail(&mut self, bytes: usize) {
+        self.tail_bytes += bytes;
+    }
+}
+
+#[inline(always)]
+fn memchr_tallied<T: Tally>(
+    n1: u8,
+    haystack: &[u8],
+    tally: &mut T,
+) -> Option<usize> {
+    let vn1 = repeat_byte(n1);
+    let confirm = |&byte: &u8| byte == n1;
+    // Splitting off the aligned words in the middle of the haystack lets us
+    // read them without any pointer arithmetic. This is sound because every
+    // bit pattern is a valid `usize`.
+    let (head, words, _) = unsafe { haystack.align_to::<usize>() };
+    let found = head.iter().position(confirm);
+    tally.head(found.map_or(head.len(), |i| i + 1));
+    if found.is_some() {
+        return found;
+    }
+
+    let mut at = head.len();
+    for pair in words.chunks_exact(2) {
+        tally.words(2);
+        let eqa = contains_zero_byte(pair[0] ^ vn1);
+        let eqb = contains_zero_byte(pair[1] ^ vn1);
+        if eqa || eqb {
+            break;
+        }
+        at += LOOP_SIZE;
+    }
+    let found = haystack[at..].iter().position(confirm);
+    tally.tail(found.map_or(haystack.len() - at, |i| i + 1));
+    found.map(|i| at + i)
+}
+
+/// A haystack split into an unaligned head, aligned words and an unaligned
+/// tail, so that many searches can share the split.
+#[derive(Clone, Copy)]
+pub struct Split<'a> {
+    haystack: &'a [u8],
+    // The length of the unaligned head, which is where `words` starts.
+    head: usize,
+    words: &'a [usize],
+}
+
+impl<'a> Split<'a> {
+    pub fn new(haystack: &'a [u8]) -> Split<'a> {
+        // This is sound for the same reason as in `memchr_tallied`.
+        let (head, words, _) = unsafe { haystack.align_to::<usize>() };
+        Split { haystack, head: head.len(), words }
+    }
+
+    pub fn haystack(&self) -> &'a [u8] {
+        self.haystack
+    }
+
+    /// Like `memchr(n1, &haystack[start..])`, but the index returned is
+    /// relative to the start of the entire haystack. `start` must not be
+    /// greater than the length of the haystack.
+    pub fn memchr_from(&self, n1: u8, start: usize) -> Option<usize> {
+        let confirm = |&byte: &u8| byte == n1;
+        // The bytes from `start` up to the first word that starts at or
+        // after it are searched one at a time, as is everything after the
+        // last word.
+        let first = if start <= self.head {
+            0
+        } else {
+            let past = start - self.head;
+            let first = past / USIZE_BYTES + cmp::min(1, past % USIZE_BYTES);
+            cmp::min(self.words.len(), first)
+        };
+        let mut at = cmp::max(start, self.head + first * USIZE_BYTES);
+        if let Some(i) = self.haystack[start..at].iter().position(confirm) {
+            return Some(start + i);
+        }
+        let vn1 = repeat_byte(n1);
+        for &word in &self.words[first..] {
+            if contains_zero_byte(word ^ vn1) {
+                break;
+            }
+            at += USIZE_BYTES;
+        }
+        self.haystack[at..].iter().position(confirm).map(|i| at + i)
+    }
+}
+
+/// Like `memchr`, but reads four words per iteration instead of two.
+///
+/// A wider unroll can hide more load latency on some out-of-order cores,
+/// but it also means more work before the loop is entered and more words
+/// to re-scan once a match is spotted. On the x86_64 machine this was
+/// benchmarked on, it lost to `memchr` on large haystacks, so it is only
+/// used when the `fallback_unroll4` feature is enabled. See
+/// `memchr1/fallback4` in the benchmark suite.
+pub fn memchr_unroll4(n1: u8, haystack: &[u8]) -> Option<usize> {
     let vn1 = repeat_byte(n1);
     let confirm = |byte| byte == n1;
-    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let loop_size = cmp::min(LOOP_SIZE4, haystack.len());
     let align = USIZE_BYTES - 1;
     let start_ptr = haystack.as_ptr();
     let end_ptr = haystack[haystack.len()..].as_ptr();
@@ -61,20 +240,298 @@ pub fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
             return forward_search(start_ptr, end_ptr, ptr, confirm);
         }
 
-        ptr = ptr_add(ptr, USIZE_BYTES - (start_ptr as usize & align));
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
         debug_assert!(ptr > start_ptr);
-        debug_assert!(ptr_sub(end_ptr, USIZE_BYTES) >= start_ptr);
-        while loop_size == LOOP_SIZE && ptr <= ptr_sub(end_ptr, loop_size) {
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE4 && ptr <= end_ptr.sub(loop_size) {
             debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
 
             let a = *(ptr as *const usize);
-            let b = *(ptr_add(ptr, USIZE_BYTES) as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            let c = *(ptr.add(2 * USIZE_BYTES) as *const usize);
+            let d = *(ptr.add(3 * USIZE_BYTES) as *const usize);
             let eqa = contains_zero_byte(a ^ vn1);
             let eqb = contains_zero_byte(b ^ vn1);
+            let eqc = contains_zero_byte(c ^ vn1);
+            let eqd = contains_zero_byte(d ^ vn1);
+            if eqa || eqb || eqc || eqd {
+                break;
+            }
+            ptr = ptr.add(LOOP_SIZE4);
+        }
+        forward_search(start_ptr, end_ptr, ptr, confirm)
+    }
+}
+
+/// Returns true if and only if `n1` occurs in `haystack`.
+///
+/// Once a match is spotted in a word, the fallback memchr only needs to look
+/// at a handful of bytes to find its position, so there isn't anything to
+/// gain by skipping that here.
+pub fn contains_byte(n1: u8, haystack: &[u8]) -> bool {
+    memchr(n1, haystack).is_some()
+}
+
+/// Return the number of occurrences of `n1` in `haystack`.
+pub fn count(n1: u8, haystack: &[u8]) -> usize {
+    let vn1 = repeat_byte(n1);
+    let start_ptr = haystack.as_ptr();
+    let mut count = 0;
+    let mut i = 0;
+    while haystack.len() - i >= USIZE_BYTES {
+        let chunk = unsafe { read_unaligned_usize(start_ptr.add(i)) };
+        count += zero_byte_mask(chunk ^ vn1).count_ones() as usize;
+        i += USIZE_BYTES;
+    }
+    count + haystack[i..].iter().filter(|&&b| b == n1).count()
+}
+
+/// Return the index of the first byte in `haystack` that is not `n1`.
+pub fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = repeat_byte(n1);
+    let confirm = |byte| byte != n1;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    unsafe {
+        if haystack.len() < USIZE_BYTES {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        let chunk = read_unaligned_usize(ptr);
+        if chunk != vn1 {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
+        debug_assert!(ptr > start_ptr);
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            if (a ^ vn1) | (b ^ vn1) != 0 {
+                break;
+            }
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        forward_search(start_ptr, end_ptr, ptr, confirm)
+    }
+}
+
+/// Return the index of the first byte in `haystack` that satisfies `fine`,
+/// skipping every word for which `coarse` returns `false`.
+pub fn memchr_pred<C, F>(coarse: C, fine: F, haystack: &[u8]) -> Option<usize>
+where
+    C: Fn(usize) -> bool,
+    F: Fn(u8) -> bool,
+{
+    let start_ptr = haystack.as_ptr();
+    let mut i = 0;
+    while haystack.len() - i >= USIZE_BYTES {
+        let chunk = unsafe { read_unaligned_usize(start_ptr.add(i)) };
+        if coarse(chunk) {
+            let word = &haystack[i..i + USIZE_BYTES];
+            if let Some(j) = word.iter().position(|&b| fine(b)) {
+                return Some(i + j);
+            }
+        }
+        i += USIZE_BYTES;
+    }
+    haystack[i..].iter().position(|&b| fine(b)).map(|j| i + j)
+}
+
+/// Return the index of the last byte in `haystack` that satisfies `fine`,
+/// skipping every word for which `coarse` returns `false`.
+///
+/// This is the reverse of `memchr_pred`. Words are read backward from the
+/// end of `haystack`, so any bytes at its start that don't fill a word are
+/// always passed to `fine`.
+pub fn memrchr_pred<C, F>(coarse: C, fine: F, haystack: &[u8]) -> Option<usize>
+where
+    C: Fn(usize) -> bool,
+    F: Fn(u8) -> bool,
+{
+    let start_ptr = haystack.as_ptr();
+    let mut end = haystack.len();
+    while end >= USIZE_BYTES {
+        let i = end - USIZE_BYTES;
+        let chunk = unsafe { read_unaligned_usize(start_ptr.add(i)) };
+        if coarse(chunk) {
+            let word = &haystack[i..end];
+            if let Some(j) = word.iter().rposition(|&b| fine(b)) {
+                return Some(i + j);
+            }
+        }
+        end = i;
+    }
+    haystack[..end].iter().rposition(|&b| fine(b))
+}
+
+/// Return the index of the first byte yielded by `iter` that is `n1`.
+///
+/// Bytes are gathered into a word before they are tested, so up to a word's
+/// worth of bytes after the match may be consumed from `iter`.
+pub fn memchr_of_iter<I: Iterator<Item = u8>>(
+    n1: u8,
+    mut iter: I,
+) -> Option<usize> {
+    let vn1 = repeat_byte(n1);
+    let mut buf = [0; USIZE_BYTES];
+    let mut offset = 0;
+    loop {
+        let mut len = 0;
+        while len < USIZE_BYTES {
+            match iter.next() {
+                None => break,
+                Some(b) => buf[len] = b,
+            }
+            len += 1;
+        }
+        let full = len == USIZE_BYTES;
+        // A full word is only checked byte by byte if it contains a match.
+        if !full || contains_zero_byte(usize::from_ne_bytes(buf) ^ vn1) {
+            if let Some(i) = buf[..len].iter().position(|&b| b == n1) {
+                return Some(offset + i);
+            }
+        }
+        if !full {
+            return None;
+        }
+        offset += USIZE_BYTES;
+    }
+}
+
+/// Return the byte offset of the first 2-byte unit at an even offset in
+/// `haystack` whose bytes are `unit`, in memory order.
+///
+/// A trailing odd byte is never part of a unit and is ignored.
+pub fn memchr_u16(unit: [u8; 2], haystack: &[u8]) -> Option<usize> {
+    // Every even offset in the haystack is also an even offset in each word
+    // read from it, so the 2-byte lanes of a word line up with units.
+    // Repeating the unit in memory order across a word, and reading each
+    // word in native order, means the lanes of the two words agree no matter
+    // which byte order the target uses.
+    let lo = usize::MAX / 0xFFFF;
+    let hi = lo << 15;
+    let vunit = (u16::from_ne_bytes(unit) as usize) * lo;
+    let haystack = &haystack[..haystack.len() & !1];
+    let start_ptr = haystack.as_ptr();
+    let mut i = 0;
+    while haystack.len() - i >= USIZE_BYTES {
+        let x = unsafe { read_unaligned_usize(start_ptr.add(i)) } ^ vunit;
+        // This is `contains_zero_byte`, but for 16-bit lanes.
+        if x.wrapping_sub(lo) & !x & hi != 0 {
+            break;
+        }
+        i += USIZE_BYTES;
+    }
+    let mut units = haystack[i..].chunks_exact(2);
+    units.position(|pair| pair == unit).map(|j| i + 2 * j)
+}
+
+/// Return the first index at which `a` and `b` differ, comparing only their
+/// first `cmp::min(a.len(), b.len())` bytes.
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    let len = cmp::min(a.len(), b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let mut i = 0;
+    while len - i >= USIZE_BYTES {
+        let (x, y) = unsafe {
+            (
+                read_unaligned_usize(a.as_ptr().add(i)),
+                read_unaligned_usize(b.as_ptr().add(i)),
+            )
+        };
+        if x != y {
+            break;
+        }
+        i += USIZE_BYTES;
+    }
+    // Finding the differing byte one byte at a time, instead of from the
+    // XOR of the two words, doesn't depend on the byte order.
+    a[i..].iter().zip(&b[i..]).position(|(x, y)| x != y).map(|j| i + j)
+}
+
+/// Like `memchr`, but specialized to searching for a NUL byte.
+///
+/// Since the repeated needle is just `0`, each word can be handed to
+/// `contains_zero_byte` directly without first XOR'ing it with the needle.
+pub fn memchr0(haystack: &[u8]) -> Option<usize> {
+    let confirm = |byte| byte == 0;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    unsafe {
+        if haystack.len() < USIZE_BYTES {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        let chunk = read_unaligned_usize(ptr);
+        if contains_zero_byte(chunk) {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
+        debug_assert!(ptr > start_ptr);
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            if contains_zero_byte(a) || contains_zero_byte(b) {
+                break;
+            }
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        forward_search(start_ptr, end_ptr, ptr, confirm)
+    }
+}
+
+/// Like `memchr`, but only compares the bits of each byte that are set in
+/// `mask`.
+pub fn memchr_masked(n1: u8, mask: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = repeat_byte(n1 & mask);
+    let vmask = repeat_byte(mask);
+    let confirm = |byte| byte & mask == n1 & mask;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    unsafe {
+        if haystack.len() < USIZE_BYTES {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        let chunk = read_unaligned_usize(ptr);
+        if contains_zero_byte((chunk & vmask) ^ vn1) {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
+        debug_assert!(ptr > start_ptr);
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            let eqa = contains_zero_byte((a & vmask) ^ vn1);
+            let eqb = contains_zero_byte((b & vmask) ^ vn1);
             if eqa || eqb {
                 break;
             }
-            ptr = ptr_add(ptr, LOOP_SIZE);
+            ptr = ptr.add(LOOP_SIZE);
         }
         forward_search(start_ptr, end_ptr, ptr, confirm)
     }
@@ -102,10 +559,10 @@ pub fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
             return forward_search(start_ptr, end_ptr, ptr, confirm);
         }
 
-        ptr = ptr_add(ptr, USIZE_BYTES - (start_ptr as usize & align));
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
         debug_assert!(ptr > start_ptr);
-        debug_assert!(ptr_sub(end_ptr, USIZE_BYTES) >= start_ptr);
-        while ptr <= ptr_sub(end_ptr, USIZE_BYTES) {
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while ptr <= end_ptr.sub(USIZE_BYTES) {
             debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
 
             let chunk = *(ptr as *const usize);
@@ -114,7 +571,7 @@ pub fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
             if eq1 || eq2 {
                 break;
             }
-            ptr = ptr_add(ptr, USIZE_BYTES);
+            ptr = ptr.add(USIZE_BYTES);
         }
         forward_search(start_ptr, end_ptr, ptr, confirm)
     }
@@ -144,10 +601,10 @@ pub fn memchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
             return forward_search(start_ptr, end_ptr, ptr, confirm);
         }
 
-        ptr = ptr_add(ptr, USIZE_BYTES - (start_ptr as usize & align));
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
         debug_assert!(ptr > start_ptr);
-        debug_assert!(ptr_sub(end_ptr, USIZE_BYTES) >= start_ptr);
-        while ptr <= ptr_sub(end_ptr, USIZE_BYTES) {
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while ptr <= end_ptr.sub(USIZE_BYTES) {
             debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
 
             let chunk = *(ptr as *const usize);
@@ -157,7 +614,7 @@ pub fn memchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
             if eq1 || eq2 || eq3 {
                 break;
             }
-            ptr = ptr_add(ptr, USIZE_BYTES);
+            ptr = ptr.add(USIZE_BYTES);
         }
         forward_search(start_ptr, end_ptr, ptr, confirm)
     }
@@ -166,7 +623,30 @@ pub fn memchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
 /// Return the last index matching the byte `x` in `text`.
 pub fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     let vn1 = repeat_byte(n1);
-    let confirm = |byte| byte == n1;
+    let confirm = |&byte: &u8| byte == n1;
+    // See `memchr` for why this is sound.
+    let (_, words, tail) = unsafe { haystack.align_to::<usize>() };
+    let tail_start = haystack.len() - tail.len();
+    if let Some(i) = tail.iter().rposition(confirm) {
+        return Some(tail_start + i);
+    }
+
+    let mut end = tail_start;
+    for pair in words.rchunks_exact(2) {
+        let eqa = contains_zero_byte(pair[0] ^ vn1);
+        let eqb = contains_zero_byte(pair[1] ^ vn1);
+        if eqa || eqb {
+            break;
+        }
+        end -= LOOP_SIZE;
+    }
+    haystack[..end].iter().rposition(confirm)
+}
+
+/// Return the index of the last byte in `haystack` that is not `n1`.
+pub fn memrchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = repeat_byte(n1);
+    let confirm = |byte| byte != n1;
     let loop_size = cmp::min(LOOP_SIZE, haystack.len());
     let align = USIZE_BYTES - 1;
     let start_ptr = haystack.as_ptr();
@@ -178,24 +658,22 @@ pub fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
             return reverse_search(start_ptr, end_ptr, ptr, confirm);
         }
 
-        let chunk = read_unaligned_usize(ptr_sub(ptr, USIZE_BYTES));
-        if contains_zero_byte(chunk ^ vn1) {
+        let chunk = read_unaligned_usize(ptr.sub(USIZE_BYTES));
+        if chunk != vn1 {
             return reverse_search(start_ptr, end_ptr, ptr, confirm);
         }
 
         ptr = (end_ptr as usize & !align) as *const u8;
         debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
-        while loop_size == LOOP_SIZE && ptr >= ptr_add(start_ptr, loop_size) {
+        while loop_size == LOOP_SIZE && ptr >= start_ptr.add(loop_size) {
             debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
 
-            let a = *(ptr_sub(ptr, 2 * USIZE_BYTES) as *const usize);
-            let b = *(ptr_sub(ptr, 1 * USIZE_BYTES) as *const usize);
-            let eqa = contains_zero_byte(a ^ vn1);
-            let eqb = contains_zero_byte(b ^ vn1);
-            if eqa || eqb {
+            let a = *(ptr.sub(2 * USIZE_BYTES) as *const usize);
+            let b = *(ptr.sub(USIZE_BYTES) as *const usize);
+            if (a ^ vn1) | (b ^ vn1) != 0 {
                 break;
             }
-            ptr = ptr_sub(ptr, loop_size);
+            ptr = ptr.sub(loop_size);
         }
         reverse_search(start_ptr, end_ptr, ptr, confirm)
     }
@@ -216,7 +694,7 @@ pub fn memrchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
             return reverse_search(start_ptr, end_ptr, ptr, confirm);
         }
 
-        let chunk = read_unaligned_usize(ptr_sub(ptr, USIZE_BYTES));
+        let chunk = read_unaligned_usize(ptr.sub(USIZE_BYTES));
         let eq1 = contains_zero_byte(chunk ^ vn1);
         let eq2 = contains_zero_byte(chunk ^ vn2);
         if eq1 || eq2 {
@@ -225,16 +703,16 @@ pub fn memrchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
 
         ptr = (end_ptr as usize & !align) as *const u8;
         debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
-        while ptr >= ptr_add(start_ptr, USIZE_BYTES) {
+        while ptr >= start_ptr.add(USIZE_BYTES) {
             debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
 
-            let chunk = *(ptr_sub(ptr, USIZE_BYTES) as *const usize);
+            let chunk = *(ptr.sub(USIZE_BYTES) as *const usize);
             let eq1 = contains_zero_byte(chunk ^ vn1);
             let eq2 = contains_zero_byte(chunk ^ vn2);
             if eq1 || eq2 {
                 break;
             }
-            ptr = ptr_sub(ptr, USIZE_BYTES);
+            ptr = ptr.sub(USIZE_BYTES);
         }
         reverse_search(start_ptr, end_ptr, ptr, confirm)
     }
@@ -256,7 +734,7 @@ pub fn memrchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
             return reverse_search(start_ptr, end_ptr, ptr, confirm);
         }
 
-        let chunk = read_unaligned_usize(ptr_sub(ptr, USIZE_BYTES));
+        let chunk = read_unaligned_usize(ptr.sub(USIZE_BYTES));
         let eq1 = contains_zero_byte(chunk ^ vn1);
         let eq2 = contains_zero_byte(chunk ^ vn2);
         let eq3 = contains_zero_byte(chunk ^ vn3);
@@ -266,22 +744,122 @@ pub fn memrchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
 
         ptr = (end_ptr as usize & !align) as *const u8;
         debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
-        while ptr >= ptr_add(start_ptr, USIZE_BYTES) {
+        while ptr >= start_ptr.add(USIZE_BYTES) {
             debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
 
-            let chunk = *(ptr_sub(ptr, USIZE_BYTES) as *const usize);
+            let chunk = *(ptr.sub(USIZE_BYTES) as *const usize);
             let eq1 = contains_zero_byte(chunk ^ vn1);
             let eq2 = contains_zero_byte(chunk ^ vn2);
             let eq3 = contains_zero_byte(chunk ^ vn3);
             if eq1 || eq2 || eq3 {
                 break;
             }
-            ptr = ptr_sub(ptr, USIZE_BYTES);
+            ptr = ptr.sub(USIZE_BYTES);
         }
         reverse_search(start_ptr, end_ptr, ptr, confirm)
     }
 }
 
+/// Return a mask where bit `i` is set if and only if `block[i] == n1`.
+///
+/// `block` must not be longer than 64 bytes.
+pub fn match_mask(n1: u8, block: &[u8]) -> u64 {
+    debug_assert!(block.len() <= 64);
+    let mut mask = 0;
+    for (i, &b) in block.iter().enumerate() {
+        mask |= ((b == n1) as u64) << i;
+    }
+    mask
+}
+
+/// Return `true` if and only if no byte in `haystack` has its most
+/// significant bit set.
+pub fn is_ascii(haystack: &[u8]) -> bool {
+    let confirm = |byte| byte >= 0x80;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    unsafe {
+        if haystack.len() < USIZE_BYTES {
+            return forward_search(start_ptr, end_ptr, ptr, confirm).is_none();
+        }
+
+        let chunk = read_unaligned_usize(ptr);
+        if chunk & HI_USIZE != 0 {
+            return false;
+        }
+
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
+        debug_assert!(ptr > start_ptr);
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            if (a | b) & HI_USIZE != 0 {
+                return false;
+            }
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        forward_search(start_ptr, end_ptr, ptr, confirm).is_none()
+    }
+}
+
+/// Return a word with the most significant bit of a byte set if and only if
+/// the corresponding byte in `x` is a UTF-8 continuation byte, i.e., its two
+/// most significant bits are `10`.
+///
+/// Shifting left by one moves bit 6 of each byte into bit 7 of the same
+/// byte, and bit 7 into the next byte, where it is masked off. So this is
+/// exact for every byte in `x`.
+#[inline(always)]
+fn continuation_byte_mask(x: usize) -> usize {
+    x & !(x << 1) & HI_USIZE
+}
+
+/// Return the index of the first byte in `haystack` that is not a UTF-8
+/// continuation byte.
+pub fn find_char_boundary(haystack: &[u8]) -> Option<usize> {
+    let confirm = |byte| byte & 0xC0 != 0x80;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    unsafe {
+        if haystack.len() < USIZE_BYTES {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        let chunk = read_unaligned_usize(ptr);
+        if continuation_byte_mask(chunk) != HI_USIZE {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
+        debug_assert!(ptr > start_ptr);
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            let conta = continuation_byte_mask(a);
+            let contb = continuation_byte_mask(b);
+            if conta & contb != HI_USIZE {
+                break;
+            }
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        forward_search(start_ptr, end_ptr, ptr, confirm)
+    }
+}
+
 #[inline(always)]
 unsafe fn forward_search<F: Fn(u8) -> bool>(
     start_ptr: *const u8,
@@ -320,17 +898,13 @@ unsafe fn reverse_search<F: Fn(u8) -> bool>(
     None
 }
 
-/// Increment the given pointer by the given amount.
-unsafe fn ptr_add(ptr: *const u8, amt: usize) -> *const u8 {
-    debug_assert!(amt < ::core::isize::MAX as usize);
-    ptr.offset(amt as isize)
-}
-
-/// Decrement the given pointer by the given amount.
-unsafe fn ptr_sub(ptr: *const u8, amt: usize) -> *const u8 {
-    debug_assert!(amt < ::core::isize::MAX as usize);
-    ptr.offset((amt as isize).wrapping_neg())
-}
+// A note on haystack sizes: the pointer arithmetic above uses `add` and
+// `sub`, which take a `usize` offset but require that it not exceed
+// `isize::MAX`. This always holds, since Rust guarantees that no slice spans
+// more than `isize::MAX` bytes (see `slice::from_raw_parts`), and every
+// offset we compute is bounded by the length of the haystack. This is true
+// on 32-bit targets too, where a memory map might otherwise approach
+// `usize::MAX / 2` bytes.
 
 unsafe fn read_unaligned_usize(ptr: *const u8) -> usize {
     let mut n: usize = 0;
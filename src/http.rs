@@ -0,0 +1,54 @@
+// This module defines a parser for the size lines of HTTP's chunked transfer
+// coding, which shows how memmem can be used to find the end of a line in a
+// buffer that is still being filled.
+
+use {memchr, memmem};
+
+/// Parse the chunk size line at the start of `haystack`.
+///
+/// In HTTP's chunked transfer coding, each chunk starts with a line holding
+/// its size in hexadecimal, optionally followed by extensions after a `;`,
+/// and terminated by `\r\n`. This finds that `\r\n` with `memmem`, parses the
+/// size before it, and returns `(size, consumed)`, where `consumed` is the
+/// length of the line including its `\r\n`. So the chunk's data starts at
+/// `haystack[consumed..]`. Extensions, and any spaces or tabs before the
+/// `;`, are ignored.
+///
+/// This returns `None` if `haystack` doesn't contain a complete line yet,
+/// in which case the caller should read more bytes and try again. It also
+/// returns `None` if the line is complete but its size isn't made up of one
+/// or more hexadecimal digits, or doesn't fit in a `usize`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::chunk_size_line;
+///
+/// assert_eq!(chunk_size_line(b"1a\r\n..."), Some((26, 4)));
+/// assert_eq!(chunk_size_line(b"5;name=value\r\nhello"), Some((5, 14)));
+/// // The line isn't complete yet.
+/// assert_eq!(chunk_size_line(b"1a\r"), None);
+/// ```
+pub fn chunk_size_line(haystack: &[u8]) -> Option<(usize, usize)> {
+    let end = memmem(b"\r\n", haystack)?;
+    let line = &haystack[..end];
+    let mut digits = match memchr(b';', line) {
+        None => line,
+        Some(i) => &line[..i],
+    };
+    while let Some((&last, init)) = digits.split_last() {
+        if last != b' ' && last != b'\t' {
+            break;
+        }
+        digits = init;
+    }
+    if digits.is_empty() {
+        return None;
+    }
+    let mut size: usize = 0;
+    for &b in digits {
+        let digit = (b as char).to_digit(16)? as usize;
+        size = size.checked_mul(16)?.checked_add(digit)?;
+    }
+    Some((size, end + 2))
+}
@@ -1,4 +1,10 @@
-use {memchr, memchr2, memchr3, memrchr, memrchr2, memrchr3};
+#[cfg(feature = "use_std")]
+use core::cmp;
+
+use fallback;
+use {count, memchr, memchr2, memchr3, memrchr, memrchr2, memrchr3};
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+use x86;
 
 macro_rules! iter_next {
     // Common code for the memchr iterators:
@@ -23,7 +29,9 @@ macro_rules! iter_next {
 macro_rules! iter_next_back {
     ($self_:expr, $search_result:expr) => {
         $search_result.map(move |index| {
-            // split and take the remaining front half
+            // split and take the remaining front half. When the match is at
+            // index 0, this leaves an empty haystack, in which every later
+            // search fails, so no index is ever decremented below zero.
             $self_.haystack = $self_.haystack.split_at(index).0;
             $self_.position + index
         })
@@ -37,6 +45,10 @@ pub struct Memchr<'a> {
     haystack: &'a [u8],
     // The index
     position: usize,
+    // The matches found by `next_back` but not yielded yet, which all come
+    // after `haystack`. Bit `i` is set for a match at `back_start + i`.
+    back_mask: u64,
+    back_start: usize,
 }
 
 impl<'a> Memchr<'a> {
@@ -47,8 +59,26 @@ impl<'a> Memchr<'a> {
             needle: needle,
             haystack: haystack,
             position: 0,
+            back_mask: 0,
+            back_start: 0,
         }
     }
+
+    /// Find the last match in `haystack`, and move it along with every
+    /// other match in the 64 bytes that end with it into `back_mask`.
+    ///
+    /// When matches are dense, this makes `next_back` find up to 64 of them
+    /// with a single call to `memrchr` and a single comparison of each
+    /// byte, instead of calling `memrchr` once per match.
+    #[inline]
+    fn fill_back_mask(&mut self) -> Option<()> {
+        let end = memrchr(self.needle, self.haystack)? + 1;
+        let start = end.saturating_sub(64);
+        self.back_mask = match_mask(self.needle, &self.haystack[start..end]);
+        self.back_start = self.position + start;
+        self.haystack = &self.haystack[..start];
+        Some(())
+    }
 }
 
 impl<'a> Iterator for Memchr<'a> {
@@ -56,19 +86,207 @@ impl<'a> Iterator for Memchr<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<usize> {
-        iter_next!(self, memchr(self.needle, self.haystack))
+        // When the needle is a very common byte (e.g., a space in text), the
+        // next match is often the very next byte. Checking for that here
+        // skips the call to memchr, which on x86_64 means an indirect call
+        // and setting up vectors to search a single byte.
+        if self.haystack.first() == Some(&self.needle) {
+            self.haystack = &self.haystack[1..];
+            self.position += 1;
+            return Some(self.position - 1);
+        }
+        if let Some(index) = memchr(self.needle, self.haystack) {
+            self.haystack = &self.haystack[index + 1..];
+            let found_position = self.position + index;
+            self.position = found_position + 1;
+            return Some(found_position);
+        }
+        if self.back_mask == 0 {
+            return None;
+        }
+        // Every match left is in `back_mask`, so there's no need to search
+        // `haystack` again.
+        self.haystack = &[];
+        let i = self.back_mask.trailing_zeros() as usize;
+        self.back_mask &= self.back_mask - 1;
+        Some(self.back_start + i)
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, Some(self.haystack.len()))
+        let back = self.back_mask.count_ones() as usize;
+        (back, Some(self.haystack.len() + back))
     }
 }
 
 impl<'a> DoubleEndedIterator for Memchr<'a> {
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
-        iter_next_back!(self, memrchr(self.needle, self.haystack))
+        if self.back_mask == 0 {
+            self.fill_back_mask()?;
+        }
+        let i = 63 - self.back_mask.leading_zeros() as usize;
+        self.back_mask &= !(1 << i);
+        Some(self.back_start + i)
+    }
+}
+
+/// Return a mask where bit `i` is set if and only if `block[i] == needle`,
+/// for a block of at most 64 bytes.
+#[inline(always)]
+fn match_mask(needle: u8, block: &[u8]) -> u64 {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(n1: u8, block: &[u8]) -> u64 {
+                if block.len() != 64 {
+                    return fallback::match_mask(n1, block);
+                }
+                // This is `<&[u8; 64]>::try_from(block)`, which needs Rust
+                // 1.47. An array has the same layout as a slice of the same
+                // length, and the length was just checked.
+                let block = unsafe { &*(block.as_ptr() as *const [u8; 64]) };
+                x86::match_mask(n1, block)
+            }
+        } else {
+            #[inline(always)]
+            fn imp(n1: u8, block: &[u8]) -> u64 {
+                fallback::match_mask(n1, block)
+            }
+        }
+    }
+    imp(needle, block)
+}
+
+/// An iterator for `memchr` that knows how many matches remain.
+///
+/// This is created by `memchr_iter_counted`.
+pub struct MemchrCounted<'a> {
+    it: Memchr<'a>,
+    // The number of matches not yet yielded
+    remaining: usize,
+}
+
+impl<'a> MemchrCounted<'a> {
+    /// Creates a new iterator that yields all positions of needle in
+    /// haystack, after counting them.
+    #[inline]
+    pub fn new(needle: u8, haystack: &[u8]) -> MemchrCounted {
+        MemchrCounted {
+            it: Memchr::new(needle, haystack),
+            remaining: count(needle, haystack),
+        }
+    }
+}
+
+impl<'a> Iterator for MemchrCounted<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let next = self.it.next();
+        if next.is_some() {
+            self.remaining -= 1;
+        }
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for MemchrCounted<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let next = self.it.next_back();
+        if next.is_some() {
+            self.remaining -= 1;
+        }
+        next
+    }
+}
+
+impl<'a> ExactSizeIterator for MemchrCounted<'a> {}
+
+/// An iterator for `memchr` that groups matches by fixed-size windows.
+///
+/// This is created by `memchr_grouped`.
+#[cfg(feature = "use_std")]
+pub struct MemchrGrouped<'a> {
+    needle: u8,
+    haystack: &'a [u8],
+    group: usize,
+    // The start of the first window that hasn't been searched yet
+    position: usize,
+}
+
+#[cfg(feature = "use_std")]
+impl<'a> MemchrGrouped<'a> {
+    /// Creates a new iterator that yields the positions of needle in
+    /// haystack, grouped by windows of `group` bytes.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `group` is `0`.
+    #[inline]
+    pub fn new(needle: u8, haystack: &'a [u8], group: usize) -> Self {
+        assert!(group > 0, "group size must be greater than zero");
+        MemchrGrouped { needle, haystack, group, position: 0 }
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<'a> Iterator for MemchrGrouped<'a> {
+    type Item = (usize, Vec<usize>);
+
+    fn next(&mut self) -> Option<(usize, Vec<usize>)> {
+        let rest = self.haystack.get(self.position..)?;
+        let first = self.position + memchr(self.needle, rest)?;
+        let start = first - first % self.group;
+        let end = cmp::min(start + self.group, self.haystack.len());
+        let offsets = Memchr::new(self.needle, &self.haystack[first..end])
+            .map(|i| first - start + i)
+            .collect();
+        self.position = end;
+        Some((start, offsets))
+    }
+}
+
+/// An iterator over the distances between consecutive matches of `memchr`.
+///
+/// This is created by `match_gaps`.
+pub struct MatchGaps<'a> {
+    it: Memchr<'a>,
+    // The position of the previous match, which is `None` before the first
+    last: Option<usize>,
+}
+
+impl<'a> MatchGaps<'a> {
+    /// Creates a new iterator that yields the position of the first match
+    /// of needle in haystack, followed by the distance from each match to
+    /// the next.
+    #[inline]
+    pub fn new(needle: u8, haystack: &'a [u8]) -> MatchGaps<'a> {
+        MatchGaps { it: Memchr::new(needle, haystack), last: None }
+    }
+}
+
+impl<'a> Iterator for MatchGaps<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let i = self.it.next()?;
+        let gap = i - self.last.unwrap_or(0);
+        self.last = Some(i);
+        Some(gap)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.it.size_hint()
     }
 }
 
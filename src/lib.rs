@@ -8,8 +8,8 @@ Assembly implementations (like that found in GNU's libc) all the way to
 non-vectorized C implementations (like that found in MUSL).
 
 To smooth out the differences between implementations of libc, at least
-on `x86_64` for Rust 1.27+, this crate provides its own implementation of
-`memchr` that should perform competitively with the one found in GNU's libc.
+on `x86_64`, this crate provides its own implementation of `memchr` that
+should perform competitively with the one found in GNU's libc.
 The implementation is in pure Rust and has no dependency on a C compiler or an
 Assembler.
 
@@ -18,9 +18,23 @@ provides its own implementation of `memrchr` as well, on top of `memchr2`,
 `memchr3`, `memrchr2` and `memrchr3`. The difference between `memchr` and
 `memchr2` is that that `memchr2` permits finding all occurrences of two bytes
 instead of one. Similarly for `memchr3`.
+
+# Out of range indices
+
+Some routines, like `memrchr_at`, `next_char_boundary` and
+`Scanner::find_from`, take an index into the haystack in addition to the
+haystack itself. None of them panic when that index is out of range.
+Instead, an index greater than the length of the haystack is clamped to the
+nearest index that makes sense for the search. For a forward search that
+starts at an index, that is the end of the haystack, so nothing is searched
+and the result is `None`. For a reverse search that ends at an index, that
+is the last byte of the haystack, so the entire haystack is searched.
+
+New routines that take an index follow the same rule.
 */
 
 #![cfg_attr(not(feature = "use_std"), no_std)]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
 
 #![deny(missing_docs)]
 #![doc(html_root_url = "https://docs.rs/memchr/2.0.0")]
@@ -39,9 +53,59 @@ extern crate cfg_if;
 #[macro_use]
 extern crate quickcheck;
 
+use core::cmp;
+#[cfg(feature = "use_std")]
+use core::convert::TryFrom;
 use core::iter::Rev;
+use core::slice;
+use core::str::{self, Utf8Error};
 
-pub use iter::{Memchr, Memchr2, Memchr3};
+pub use byteset::{
+    ByteSet, first_forbidden_control, memchr_byteset, memchr_not_byteset,
+};
+pub use bytestr::{ByteStr, Lines, Split};
+pub use cstr::{cstr_len, cstr_len_raw};
+pub use csv::{CsvFields, csv_fields};
+#[cfg(feature = "use_std")]
+pub use deque::memchr_deque;
+pub use ext::MemchrExt;
+pub use http::chunk_size_line;
+pub use iter::{MatchGaps, Memchr, Memchr2, Memchr3, MemchrCounted};
+#[cfg(feature = "use_std")]
+pub use iter::MemchrGrouped;
+pub use lines::{
+    LineEnding, count_lines, detect_line_ending, line_col, nth_line,
+    nth_line_offset,
+};
+pub use memmem::{FindIter, Finder, ends_with, memmem, rfind_suffix};
+pub use nonmax::{NonMaxUsize, memchr_nz};
+pub use pattern::{BytePattern, find, rfind};
+pub use read::{ReadLike, ScanError, memchr_chunked};
+#[cfg(all(unix, feature = "use_std"))]
+pub use os::memchr_os;
+pub use prefetch::memchr_tuned;
+pub use scanner::Scanner;
+pub use split::{
+    KeyValuePairs, RSplitN, SegmentLengths, SplitInclusive, SplitN,
+    SplitTerminatorWithPos, key_value_pairs, next_token, rsplitn,
+    segment_lengths, split_at_byte, split_inclusive, splitn,
+    split_terminator_with_pos,
+};
+#[cfg(feature = "use_std")]
+pub use split::{field_ranges, split_collect};
+#[cfg(feature = "use_std")]
+pub use stream::{find_in_bufread, memchr_stream, memchr_stream_with};
+#[cfg(feature = "telemetry")]
+pub use telemetry::{KernelId, memchr_traced};
+#[cfg(feature = "bench-internals")]
+pub use telemetry::{kernel_available, memchr_kernel};
+#[cfg(feature = "profiling")]
+pub use telemetry::{ScanStats, memchr_profiled};
+pub use transform::{
+    UnpadRecords, trim_ascii_whitespace, unpad_record, unpad_records,
+};
+#[cfg(feature = "use_std")]
+pub use transform::{collapse_runs, replace_byte};
 
 #[cfg(all(
     feature = "libc",
@@ -49,21 +113,237 @@ pub use iter::{Memchr, Memchr2, Memchr3};
     not(target_env = "sgx"),
 ))]
 mod c;
+mod byteset;
+mod bytestr;
+mod cstr;
+mod csv;
+#[cfg(feature = "use_std")]
+mod deque;
+mod ext;
 #[allow(dead_code)]
 mod fallback;
+mod http;
 mod iter;
+mod lines;
+mod memmem;
 mod naive;
+mod nonmax;
+mod pattern;
+mod prefetch;
+mod read;
+mod scanner;
+mod split;
+#[cfg(feature = "portable_simd")]
+#[allow(dead_code)]
+mod simd;
+#[cfg(all(unix, feature = "use_std"))]
+mod os;
+#[cfg(feature = "use_std")]
+mod stream;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+mod transform;
 #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
 mod x86;
 #[cfg(test)]
 mod tests;
 
+// A note on generics and code size.
+//
+// Every generic routine in this crate (for example, one that is generic over
+// a reader type or over `AsRef<[u8]>`) must do no more than adapt its
+// arguments before calling one of the non-generic routines defined in this
+// file, such as `memchr` or `memrchr`. Those in turn dispatch to the kernels
+// in the `x86`, `fallback` and `c` modules. On x86_64, the kernels are
+// compiled with `#[target_feature]` and usually selected at runtime and
+// called through a function pointer, which prevents inlining. The exception
+// is a build with AVX2 enabled at compile time (e.g., with
+// `-C target-cpu=native`), where the AVX2 kernel is called directly and the
+// compiler may choose to inline it. Generic code must never call a kernel
+// directly.
+//
+// The upshot is that each monomorphization of a generic routine only adds a
+// small adapter to a binary, while the kernels themselves are shared. For
+// example, a `fn find<H: AsRef<[u8]>>(n: u8, h: H) -> Option<usize>` that
+// calls `memchr(n, h.as_ref())`, instantiated for `String`, `&[u8]` and
+// `Vec<u8>` in a release build, produces three small copies of `find` and,
+// with runtime detection, a single copy of each of `x86::sse2::memchr` and
+// `x86::avx::memchr`. This can be checked with
+// `nm --print-size --demangle <binary> | grep memchr`.
+
 /// An iterator over all occurrences of the needle in a haystack.
 #[inline]
 pub fn memchr_iter(needle: u8, haystack: &[u8]) -> Memchr {
     Memchr::new(needle, haystack)
 }
 
+/// An iterator over all occurrences of the needle in a haystack, which knows
+/// exactly how many occurrences there are.
+///
+/// The iterator returned implements `ExactSizeIterator`, which lets, e.g.,
+/// `collect::<Vec<_>>()` allocate exactly once. This comes at the cost of two
+/// passes over the haystack, since every occurrence is counted (with `count`)
+/// before the iterator is returned. When the haystack is large and only a few
+/// of its occurrences are needed, prefer `memchr_iter`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_iter_counted;
+///
+/// let it = memchr_iter_counted(b'o', b"the quick brown fox");
+/// assert_eq!(it.len(), 2);
+/// assert_eq!(it.collect::<Vec<usize>>(), vec![12, 17]);
+/// ```
+#[inline]
+pub fn memchr_iter_counted(needle: u8, haystack: &[u8]) -> MemchrCounted {
+    MemchrCounted::new(needle, haystack)
+}
+
+/// Collect the positions of every occurrence of a byte as `u32`s.
+///
+/// This returns the same positions as `memchr_iter(needle, haystack)`, but
+/// as `u32`s, which take half the space of `usize`s on 64-bit targets and
+/// can be uploaded as is to APIs (such as GPU buffers) that expect 32-bit
+/// indices. The vector is allocated once, with its capacity computed by
+/// `count`.
+///
+/// # Panics
+///
+/// This panics if `needle` occurs at an index greater than `u32::MAX`,
+/// which can only happen when `haystack` is longer than 4 GiB. Matches
+/// before that index are fine, so a longer haystack only panics if it has a
+/// match past the limit.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_positions_u32;
+///
+/// let positions = memchr_positions_u32(b'o', b"the quick brown fox");
+/// assert_eq!(positions, vec![12u32, 17]);
+/// ```
+#[cfg(feature = "use_std")]
+pub fn memchr_positions_u32(needle: u8, haystack: &[u8]) -> Vec<u32> {
+    let mut positions = Vec::with_capacity(count(needle, haystack));
+    for i in memchr_iter(needle, haystack) {
+        match u32::try_from(i) {
+            Ok(i) => positions.push(i),
+            Err(_) => panic!("match at {} exceeds u32::MAX", i),
+        }
+    }
+    positions
+}
+
+/// Append the positions of every occurrence of a byte to `out`, converted
+/// to the integer type `T`.
+///
+/// This appends the same positions as `memchr_iter(needle, haystack)`, in
+/// ascending order, which generalizes `memchr_positions_u32` to any type
+/// that can be converted from a `usize`, such as the element type of a
+/// buffer that is handed to numeric code. `out` is reserved once, with the
+/// number of occurrences computed by `count`, and its existing elements are
+/// kept.
+///
+/// # Panics
+///
+/// This panics if a match is at an index that doesn't fit in `T`, such as
+/// an index greater than `u32::MAX` when `T` is `u32`. As with
+/// `memchr_positions_u32`, only the matches need to fit, not the length of
+/// `haystack`. The positions before the one that doesn't fit have already
+/// been appended to `out` when this panics.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_indices;
+///
+/// let mut indices: Vec<u64> = vec![];
+/// memchr_indices(b'o', b"the quick brown fox", &mut indices);
+/// assert_eq!(indices, vec![12, 17]);
+///
+/// let mut indices: Vec<u8> = vec![0];
+/// memchr_indices(b'a', b"banana", &mut indices);
+/// assert_eq!(indices, vec![0, 1, 3, 5]);
+/// ```
+#[cfg(feature = "use_std")]
+pub fn memchr_indices<T: TryFrom<usize>>(
+    needle: u8,
+    haystack: &[u8],
+    out: &mut Vec<T>,
+) {
+    out.reserve(count(needle, haystack));
+    for i in memchr_iter(needle, haystack) {
+        match T::try_from(i) {
+            Ok(index) => out.push(index),
+            Err(_) => panic!("match at {} exceeds the output type", i),
+        }
+    }
+}
+
+/// An iterator over all occurrences of the needle in a haystack, grouped by
+/// the fixed-size window that each one falls in.
+///
+/// `haystack` is divided into consecutive windows of `group` bytes, the
+/// first one starting at index `0`. For each window that contains at least
+/// one occurrence of `needle`, this yields `(start, offsets)`, where `start`
+/// is the index at which the window starts and `offsets` are the positions
+/// of the occurrences relative to `start`, in ascending order. Windows
+/// without any occurrences are skipped with `memchr`, so they cost no more
+/// than they would with `memchr_iter`. When `haystack.len()` isn't a
+/// multiple of `group`, the last window is shorter and ends with the
+/// haystack, so all of its offsets are still less than `group`.
+///
+/// This is useful for code that processes matches one aligned block at a
+/// time, such as SIMD code that builds a mask per block.
+///
+/// # Panics
+///
+/// This panics if `group` is `0`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_grouped;
+///
+/// let haystack = b"a,b,c....,d,";
+/// let groups: Vec<(usize, Vec<usize>)> =
+///     memchr_grouped(b',', haystack, 4).collect();
+/// assert_eq!(groups, vec![(0, vec![1, 3]), (8, vec![1, 3])]);
+/// ```
+#[cfg(feature = "use_std")]
+#[inline]
+pub fn memchr_grouped<'a>(
+    needle: u8,
+    haystack: &'a [u8],
+    group: usize,
+) -> MemchrGrouped<'a> {
+    MemchrGrouped::new(needle, haystack, group)
+}
+
+/// An iterator over the distances between consecutive occurrences of the
+/// needle in a haystack.
+///
+/// The first item is the index of the first occurrence, i.e., its distance
+/// from the start of `haystack`. Every subsequent item is the index of an
+/// occurrence minus the index of the occurrence before it, so it is always
+/// at least `1`, and the running sum of the items is the index of each
+/// occurrence in turn. No item is yielded for the bytes after the last
+/// occurrence, and a haystack without occurrences yields nothing.
+///
+/// # Example
+///
+/// ```
+/// use memchr::match_gaps;
+///
+/// let gaps: Vec<usize> = match_gaps(b'a', b"a.a..a").collect();
+/// assert_eq!(gaps, vec![0, 2, 3]);
+/// ```
+#[inline]
+pub fn match_gaps<'a>(needle: u8, haystack: &'a [u8]) -> MatchGaps<'a> {
+    MatchGaps::new(needle, haystack)
+}
+
 /// An iterator over all occurrences of the needles in a haystack.
 #[inline]
 pub fn memchr2_iter(
@@ -122,6 +402,16 @@ pub fn memrchr3_iter(
 /// optimized routine that can be up to an order of magnitude faster in some
 /// cases.
 ///
+/// # Determinism
+///
+/// The index returned depends only on `needle` and `haystack`. It is always
+/// exactly `haystack.iter().position(|&b| b == needle)`, no matter which
+/// implementation ends up being used (libc, one of the SIMD implementations
+/// selected at runtime, or the portable fallback), and regardless of
+/// whether `needle` is NUL. So results are the same on every platform and
+/// every CPU. The same holds for `memrchr`, `memchr2`, `memchr3`, `memrchr2`
+/// and `memrchr3`.
+///
 /// # Example
 ///
 /// This shows how to find the first position of a byte in a byte string.
@@ -140,16 +430,26 @@ pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
             fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
                 x86::memchr(n1, haystack)
             }
+        } else if #[cfg(feature = "portable_simd")] {
+            #[inline(always)]
+            fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+                simd::memchr(n1, haystack)
+            }
         } else if #[cfg(all(
             feature = "libc",
             not(target_arch = "wasm32"),
-            not(target_arch = "windows"),
+            not(target_os = "windows"),
             not(target_env = "sgx"),
         ))] {
             #[inline(always)]
             fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
                 c::memchr(n1, haystack)
             }
+        } else if #[cfg(feature = "fallback_unroll4")] {
+            #[inline(always)]
+            fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+                fallback::memchr_unroll4(n1, haystack)
+            }
         } else {
             #[inline(always)]
             fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
@@ -164,100 +464,373 @@ pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
     }
 }
 
-/// Like `memchr`, but searches for two bytes instead of one.
+/// Search for the first occurrence of a byte in anything that can be viewed
+/// as a byte slice.
+///
+/// This is the same as `memchr(needle, haystack.as_ref())`. It accepts, e.g.,
+/// `Vec<u8>`, `String`, `str`, byte arrays and shared buffers like
+/// `Box<[u8]>`, `Rc<[u8]>` and `Arc<[u8]>` without an explicit `&buf[..]`.
+/// Since `haystack` is borrowed, nothing is cloned or allocated, not even a
+/// reference count update for `Rc` and `Arc`.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use memchr::memchr_in;
+///
+/// let shared: Arc<[u8]> = Arc::from(&b"the quick brown fox"[..]);
+/// assert_eq!(memchr_in(b'k', &shared), Some(8));
+/// assert_eq!(memchr_in(b'k', "the quick brown fox"), Some(8));
+/// ```
 #[inline]
-pub fn memchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
+pub fn memchr_in<H: AsRef<[u8]> + ?Sized>(
+    needle: u8,
+    haystack: &H,
+) -> Option<usize> {
+    memchr(needle, haystack.as_ref())
+}
+
+/// Search for the first occurrence of a byte in a mutable slice.
+///
+/// This returns the index of the first occurrence of `needle` in `haystack`,
+/// along with the mutable subslice of `haystack` that starts at that index,
+/// or `None` if one is not found. The subslice always begins with `needle`.
+///
+/// # Example
+///
+/// This shows how to find a delimiter and then rewrite the field that
+/// follows it in place.
+///
+/// ```
+/// use memchr::memchr_mut;
+///
+/// let mut record = *b"key=value";
+/// if let Some((i, rest)) = memchr_mut(b'=', &mut record) {
+///     assert_eq!(i, 3);
+///     rest[1..].make_ascii_uppercase();
+/// }
+/// assert_eq!(&record, b"key=VALUE");
+/// ```
+#[inline]
+pub fn memchr_mut(
+    needle: u8,
+    haystack: &mut [u8],
+) -> Option<(usize, &mut [u8])> {
+    let i = memchr(needle, haystack)?;
+    Some((i, &mut haystack[i..]))
+}
+
+/// Search for the first occurrence of a byte in the initialized prefix of a
+/// possibly uninitialized buffer.
+///
+/// This treats the first `init_len` elements of `haystack` as initialized
+/// bytes and searches them as `memchr` would, returning an index into
+/// `haystack`. The rest of `haystack` is never read. This lets a read loop
+/// search the bytes that a read just wrote into a buffer, without zeroing
+/// the buffer first.
+///
+/// This is only available with Rust 1.36 or newer, since that's when
+/// `MaybeUninit` was stabilized.
+///
+/// # Panics
+///
+/// This panics if `init_len > haystack.len()`.
+///
+/// # Safety
+///
+/// The first `init_len` elements of `haystack` must be initialized.
+///
+/// # Example
+///
+/// ```
+/// use std::mem::MaybeUninit;
+/// use memchr::memchr_maybe_uninit;
+///
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// for (dst, &src) in buf.iter_mut().zip(b"foo\nbar") {
+///     *dst = MaybeUninit::new(src);
+/// }
+/// let found = unsafe { memchr_maybe_uninit(b'\n', &buf, 7) };
+/// assert_eq!(found, Some(3));
+/// ```
+#[cfg(memchr_maybe_uninit)]
+#[inline]
+pub unsafe fn memchr_maybe_uninit(
+    needle: u8,
+    haystack: &[::core::mem::MaybeUninit<u8>],
+    init_len: usize,
+) -> Option<usize> {
+    let init = &haystack[..init_len];
+    // `MaybeUninit<u8>` has the same layout as `u8`, and the caller
+    // guarantees that these elements are initialized.
+    memchr(needle, slice::from_raw_parts(init.as_ptr() as *const u8, init_len))
+}
+
+/// Search for the first occurrence of a byte and report how much of the
+/// haystack was consumed.
+///
+/// This returns the index of the first occurrence of `needle` (as `memchr`
+/// does), along with the number of bytes that a streaming parser should
+/// consume: one past the match when there is one, or `haystack.len()` when
+/// there isn't. In other words, the second value is always where the next
+/// search should resume.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_progress;
+///
+/// assert_eq!(memchr_progress(b'\n', b"foo\nbar"), (Some(3), 4));
+/// assert_eq!(memchr_progress(b'\n', b"bar"), (None, 3));
+/// ```
+#[inline]
+pub fn memchr_progress(needle: u8, haystack: &[u8]) -> (Option<usize>, usize) {
+    match memchr(needle, haystack) {
+        None => (None, haystack.len()),
+        Some(i) => (Some(i), i + 1),
+    }
+}
+
+/// Search for the first occurrence of a byte at a multiple of `stride`.
+///
+/// This returns the smallest index `i` such that `i % stride == 0` and
+/// `haystack[i] == needle`, or `None` if one is not found. This is useful
+/// for interleaved data, where a field of interest occurs at a fixed stride.
+///
+/// When `stride` is `1`, this is the same as `memchr`. On `x86_64` CPUs that
+/// support AVX2, a `stride` that is a power of two no greater than `4096` is
+/// searched 8 strided bytes at a time with SIMD gathers, which is several
+/// times faster than a hand written loop for small strides, with the gap
+/// narrowing as the stride grows. Any other `stride` steps through
+/// `haystack` one byte at a time, since the bytes in between are skipped. It
+/// is thus much slower than `memchr` per byte of haystack, but is no slower
+/// than a hand written loop.
+///
+/// # Panics
+///
+/// This panics if `stride` is `0`.
+///
+/// # Example
+///
+/// This shows how to search only the first byte of each two byte record.
+///
+/// ```
+/// use memchr::memchr_strided;
+///
+/// let haystack = b"axbxcx";
+/// assert_eq!(memchr_strided(b'c', haystack, 2), Some(4));
+/// assert_eq!(memchr_strided(b'x', haystack, 2), None);
+/// ```
+#[inline]
+pub fn memchr_strided(
+    needle: u8,
+    haystack: &[u8],
+    stride: usize,
+) -> Option<usize> {
+    assert!(stride > 0, "stride must be greater than zero");
+    if stride == 1 {
+        return memchr(needle, haystack);
+    }
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    {
+        if stride.is_power_of_two() && stride <= x86::STRIDED_MAX {
+            if let Some(result) = x86::memchr_strided(needle, haystack, stride)
+            {
+                return result;
+            }
+        }
+    }
+    let mut i = 0;
+    while i < haystack.len() {
+        if haystack[i] == needle {
+            return Some(i);
+        }
+        i = i.saturating_add(stride);
+    }
+    None
+}
+
+/// Search for the first occurrence of a byte at a multiple of `align`.
+///
+/// This returns the smallest index `i` such that `i % align == 0` and
+/// `haystack[i] == needle`, or `None` if one is not found. Indices are
+/// relative to the start of `haystack`, so when `haystack` starts at an
+/// address that is aligned to `align`, so is any match.
+///
+/// Unlike `memchr_strided`, this uses `memchr` to jump from one occurrence
+/// of `needle` to the next, skipping ahead to the next multiple of `align`
+/// whenever an occurrence is misaligned. So it is about as fast as `memchr`
+/// when misaligned occurrences are rare, and degrades to visiting every
+/// occurrence when they are common.
+///
+/// # Panics
+///
+/// This panics if `align` is not a power of two (which includes `0`).
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_aligned;
+///
+/// let mut haystack = [0u8; 64];
+/// haystack[5] = b'x';
+/// haystack[32] = b'x';
+/// assert_eq!(memchr_aligned(b'x', &haystack, 16), Some(32));
+/// assert_eq!(memchr_aligned(b'x', &haystack[..32], 16), None);
+/// ```
+#[inline]
+pub fn memchr_aligned(
+    needle: u8,
+    haystack: &[u8],
+    align: usize,
+) -> Option<usize> {
+    assert!(align.is_power_of_two(), "align must be a power of two");
+    let mask = align - 1;
+    let mut start = 0;
+    while let Some(i) = memchr(needle, &haystack[start..]) {
+        let i = start + i;
+        if i & mask == 0 {
+            return Some(i);
+        }
+        start = (i | mask).checked_add(1)?;
+        if start >= haystack.len() {
+            return None;
+        }
+    }
+    None
+}
+
+/// Returns true if and only if `needle` occurs in `haystack`.
+///
+/// This is operationally the same as `memchr(needle, haystack).is_some()`,
+/// but it can be faster on large haystacks, since it stops as soon as it sees
+/// a match without working out where that match is.
+///
+/// # Example
+///
+/// ```
+/// use memchr::contains_byte;
+///
+/// let haystack = b"the quick brown fox";
+/// assert!(contains_byte(b'k', haystack));
+/// assert!(!contains_byte(b'z', haystack));
+/// ```
+#[inline]
+pub fn contains_byte(needle: u8, haystack: &[u8]) -> bool {
     cfg_if! {
         if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
             #[inline(always)]
-            fn imp(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
-                x86::memchr2(n1, n2, haystack)
+            fn imp(n1: u8, haystack: &[u8]) -> bool {
+                x86::contains_byte(n1, haystack)
             }
         } else {
             #[inline(always)]
-            fn imp(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
-                fallback::memchr2(n1, n2, haystack)
+            fn imp(n1: u8, haystack: &[u8]) -> bool {
+                memchr(n1, haystack).is_some()
             }
         }
     }
     if haystack.is_empty() {
-        None
+        false
     } else {
-        imp(needle1, needle2, haystack)
+        imp(needle, haystack)
     }
 }
 
-/// Like `memchr`, but searches for three bytes instead of one.
+/// Returns true if and only if `needle` occurs in `haystack`, searching
+/// from the end.
+///
+/// This returns the same answer as `contains_byte`, but it searches
+/// backward with `memrchr`, so the last bytes of `haystack` are examined
+/// first. When bytes were just appended to the end of a buffer, they are
+/// likely still in cache, so a match among them is found before any of the
+/// older, colder bytes are touched. It only answers whether `needle` is
+/// present; use `memrchr` to find where its last occurrence is.
+///
+/// # Example
+///
+/// ```
+/// use memchr::contains_byte_from_end;
+///
+/// let haystack = b"the quick brown fox";
+/// assert!(contains_byte_from_end(b'x', haystack));
+/// assert!(!contains_byte_from_end(b'z', haystack));
+/// ```
 #[inline]
-pub fn memchr3(
-    needle1: u8,
-    needle2: u8,
-    needle3: u8,
-    haystack: &[u8],
-) -> Option<usize> {
+pub fn contains_byte_from_end(needle: u8, haystack: &[u8]) -> bool {
+    memrchr(needle, haystack).is_some()
+}
+
+/// Count the number of occurrences of a byte in a slice.
+///
+/// This is operationally the same as `memchr_iter(needle, haystack).count()`,
+/// but it is much faster when `needle` occurs frequently, since it never
+/// needs to stop at each match.
+///
+/// # Example
+///
+/// ```
+/// use memchr::count;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(count(b'o', haystack), 2);
+/// assert_eq!(count(b'z', haystack), 0);
+/// ```
+#[inline]
+pub fn count(needle: u8, haystack: &[u8]) -> usize {
     cfg_if! {
         if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
             #[inline(always)]
-            fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
-                x86::memchr3(n1, n2, n3, haystack)
+            fn imp(n1: u8, haystack: &[u8]) -> usize {
+                x86::count(n1, haystack)
             }
         } else {
             #[inline(always)]
-            fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
-                fallback::memchr3(n1, n2, n3, haystack)
+            fn imp(n1: u8, haystack: &[u8]) -> usize {
+                fallback::count(n1, haystack)
             }
         }
     }
     if haystack.is_empty() {
-        None
+        0
     } else {
-        imp(needle1, needle2, needle3, haystack)
+        imp(needle, haystack)
     }
 }
 
-/// Search for the last occurrence of a byte in a slice.
-///
-/// This returns the index corresponding to the last occurrence of `needle` in
-/// `haystack`, or `None` if one is not found.
+/// Search for the first byte in a slice that is not equal to the given byte.
 ///
-/// While this is operationally the same as something like
-/// `haystack.iter().rposition(|&b| b == needle)`, `memrchr` will use a highly
-/// optimized routine that can be up to an order of magnitude master in some
-/// cases.
+/// This returns the index of the first byte in `haystack` that differs from
+/// `needle`, or `None` if every byte is `needle` (which includes the case of
+/// an empty haystack). This is useful for skipping over long runs of
+/// padding, such as NUL bytes or spaces at the start of a record.
 ///
 /// # Example
 ///
-/// This shows how to find the last position of a byte in a byte string.
-///
 /// ```
-/// use memchr::memrchr;
+/// use memchr::memchr_not;
 ///
-/// let haystack = b"the quick brown fox";
-/// assert_eq!(memrchr(b'o', haystack), Some(17));
+/// assert_eq!(memchr_not(b' ', b"   foo"), Some(3));
+/// assert_eq!(memchr_not(b' ', b"   "), None);
 /// ```
 #[inline]
-pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+pub fn memchr_not(needle: u8, haystack: &[u8]) -> Option<usize> {
     cfg_if! {
         if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
             #[inline(always)]
             fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
-                x86::memrchr(n1, haystack)
-            }
-        } else if #[cfg(all(
-            feature = "libc",
-            target_os = "linux",
-            not(target_arch = "wasm32"),
-            not(target_arch = "windows"),
-            not(target_env = "sgx"),
-        ))] {
-            #[inline(always)]
-            fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
-                c::memrchr(n1, haystack)
+                x86::memchr_not(n1, haystack)
             }
         } else {
             #[inline(always)]
             fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
-                fallback::memrchr(n1, haystack)
+                fallback::memchr_not(n1, haystack)
             }
         }
     }
@@ -268,6 +841,856 @@ pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
     }
 }
 
+/// Search for the last byte that is not equal to the given byte.
+///
+/// This returns the index corresponding to the last byte in `haystack` that
+/// is not `needle`, or `None` if every byte is `needle` (which includes the
+/// case of an empty haystack). This is the reverse of `memchr_not`, and is
+/// useful for trimming a run of padding from the end of a buffer.
+///
+/// Unlike `memchr_not`, this doesn't have a SIMD implementation yet, but it
+/// still skips over a word of padding at a time.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memrchr_not;
+///
+/// assert_eq!(memrchr_not(b' ', b"foo   "), Some(2));
+/// assert_eq!(memrchr_not(b' ', b"   "), None);
+/// ```
+#[inline]
+pub fn memrchr_not(needle: u8, haystack: &[u8]) -> Option<usize> {
+    if haystack.is_empty() {
+        None
+    } else {
+        fallback::memrchr_not(needle, haystack)
+    }
+}
+
+/// The number of bytes that `memchr_early` checks one at a time before
+/// handing off to `memchr`.
+const EARLY_PROBE_LEN: usize = 16;
+
+/// Search for the first occurrence of a byte, optimized for the case where
+/// it is usually found within the first few bytes.
+///
+/// This returns the same result as `memchr`. It first checks the first 16
+/// bytes of `haystack` one at a time, and only calls `memchr` on the rest if
+/// none of them match. This skips the setup that `memchr` does before its
+/// main loop, which dominates when matches are almost always that close by,
+/// like the delimiters in a JSON document. When matches are usually
+/// further away, the extra checks make this slower than `memchr`. See the
+/// `memchr_early` groups in the benchmark suite: finding every quote in flat
+/// JSON was about three times faster than with `memchr`, while finding
+/// every line terminator in English prose was about 20% slower.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_early;
+///
+/// assert_eq!(memchr_early(b':', b"\"key\": 1"), Some(5));
+/// ```
+#[inline]
+pub fn memchr_early(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let probe = cmp::min(EARLY_PROBE_LEN, haystack.len());
+    if let Some(i) = haystack[..probe].iter().position(|&b| b == needle) {
+        return Some(i);
+    }
+    memchr(needle, &haystack[probe..]).map(|i| probe + i)
+}
+
+/// Search for the first occurrence of a byte in the sequence yielded by an
+/// iterator.
+///
+/// This returns the position of the first byte yielded by `iter` that is
+/// equal to `needle`, or `None` if `iter` is exhausted without yielding one.
+/// This is useful for searching the output of a decoder without collecting
+/// it into a slice first. Bytes are gathered into words, and only words that
+/// contain `needle` are checked byte by byte.
+///
+/// Since bytes are consumed a word at a time, up to a word's worth of bytes
+/// after the match may be consumed from `iter`. Pass `iter.by_ref()` to
+/// keep using an iterator after searching it, but don't rely on where it
+/// stopped.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_of_iter;
+///
+/// let decoded = b"ifmmp!xpsme".iter().map(|&b| b - 1);
+/// assert_eq!(memchr_of_iter(b' ', decoded), Some(5));
+/// ```
+#[inline]
+pub fn memchr_of_iter<I: Iterator<Item = u8>>(
+    needle: u8,
+    iter: I,
+) -> Option<usize> {
+    fallback::memchr_of_iter(needle, iter)
+}
+
+/// Return the first index at which two byte slices differ.
+///
+/// This returns the smallest `i` such that `a[i] != b[i]`. When one slice
+/// is a prefix of the other but they have different lengths, the first
+/// difference is the end of the shorter slice, so this returns the length
+/// of the shorter slice. This returns `None` only if `a == b`.
+///
+/// The slices are compared a word at a time.
+///
+/// # Example
+///
+/// ```
+/// use memchr::mismatch;
+///
+/// assert_eq!(mismatch(b"the quick fox", b"the quack fox"), Some(6));
+/// assert_eq!(mismatch(b"the quick fox", b"the quick"), Some(9));
+/// assert_eq!(mismatch(b"the quick fox", b"the quick fox"), None);
+/// ```
+#[inline]
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    match fallback::mismatch(a, b) {
+        Some(i) => Some(i),
+        None if a.len() != b.len() => Some(cmp::min(a.len(), b.len())),
+        None => None,
+    }
+}
+
+/// Returns true if and only if `needle` is a subsequence of `haystack`.
+///
+/// A subsequence is made up of bytes of `haystack` in the same order, but
+/// unlike a substring (as found by `memmem`), they don't need to be
+/// adjacent. For example, `b"abc"` is a subsequence of `b"axbyc"`, but not a
+/// substring of it. This is the kind of matching used by fuzzy finders.
+///
+/// Each byte of `needle` is matched greedily against its first occurrence
+/// in the rest of `haystack`, which is found with `memchr`. An empty needle
+/// is a subsequence of every haystack, while a needle longer than `haystack`
+/// never is.
+///
+/// # Example
+///
+/// ```
+/// use memchr::is_subsequence;
+///
+/// assert!(is_subsequence(b"abc", b"axbyc"));
+/// assert!(!is_subsequence(b"acb", b"axbyc"));
+/// assert!(is_subsequence(b"", b"axbyc"));
+/// ```
+pub fn is_subsequence(needle: &[u8], haystack: &[u8]) -> bool {
+    if needle.len() > haystack.len() {
+        return false;
+    }
+    let mut rest = haystack;
+    for &b in needle {
+        match memchr(b, rest) {
+            None => return false,
+            Some(i) => rest = &rest[i + 1..],
+        }
+    }
+    true
+}
+
+/// Search for the first occurrence of a byte and measure the run of that
+/// byte that starts there.
+///
+/// This returns the index of the first occurrence of `needle` along with
+/// the number of consecutive copies of `needle` starting at that index, or
+/// `None` if `needle` doesn't occur. The length of the run is always at
+/// least `1`. The start is found with `memchr` and the end with
+/// `memchr_not`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_run;
+///
+/// assert_eq!(memchr_run(b'a', b"xxaaax"), Some((2, 3)));
+/// assert_eq!(memchr_run(b'z', b"xxaaax"), None);
+/// ```
+#[inline]
+pub fn memchr_run(needle: u8, haystack: &[u8]) -> Option<(usize, usize)> {
+    let start = memchr(needle, haystack)?;
+    let rest = &haystack[start..];
+    let len = memchr_not(needle, rest).unwrap_or(rest.len());
+    Some((start, len))
+}
+
+/// Search for the first NUL byte in a slice.
+///
+/// This returns the index corresponding to the first occurrence of `\x00` in
+/// `haystack`, or `None` if one is not found. It is operationally the same as
+/// `memchr(0, haystack)`, but it is specialized for what is by far the most
+/// common byte to search for when bridging to C strings.
+///
+/// # Example
+///
+/// This shows how to find the NUL terminator in a byte string.
+///
+/// ```
+/// use memchr::memchr0;
+///
+/// let haystack = b"foo\x00bar\x00";
+/// assert_eq!(memchr0(haystack), Some(3));
+/// ```
+#[inline]
+pub fn memchr0(haystack: &[u8]) -> Option<usize> {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(haystack: &[u8]) -> Option<usize> {
+                x86::memchr(0, haystack)
+            }
+        } else if #[cfg(all(
+            feature = "libc",
+            not(target_arch = "wasm32"),
+            not(target_os = "windows"),
+            not(target_env = "sgx"),
+        ))] {
+            #[inline(always)]
+            fn imp(haystack: &[u8]) -> Option<usize> {
+                c::memchr(0, haystack)
+            }
+        } else {
+            #[inline(always)]
+            fn imp(haystack: &[u8]) -> Option<usize> {
+                fallback::memchr0(haystack)
+            }
+        }
+    }
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(haystack)
+    }
+}
+
+/// The number of bytes that `looks_binary_default` checks for a NUL byte.
+const BINARY_PREFIX_LEN: usize = 8192;
+
+/// Returns true if the first `prefix_len` bytes of `haystack` contain a NUL
+/// byte.
+///
+/// This is the heuristic that tools like `grep` use to decide whether a
+/// file is binary: text in most encodings that are in common use (ASCII,
+/// UTF-8 and the ISO 8859 family) never contains a NUL byte, while most
+/// binary formats contain plenty of them near their start. Only a prefix is
+/// searched so that the decision is cheap even for huge files. It can be
+/// wrong in both directions. For example, UTF-16 text looks binary, and a
+/// binary file without a NUL in its prefix doesn't.
+///
+/// A `prefix_len` greater than `haystack.len()` is clamped to
+/// `haystack.len()`, following the crate's rules for
+/// [out of range indices](index.html#out-of-range-indices).
+///
+/// # Example
+///
+/// ```
+/// use memchr::looks_binary;
+///
+/// assert!(!looks_binary(b"just some text\n", 1024));
+/// assert!(looks_binary(b"\x7FELF\x02\x01\x01\x00", 1024));
+/// assert!(!looks_binary(b"\x7FELF\x02\x01\x01\x00", 4));
+/// ```
+#[inline]
+pub fn looks_binary(haystack: &[u8], prefix_len: usize) -> bool {
+    let prefix = &haystack[..cmp::min(prefix_len, haystack.len())];
+    memchr0(prefix).is_some()
+}
+
+/// Returns true if the first 8 KiB of `haystack` contain a NUL byte.
+///
+/// This is `looks_binary` with a `prefix_len` of `8192`, which is enough to
+/// cover the headers of most binary formats while keeping the search short.
+/// See `looks_binary` for how the heuristic works and when it fails.
+///
+/// # Example
+///
+/// ```
+/// use memchr::looks_binary_default;
+///
+/// assert!(!looks_binary_default(b"just some text\n"));
+/// assert!(looks_binary_default(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"));
+/// ```
+#[inline]
+pub fn looks_binary_default(haystack: &[u8]) -> bool {
+    looks_binary(haystack, BINARY_PREFIX_LEN)
+}
+
+/// Search for the first byte in a slice that matches `needle` in all of the
+/// bits set in `mask`.
+///
+/// This returns the first index `i` such that
+/// `haystack[i] & mask == needle & mask`, or `None` if one is not found. Bits
+/// that are not set in `mask` are ignored, so a `mask` of `0xFF` behaves just
+/// like `memchr` and a `mask` of `0x00` matches any byte.
+///
+/// # Example
+///
+/// This shows how to search for an ASCII letter case insensitively, by
+/// ignoring the bit that distinguishes upper and lower case.
+///
+/// ```
+/// use memchr::memchr_masked;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memchr_masked(b'K', 0xDF, haystack), Some(8));
+/// ```
+#[inline]
+pub fn memchr_masked(needle: u8, mask: u8, haystack: &[u8]) -> Option<usize> {
+    fallback::memchr_masked(needle, mask, haystack)
+}
+
+/// Returns true if and only if every byte in `haystack` is ASCII.
+///
+/// That is, this returns `false` as soon as it finds a byte with its most
+/// significant bit set. An empty haystack is ASCII.
+///
+/// # Example
+///
+/// ```
+/// use memchr::is_ascii;
+///
+/// assert!(is_ascii(b"the quick brown fox"));
+/// assert!(!is_ascii("the quick brown 🦊".as_bytes()));
+/// ```
+#[inline]
+pub fn is_ascii(haystack: &[u8]) -> bool {
+    fallback::is_ascii(haystack)
+}
+
+/// Search for the first occurrence of a little endian UTF-16 code unit.
+///
+/// `haystack` is treated as a sequence of 2-byte little endian units, and
+/// this returns the byte offset of the first unit equal to `needle`, which
+/// is always even. Only units at even offsets are compared, so a match that
+/// straddles two units isn't found. If `haystack` has an odd length, its
+/// last byte isn't part of any unit and is ignored.
+///
+/// The units are compared a word at a time, with a word in which the needle
+/// is repeated in little endian order.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{memchr_u16_be, memchr_u16_le};
+///
+/// // "hi!" in UTF-16LE.
+/// let haystack = b"h\x00i\x00!\x00";
+/// assert_eq!(memchr_u16_le(0x0069, haystack), Some(2));
+/// assert_eq!(memchr_u16_be(0x0069, haystack), None);
+/// ```
+#[inline]
+pub fn memchr_u16_le(needle: u16, haystack: &[u8]) -> Option<usize> {
+    fallback::memchr_u16(needle.to_le_bytes(), haystack)
+}
+
+/// Search for the first occurrence of a big endian UTF-16 code unit.
+///
+/// This is the same as `memchr_u16_le`, except that `haystack` is treated
+/// as a sequence of big endian units.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{memchr_u16_be, memchr_u16_le};
+///
+/// // "hi!" in UTF-16BE.
+/// let haystack = b"\x00h\x00i\x00!";
+/// assert_eq!(memchr_u16_be(0x0069, haystack), Some(2));
+/// assert_eq!(memchr_u16_le(0x0069, haystack), None);
+/// ```
+#[inline]
+pub fn memchr_u16_be(needle: u16, haystack: &[u8]) -> Option<usize> {
+    fallback::memchr_u16(needle.to_be_bytes(), haystack)
+}
+
+/// Search for the first byte that isn't printable ASCII.
+///
+/// This returns the index of the first byte in `haystack` that is outside
+/// of `0x20..=0x7E`, i.e., the first ASCII control byte, DEL or non-ASCII
+/// byte, or `None` if every byte is printable. This is useful for checking
+/// that a buffer can be displayed as is. Note that tab and newline are not
+/// printable by this definition.
+///
+/// Like `is_ascii`, this checks a word at a time, and only looks at the
+/// individual bytes of words that contain a byte outside of the range.
+///
+/// # Example
+///
+/// ```
+/// use memchr::first_non_printable;
+///
+/// assert_eq!(first_non_printable(b"the quick brown fox"), None);
+/// assert_eq!(first_non_printable(b"the quick\tbrown fox"), Some(9));
+/// assert_eq!(first_non_printable(b"the quick brown \x7F"), Some(16));
+/// ```
+#[inline]
+pub fn first_non_printable(haystack: &[u8]) -> Option<usize> {
+    use fallback::{HI_USIZE, LO_USIZE};
+
+    // There are two subtract-borrow style tests here, one for each end of
+    // the range. Subtracting `0x20` from a byte less than `0x20` borrows,
+    // which sets its high bit, while `!x` rules out bytes that had their
+    // high bit set to begin with. (A borrow can also flag the byte after a
+    // control byte, but only when there is a control byte, so no word with
+    // one is missed.) Adding `0x01` to the low 7 bits of a byte sets its high
+    // bit only if those bits are `0x7F`, and can never carry into the next
+    // byte. Along with the high bit of `x` itself, that flags exactly the
+    // bytes greater than `0x7E`.
+    let has_non_printable = |x: usize| {
+        let below = x.wrapping_sub(LO_USIZE * 0x20) & !x;
+        let above = ((x & !HI_USIZE) + LO_USIZE) | x;
+        (below | above) & HI_USIZE != 0
+    };
+    // This is `!(0x20..=0x7E).contains(&b)`, which needs Rust 1.35.
+    let is_non_printable = |b: u8| b.wrapping_sub(0x20) > 0x7E - 0x20;
+    memchr_pred(has_non_printable, is_non_printable, haystack)
+}
+
+/// Search for the first byte that satisfies a predicate, using a coarse
+/// word-at-a-time test to skip over bytes that can't match.
+///
+/// This is for predicates that can't be expressed as equality with a few
+/// bytes, but for which a cheap test on an entire word (in the style of
+/// the SWAR tricks used by this crate's fallback implementation) can rule
+/// out many words at once. `haystack` is read one `usize` at a time, in
+/// native byte order, and each word is passed to `coarse`. If `coarse`
+/// returns `false`, the whole word is skipped. Otherwise, each of its bytes
+/// is passed to `fine`, which decides whether that byte matches. Any bytes
+/// at the end of `haystack` that don't fill a word are always passed to
+/// `fine`.
+///
+/// This returns the index of the first byte for which `fine` returns
+/// `true`, or `None` if there isn't one.
+///
+/// # Contract
+///
+/// `coarse` may report false positives, which only cost a call to `fine`
+/// for each byte in the word, but it must never report false negatives:
+/// if any byte in a word satisfies `fine`, then `coarse` must return `true`
+/// for that word. Otherwise, matches in that word are silently skipped.
+/// A `coarse` that always returns `true` makes this the same as
+/// `haystack.iter().position(fine)`.
+///
+/// Since this is generic over both predicates, it is compiled anew for
+/// each pair of predicates it's called with. None of the SIMD kernels in
+/// this crate are used.
+///
+/// # Example
+///
+/// This finds the first byte in either `0x80..=0x8F` or `0xF0..=0xFF`.
+/// Both ranges only contain bytes with their most significant bit set, so a
+/// word without any such bit can be skipped.
+///
+/// ```
+/// use std::usize;
+/// use memchr::memchr_pred;
+///
+/// let hi = usize::MAX / 255 * 0x80;
+/// let coarse = |word: usize| word & hi != 0;
+/// let fine = |b: u8| b & 0xF0 == 0x80 || b >= 0xF0;
+///
+/// let haystack = b"ascii only, then \xC3\xA9 and \xF0\x9F\x98\x80";
+/// assert_eq!(memchr_pred(coarse, fine, haystack), Some(24));
+/// ```
+#[inline]
+pub fn memchr_pred(
+    coarse_mask: impl Fn(usize) -> bool,
+    fine: impl Fn(u8) -> bool,
+    haystack: &[u8],
+) -> Option<usize> {
+    fallback::memchr_pred(coarse_mask, fine, haystack)
+}
+
+/// Return the index of the first UTF-8 character boundary in `haystack` at
+/// or after `from`.
+///
+/// A character boundary is any byte that is not a UTF-8 continuation byte,
+/// that is, any byte `b` with `b & 0xC0 != 0x80`. The haystack doesn't need
+/// to be valid UTF-8. This returns `None` if every byte from `from` onwards
+/// is a continuation byte, in which case the next boundary is the end of the
+/// haystack. This also returns `None` if `from >= haystack.len()`, following
+/// the crate's rules for
+/// [out of range indices](index.html#out-of-range-indices).
+///
+/// # Example
+///
+/// ```
+/// use memchr::next_char_boundary;
+///
+/// let haystack = "é€𐍈".as_bytes();
+/// assert_eq!(next_char_boundary(haystack, 0), Some(0));
+/// assert_eq!(next_char_boundary(haystack, 1), Some(2));
+/// assert_eq!(next_char_boundary(haystack, 6), None);
+/// ```
+#[inline]
+pub fn next_char_boundary(haystack: &[u8], from: usize) -> Option<usize> {
+    let haystack = haystack.get(from..)?;
+    fallback::find_char_boundary(haystack).map(|i| from + i)
+}
+
+/// Search for the first occurrence of an ASCII byte in a slice, and check
+/// that the bytes before it are valid UTF-8.
+///
+/// This returns `Ok(Some(i))` if `needle` first occurs at index `i` and
+/// `&haystack[..i]` is valid UTF-8, so that it can be converted to a `str`
+/// without validating it again. If `needle` doesn't occur, the entire
+/// haystack is validated instead, and the result is `Ok(None)` when it is
+/// valid UTF-8.
+///
+/// Since `needle` is ASCII, it can never occur inside a multi-byte UTF-8
+/// sequence, so a match always falls on a character boundary. Any invalid
+/// UTF-8 after the match isn't looked at.
+///
+/// # Errors
+///
+/// This returns the `Utf8Error` for the bytes that were validated, if they
+/// aren't valid UTF-8. Its `valid_up_to` is relative to the start of
+/// `haystack`.
+///
+/// # Panics
+///
+/// This panics if `needle` isn't ASCII.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_utf8_checked;
+///
+/// let haystack = "clé=valeur".as_bytes();
+/// assert_eq!(memchr_utf8_checked(b'=', haystack), Ok(Some(4)));
+///
+/// let err = memchr_utf8_checked(b'=', b"cl\xFF=valeur").unwrap_err();
+/// assert_eq!(err.valid_up_to(), 2);
+/// ```
+#[inline]
+pub fn memchr_utf8_checked(
+    needle: u8,
+    haystack: &[u8],
+) -> Result<Option<usize>, Utf8Error> {
+    assert!(needle.is_ascii(), "needle must be ASCII");
+    match memchr(needle, haystack) {
+        None => str::from_utf8(haystack).map(|_| None),
+        Some(i) => str::from_utf8(&haystack[..i]).map(|_| Some(i)),
+    }
+}
+
+/// Like `memchr`, but searches for two bytes instead of one.
+#[inline]
+pub fn memchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+                x86::memchr2(n1, n2, haystack)
+            }
+        } else {
+            #[inline(always)]
+            fn imp(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+                fallback::memchr2(n1, n2, haystack)
+            }
+        }
+    }
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle1, needle2, haystack)
+    }
+}
+
+/// Like `memchr2`, but also returns the byte that was found.
+///
+/// This returns `(i, haystack[i])` for the index `i` returned by
+/// `memchr2(needle1, needle2, haystack)`, so the byte is always one of
+/// `needle1` or `needle2`, and callers can tell which one matched without
+/// reading the haystack again. When both needles are the same byte, that
+/// byte is returned.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr2_which;
+///
+/// let haystack = b"f(a, (b))";
+/// assert_eq!(memchr2_which(b'(', b')', haystack), Some((1, b'(')));
+/// assert_eq!(memchr2_which(b'(', b')', &haystack[6..]), Some((1, b')')));
+/// assert_eq!(memchr2_which(b'[', b']', haystack), None);
+/// ```
+#[inline]
+pub fn memchr2_which(
+    needle1: u8,
+    needle2: u8,
+    haystack: &[u8],
+) -> Option<(usize, u8)> {
+    memchr2(needle1, needle2, haystack).map(|i| (i, haystack[i]))
+}
+
+/// Like `memchr`, but searches for three bytes instead of one.
+#[inline]
+pub fn memchr3(
+    needle1: u8,
+    needle2: u8,
+    needle3: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
+                x86::memchr3(n1, n2, n3, haystack)
+            }
+        } else {
+            #[inline(always)]
+            fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
+                fallback::memchr3(n1, n2, n3, haystack)
+            }
+        }
+    }
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle1, needle2, needle3, haystack)
+    }
+}
+
+/// Search for the last occurrence of a byte in a slice.
+///
+/// This returns the index corresponding to the last occurrence of `needle` in
+/// `haystack`, or `None` if one is not found.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().rposition(|&b| b == needle)`, `memrchr` will use a highly
+/// optimized routine that can be up to an order of magnitude master in some
+/// cases.
+///
+/// # Example
+///
+/// This shows how to find the last position of a byte in a byte string.
+///
+/// ```
+/// use memchr::memrchr;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memrchr(b'o', haystack), Some(17));
+/// ```
+#[inline]
+pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+                x86::memrchr(n1, haystack)
+            }
+        } else if #[cfg(all(
+            feature = "libc",
+            target_os = "linux",
+            not(target_arch = "wasm32"),
+            not(target_os = "windows"),
+            not(target_env = "sgx"),
+        ))] {
+            #[inline(always)]
+            fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+                c::memrchr(n1, haystack)
+            }
+        } else {
+            #[inline(always)]
+            fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+                fallback::memrchr(n1, haystack)
+            }
+        }
+    }
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle, haystack)
+    }
+}
+
+/// Search for the last occurrence of a byte in a slice, and return its
+/// distance from the end of the slice.
+///
+/// This returns `haystack.len() - 1 - i`, where `i` is the index of the
+/// last occurrence of `needle` in `haystack`, or `None` if one is not found.
+/// That is, a match on the last byte of `haystack` returns `0`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memrchr_from_end;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memrchr_from_end(b'o', haystack), Some(1));
+/// assert_eq!(memrchr_from_end(b'x', haystack), Some(0));
+/// ```
+#[inline]
+pub fn memrchr_from_end(needle: u8, haystack: &[u8]) -> Option<usize> {
+    memrchr(needle, haystack).map(|i| haystack.len() - 1 - i)
+}
+
+/// Search for the first occurrence of a byte within the first `max` bytes of
+/// a slice.
+///
+/// This returns `Ok(i)` if `needle` occurs at an index `i < max`, and
+/// `Err(max)` if it doesn't. At most `max` bytes are searched, so this is a
+/// cheap way to enforce a limit on the length of a record: when its
+/// terminator isn't found within the limit, the record is too long (or
+/// incomplete) and the rest of the haystack doesn't need to be searched.
+///
+/// A `max` greater than `haystack.len()` is clamped to `haystack.len()`,
+/// following the crate's rules for
+/// [out of range indices](index.html#out-of-range-indices). So if the
+/// error's value is `haystack.len()`, then `needle` doesn't occur anywhere
+/// in `haystack`, while a smaller value means that it might occur after the
+/// limit.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_or_overrun;
+///
+/// let records = b"short\na rather long record\n";
+/// assert_eq!(memchr_or_overrun(b'\n', records, 8), Ok(5));
+/// assert_eq!(memchr_or_overrun(b'\n', &records[6..], 8), Err(8));
+/// assert_eq!(memchr_or_overrun(b'z', records, 100), Err(records.len()));
+/// ```
+#[inline]
+pub fn memchr_or_overrun(
+    needle: u8,
+    haystack: &[u8],
+    max: usize,
+) -> Result<usize, usize> {
+    let max = cmp::min(max, haystack.len());
+    memchr(needle, &haystack[..max]).ok_or(max)
+}
+
+/// Search for the first occurrence of a byte, returning a reference to it.
+///
+/// This returns a reference to the first byte in `haystack` that is equal
+/// to `needle`, or `None` if there isn't one. The reference borrows from
+/// `haystack`, so it can be used to get at the bytes around the match
+/// without indexing `haystack` again. `memchr`, which returns the index of
+/// the match, is the canonical form of this search.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_ref;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memchr_ref(b'q', haystack), Some(&b'q'));
+/// assert_eq!(memchr_ref(b'z', haystack), None);
+/// ```
+#[inline]
+pub fn memchr_ref(needle: u8, haystack: &[u8]) -> Option<&u8> {
+    memchr(needle, haystack).map(|i| &haystack[i])
+}
+
+/// Search for the last occurrence of a byte at or before the given index.
+///
+/// This returns the greatest index `i <= at` such that
+/// `haystack[i] == needle`, or `None` if one is not found. This is useful
+/// when walking backward through a haystack from a cursor.
+///
+/// If `at` is greater than or equal to the length of `haystack`, then it is
+/// clamped to `haystack.len() - 1`, which makes this the same as `memrchr`.
+/// This follows the crate's rules for
+/// [out of range indices](index.html#out-of-range-indices).
+///
+/// # Example
+///
+/// ```
+/// use memchr::memrchr_at;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memrchr_at(b'o', haystack, 16), Some(12));
+/// assert_eq!(memrchr_at(b'o', haystack, 17), Some(17));
+/// assert_eq!(memrchr_at(b'o', haystack, 100), Some(17));
+/// assert_eq!(memrchr_at(b'o', haystack, 11), None);
+/// ```
+#[inline]
+pub fn memrchr_at(needle: u8, haystack: &[u8], at: usize) -> Option<usize> {
+    let end = cmp::min(at, haystack.len().saturating_sub(1));
+    memrchr(needle, haystack.get(..end + 1)?)
+}
+
+/// Search for both the first and the last occurrence of a byte.
+///
+/// This returns `(first, last)`, where `first` is the same as
+/// `memchr(needle, haystack)` and `last` is the same as
+/// `memrchr(needle, haystack)`, or `None` if `needle` doesn't occur. When
+/// `needle` occurs exactly once, `first == last`. This is useful for
+/// computing the span covered by a byte.
+///
+/// The reverse search only covers the bytes from `first` onwards, so no
+/// byte is searched twice.
+///
+/// # Example
+///
+/// ```
+/// use memchr::first_and_last;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(first_and_last(b'o', haystack), Some((12, 17)));
+/// assert_eq!(first_and_last(b'q', haystack), Some((4, 4)));
+/// assert_eq!(first_and_last(b'z', haystack), None);
+/// ```
+#[inline]
+pub fn first_and_last(
+    needle: u8,
+    haystack: &[u8],
+) -> Option<(usize, usize)> {
+    let first = memchr(needle, haystack)?;
+    // The byte at `first` matches, so the reverse search always succeeds.
+    let last = first + memrchr(needle, &haystack[first..]).unwrap_or(0);
+    Some((first, last))
+}
+
+/// Search for the first occurrence of a byte without stopping early.
+///
+/// This returns the same result as `memchr`, but it always examines every
+/// byte in `haystack` and doesn't branch on any of them, so that the time it
+/// takes doesn't depend on where (or whether) `needle` occurs. It is meant
+/// for searching secret data, where the position of a match must not leak
+/// through timing. In exchange, it is much slower than `memchr`: it never
+/// uses SIMD and always takes time proportional to `haystack.len()`.
+///
+/// This is best-effort. Only the returned value reveals whether `needle`
+/// was found, but the compiler or the CPU are free to reintroduce
+/// data-dependent timing, and no guarantee is made that they don't.
+/// Callers with strict requirements should verify the generated code for
+/// their target.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_ct;
+///
+/// assert_eq!(memchr_ct(b'k', b"the quick brown fox"), Some(8));
+/// assert_eq!(memchr_ct(b'z', b"the quick brown fox"), None);
+/// ```
+pub fn memchr_ct(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let mut found = 0;
+    // All ones until the first match, and zero afterwards.
+    let mut searching = !0usize;
+    for (i, &b) in haystack.iter().enumerate() {
+        // `diff - 1` wraps around only when `diff` is zero, which sets the
+        // bit above the 8 bits that `diff` can occupy.
+        let diff = (b ^ needle) as usize;
+        let is_match = (diff.wrapping_sub(1) >> 8) & 1;
+        let first = is_match.wrapping_neg() & searching;
+        found |= i & first;
+        searching &= !first;
+    }
+    if searching == 0 {
+        Some(found)
+    } else {
+        None
+    }
+}
+
 /// Like `memrchr`, but searches for two bytes instead of one.
 #[inline]
 pub fn memrchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
@@ -0,0 +1,165 @@
+// This module defines routines for working with the lines of a haystack,
+// where each line is terminated by `\n`.
+
+use core::cmp;
+
+use {count, memchr, memchr_iter, memrchr};
+
+/// Return the 1-based line and column of the byte at `offset`.
+///
+/// The line is one more than the number of `\n` bytes before `offset`,
+/// which are counted with `count`, and the column is one more than the
+/// distance from the start of that line, which is found with `memrchr`.
+/// Columns count bytes, not characters. A `\n` itself belongs to the line
+/// that it terminates, and the line after it starts at the next byte.
+///
+/// If `offset` is greater than the length of `haystack`, then it is clamped
+/// to `haystack.len()`, which is the position just past the last byte. This
+/// follows the crate's rules for
+/// [out of range indices](index.html#out-of-range-indices).
+///
+/// # Example
+///
+/// ```
+/// use memchr::line_col;
+///
+/// let haystack = b"foo\nbar\nbaz";
+/// assert_eq!(line_col(haystack, 0), (1, 1));
+/// assert_eq!(line_col(haystack, 5), (2, 2));
+/// assert_eq!(line_col(haystack, 3), (1, 4));
+/// ```
+pub fn line_col(haystack: &[u8], offset: usize) -> (usize, usize) {
+    let before = &haystack[..cmp::min(offset, haystack.len())];
+    let line_start = memrchr(b'\n', before).map_or(0, |i| i + 1);
+    (count(b'\n', before) + 1, before.len() - line_start + 1)
+}
+
+/// Return the number of lines in `haystack`.
+///
+/// Lines are counted in the same way as `nth_line_offset`: this is the
+/// number of `\n` bytes, which are counted with `count`, plus one for a last
+/// line that doesn't end with `\n`. So an empty haystack has no lines, and
+/// `nth_line(n, haystack)` is `Some` exactly when `n < count_lines(haystack)`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::count_lines;
+///
+/// assert_eq!(count_lines(b"foo\nbar\n"), 2);
+/// assert_eq!(count_lines(b"foo\nbar"), 2);
+/// assert_eq!(count_lines(b"\n\n"), 2);
+/// assert_eq!(count_lines(b""), 0);
+/// ```
+pub fn count_lines(haystack: &[u8]) -> usize {
+    let unterminated = match haystack.last() {
+        None | Some(&b'\n') => 0,
+        Some(_) => 1,
+    };
+    count(b'\n', haystack) + unterminated
+}
+
+/// Return the byte offset at which the 0-based line `n` starts.
+///
+/// Line `0` starts at offset `0`, and every other line starts just after
+/// the `n`th `\n`, which is found with `memchr_iter`. Like `str::lines`,
+/// the last line doesn't need to end with `\n`, but an empty line after a
+/// trailing `\n` isn't counted. So this returns `None` if `haystack` has
+/// fewer than `n + 1` lines, including when `haystack` is empty.
+///
+/// # Example
+///
+/// ```
+/// use memchr::nth_line_offset;
+///
+/// let haystack = b"foo\nbar\nbaz\n";
+/// assert_eq!(nth_line_offset(0, haystack), Some(0));
+/// assert_eq!(nth_line_offset(2, haystack), Some(8));
+/// assert_eq!(nth_line_offset(3, haystack), None);
+/// ```
+pub fn nth_line_offset(n: usize, haystack: &[u8]) -> Option<usize> {
+    let start = match n.checked_sub(1) {
+        None => 0,
+        Some(n) => memchr_iter(b'\n', haystack).nth(n)? + 1,
+    };
+    if start < haystack.len() {
+        Some(start)
+    } else {
+        None
+    }
+}
+
+/// Return the 0-based line `n` of `haystack`, without its terminator.
+///
+/// Lines are counted in the same way as `nth_line_offset`, and the line
+/// ends just before the next `\n`, or at the end of `haystack` for a last
+/// line without one. Only the `\n` is removed, so a line that ends with
+/// `\r\n` keeps its `\r`. This returns `None` if `haystack` has fewer than
+/// `n + 1` lines.
+///
+/// # Example
+///
+/// ```
+/// use memchr::nth_line;
+///
+/// let haystack = b"foo\nbar\nbaz";
+/// assert_eq!(nth_line(1, haystack), Some(&b"bar"[..]));
+/// assert_eq!(nth_line(2, haystack), Some(&b"baz"[..]));
+/// assert_eq!(nth_line(3, haystack), None);
+/// ```
+pub fn nth_line(n: usize, haystack: &[u8]) -> Option<&[u8]> {
+    let start = nth_line_offset(n, haystack)?;
+    let line = &haystack[start..];
+    Some(&line[..memchr(b'\n', line).unwrap_or(line.len())])
+}
+
+/// The newline convention used by a haystack.
+///
+/// This is returned by `detect_line_ending`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineEnding {
+    /// Every line is terminated by `\n` alone.
+    Lf,
+    /// Every line is terminated by `\r\n`.
+    CrLf,
+    /// Some lines are terminated by `\n` alone and others by `\r\n`.
+    Mixed,
+    /// The haystack contains no `\n` at all.
+    None,
+}
+
+/// Detect whether the lines of `haystack` end with `\n` or `\r\n`.
+///
+/// Each `\n` is found with `memchr_iter` and is classified by whether the
+/// byte before it is `\r`. The search stops as soon as both styles have
+/// been seen, since the result can then only be `LineEnding::Mixed`. A `\r`
+/// that isn't followed by `\n` isn't a line ending, so it is ignored.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{LineEnding, detect_line_ending};
+///
+/// assert_eq!(detect_line_ending(b"foo\nbar\n"), LineEnding::Lf);
+/// assert_eq!(detect_line_ending(b"foo\r\nbar\r\n"), LineEnding::CrLf);
+/// assert_eq!(detect_line_ending(b"foo\r\nbar\n"), LineEnding::Mixed);
+/// assert_eq!(detect_line_ending(b"foo"), LineEnding::None);
+/// ```
+pub fn detect_line_ending(haystack: &[u8]) -> LineEnding {
+    let (mut lf, mut crlf) = (false, false);
+    for i in memchr_iter(b'\n', haystack) {
+        if i > 0 && haystack[i - 1] == b'\r' {
+            crlf = true;
+        } else {
+            lf = true;
+        }
+        if lf && crlf {
+            return LineEnding::Mixed;
+        }
+    }
+    match (lf, crlf) {
+        (false, false) => LineEnding::None,
+        (true, false) => LineEnding::Lf,
+        _ => LineEnding::CrLf,
+    }
+}
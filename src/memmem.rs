@@ -0,0 +1,504 @@
+use core::cmp;
+use core::fmt;
+
+use {memchr, memrchr};
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+use x86;
+
+/// Search for the first occurrence of a byte string in a slice.
+///
+/// This returns the starting index of the first occurrence of `needle` in
+/// `haystack`, or `None` if one is not found. An empty needle matches at
+/// index `0` of every haystack.
+///
+/// This builds a new `Finder` on every call. When searching for the same
+/// needle in many haystacks, build a `Finder` once and reuse it instead.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memmem(b"brown", haystack), Some(10));
+/// assert_eq!(memmem(b"lazy", haystack), None);
+/// ```
+#[inline]
+pub fn memmem(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    Finder::new(needle).find(haystack)
+}
+
+/// Returns true if and only if `haystack` ends with `suffix`.
+///
+/// This gives the same answer as `haystack.ends_with(suffix)`. The last byte
+/// of `suffix` is first looked for with `memrchr` in the last
+/// `suffix.len()` bytes of `haystack`, which rejects most mismatches
+/// without comparing the rest. An empty suffix is a suffix of every
+/// haystack, while a suffix longer than `haystack` never is.
+///
+/// # Example
+///
+/// ```
+/// use memchr::ends_with;
+///
+/// assert!(ends_with(b".gz", b"archive.tar.gz"));
+/// assert!(!ends_with(b".gz", b"archive.tar"));
+/// assert!(!ends_with(b"archive.tar.gz", b".gz"));
+/// ```
+#[inline]
+pub fn ends_with(suffix: &[u8], haystack: &[u8]) -> bool {
+    let (&last, init) = match suffix.split_last() {
+        None => return true,
+        Some(split) => split,
+    };
+    if haystack.len() < suffix.len() {
+        return false;
+    }
+    let tail = &haystack[haystack.len() - suffix.len()..];
+    memrchr(last, tail) == Some(init.len()) && &tail[..init.len()] == init
+}
+
+/// Search for the last occurrence of a byte string in a slice.
+///
+/// This returns the starting index of the last occurrence of `suffix` in
+/// `haystack`, or `None` if one is not found. Candidates are found by
+/// searching backward for the last byte of `suffix` with `memrchr`, which
+/// makes this fast when looking for a trailer near the end of a buffer,
+/// such as a signature that may be followed by some trailing data. When
+/// `haystack` ends with `suffix`, the result is
+/// `haystack.len() - suffix.len()`. An empty suffix matches at
+/// `haystack.len()`. Like `Finder`, this takes `O(n + m)` time whatever the
+/// input, since it finishes with the Two-Way algorithm once the candidates
+/// cost too many comparisons.
+///
+/// # Example
+///
+/// ```
+/// use memchr::rfind_suffix;
+///
+/// let haystack = b"PK\x01\x02 ... PK\x05\x06 trailing comment";
+/// assert_eq!(rfind_suffix(b"PK\x05\x06", haystack), Some(9));
+/// assert_eq!(rfind_suffix(b"PK\x07\x08", haystack), None);
+/// ```
+pub fn rfind_suffix(suffix: &[u8], haystack: &[u8]) -> Option<usize> {
+    rfind(suffix, haystack)
+}
+
+/// A single substring searcher with its preprocessing done up front.
+///
+/// A `Finder` builds a Boyer-Moore-Horspool skip table for its needle once,
+/// when it is constructed. Each subsequent search reuses that table, which
+/// amortizes its cost when the same needle is searched for in many
+/// haystacks. On `x86_64` CPUs that support AVX2, needles of up to 16 bytes
+/// are instead searched for by anchoring on their first and last bytes with
+/// SIMD, which is faster when those bytes are common in the haystack. CPUs
+/// without AVX2 but with SSE 4.2 search for them with the `PCMPESTRI` string
+/// instruction instead.
+///
+/// # Worst case
+///
+/// Searching takes `O(n + m)` time for a haystack of length `n` and a
+/// needle of length `m`, whatever the input, so it is safe to search for
+/// untrusted needles in untrusted haystacks. The skip table usually skips
+/// most of the haystack, but on its own it does `O(n * m)` comparisons for
+/// some inputs, like the needle `aaa…aba` in a haystack of `a`s. So the
+/// search counts its comparisons, and once they exceed 4 per byte of
+/// haystack passed, it finishes with the Two-Way algorithm, which needs no
+/// more than `2 * n` comparisons. The SIMD routines verify each candidate
+/// with at most 16 comparisons, so they are linear as well.
+///
+/// # Example
+///
+/// ```
+/// use memchr::Finder;
+///
+/// let finder = Finder::new(b"fox");
+/// assert_eq!(finder.find(b"the quick brown fox"), Some(16));
+/// assert_eq!(finder.find(b"the lazy dog"), None);
+///
+/// let matches: Vec<usize> = finder.find_iter(b"fox, fox and fox").collect();
+/// assert_eq!(matches, vec![0, 5, 13]);
+/// ```
+#[derive(Clone)]
+pub struct Finder<'n> {
+    needle: &'n [u8],
+    // For each byte, the distance to shift the window when that byte is
+    // aligned with the last byte of the needle.
+    skip: [usize; 256],
+    // The factorization of the needle used once the skip table has done
+    // too many comparisons.
+    two_way: TwoWay,
+}
+
+impl<'n> Finder<'n> {
+    /// Create a new finder for the given needle.
+    pub fn new(needle: &'n [u8]) -> Finder<'n> {
+        let mut skip = [needle.len(); 256];
+        if let Some((_, init)) = needle.split_last() {
+            for (i, &b) in init.iter().enumerate() {
+                skip[b as usize] = init.len() - i;
+            }
+        }
+        Finder { needle, skip, two_way: TwoWay::new(needle) }
+    }
+
+    /// Return the needle searched for by this finder.
+    #[inline]
+    pub fn needle(&self) -> &'n [u8] {
+        self.needle
+    }
+
+    /// Search for the first occurrence of this finder's needle in
+    /// `haystack`.
+    ///
+    /// This returns the starting index of the match, or `None` if one is
+    /// not found.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        let needle = self.needle;
+        match needle.len() {
+            0 => return Some(0),
+            1 => return memchr(needle[0], haystack),
+            _ => {}
+        }
+        #[cfg(all(
+            target_arch = "x86_64",
+            memchr_runtime_simd,
+            feature = "use_std",
+        ))]
+        {
+            if needle.len() <= x86::MEMMEM_SHORT_MAX {
+                if let Some(result) = x86::memmem_short(needle, haystack) {
+                    return result;
+                }
+            }
+        }
+        self.find_scalar(haystack, &mut 0)
+    }
+
+    /// Search for this finder's needle, which must have at least 2 bytes,
+    /// with its skip table, and finish with Two-Way once the skip table has
+    /// done more than `HORSPOOL_BUDGET` comparisons per byte of haystack.
+    ///
+    /// The number of byte comparisons done is added to `comparisons`, where
+    /// a verification that compares slices counts as comparing all of them.
+    fn find_scalar(
+        &self,
+        haystack: &[u8],
+        comparisons: &mut usize,
+    ) -> Option<usize> {
+        let needle = self.needle;
+        let (&last, init) = needle.split_last().unwrap();
+        let mut i = 0;
+        while haystack.len() - i >= needle.len() {
+            if *comparisons > HORSPOOL_BUDGET * (i + needle.len()) {
+                let rest = &haystack[i..];
+                let found = self.two_way.find(needle, rest, comparisons);
+                return found.map(|j| i + j);
+            }
+            let b = haystack[i + init.len()];
+            *comparisons += 1;
+            if b == last {
+                *comparisons += init.len();
+                if &haystack[i..i + init.len()] == init {
+                    return Some(i);
+                }
+            }
+            i += self.skip[b as usize];
+        }
+        None
+    }
+
+    /// Return an iterator over all non-overlapping occurrences of this
+    /// finder's needle in `haystack`.
+    ///
+    /// The iterator yields the starting index of each match. An empty needle
+    /// matches at every position, including the end of the haystack.
+    #[inline]
+    pub fn find_iter<'f, 'h>(
+        &'f self,
+        haystack: &'h [u8],
+    ) -> FindIter<'f, 'h> {
+        FindIter { finder: self, haystack, position: 0 }
+    }
+}
+
+/// The number of comparisons per byte of haystack that the skip table may
+/// do before the search switches to Two-Way.
+const HORSPOOL_BUDGET: usize = 4;
+
+/// Search for the last occurrence of `needle` in `haystack`.
+///
+/// Candidates are found by searching backward for the last byte of `needle`
+/// with `memrchr`, and each one is verified by comparing the rest of the
+/// needle. Like the skip table in `Finder`, that alone does `O(n * m)`
+/// comparisons for some inputs, like the needle `abaa…a` in a haystack of
+/// `a`s. So once the verifications exceed `HORSPOOL_BUDGET` comparisons per
+/// byte of haystack passed, the search finishes with Two-Way run backward,
+/// which keeps it `O(n + m)`. An empty needle matches at `haystack.len()`.
+pub fn rfind(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    rfind_counted(needle, haystack, &mut 0)
+}
+
+/// Like `rfind`, but adds the number of byte comparisons done to
+/// `comparisons`, counted the same way as by `Finder::find_scalar`.
+fn rfind_counted(
+    needle: &[u8],
+    haystack: &[u8],
+    comparisons: &mut usize,
+) -> Option<usize> {
+    let (&last, init) = match needle.split_last() {
+        None => return Some(haystack.len()),
+        Some(split) => split,
+    };
+    if haystack.len() < needle.len() {
+        return None;
+    }
+    // Only the bytes at which a match could end are searched, so that any
+    // candidate has room for the rest of the needle before it. Since the
+    // search starts at `init.len()`, each index it returns is where the
+    // candidate match starts. Every match that ends at or after `end` has
+    // been ruled out.
+    let mut end = haystack.len();
+    loop {
+        let passed = haystack.len() - end;
+        if *comparisons > HORSPOOL_BUDGET * (passed + needle.len()) {
+            let rest = Rev(&haystack[..end]);
+            let two_way = TwoWay::new(Rev(needle));
+            let found = two_way.find(Rev(needle), rest, comparisons);
+            return found.map(|j| end - j - needle.len());
+        }
+        let start = memrchr(last, &haystack[init.len()..end])?;
+        *comparisons += init.len();
+        if &haystack[start..start + init.len()] == init {
+            return Some(start);
+        }
+        end = start + init.len();
+    }
+}
+
+/// Search for `needle`, with at least 2 bytes, in `haystack` without SIMD,
+/// and return the number of byte comparisons that the search did.
+#[cfg(test)]
+pub fn scalar_comparisons(
+    needle: &[u8],
+    haystack: &[u8],
+) -> (Option<usize>, usize) {
+    let mut comparisons = 0;
+    let found = Finder::new(needle).find_scalar(haystack, &mut comparisons);
+    (found, comparisons)
+}
+
+/// Search for `needle`, with at least 2 bytes, in `haystack` with Two-Way
+/// alone.
+#[cfg(test)]
+pub fn two_way(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    TwoWay::new(needle).find(needle, haystack, &mut 0)
+}
+
+/// Search for the last occurrence of `needle`, with at least 1 byte, in
+/// `haystack`, and return the number of byte comparisons that the search
+/// did.
+#[cfg(test)]
+pub fn rfind_comparisons(
+    needle: &[u8],
+    haystack: &[u8],
+) -> (Option<usize>, usize) {
+    let mut comparisons = 0;
+    let found = rfind_counted(needle, haystack, &mut comparisons);
+    (found, comparisons)
+}
+
+/// Search for the last occurrence of `needle`, with at least 1 byte, in
+/// `haystack` with Two-Way run backward alone.
+#[cfg(test)]
+pub fn two_way_rev(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    let found = TwoWay::new(Rev(needle)).find(
+        Rev(needle),
+        Rev(haystack),
+        &mut 0,
+    );
+    found.map(|j| haystack.len() - j - needle.len())
+}
+
+/// The critical factorization of a needle, for the Two-Way algorithm of
+/// Crochemore and Perrin.
+///
+/// The needle is split at `crit_pos` into a left and a right part. Each
+/// window of the haystack is checked by comparing the right part from left
+/// to right, and then the left part from right to left. A mismatch in the
+/// right part shifts the window past the mismatched byte, and a mismatch in
+/// the left part shifts it by `period`. Each byte of the haystack is thus
+/// compared at most twice.
+#[derive(Clone, Copy, Debug)]
+struct TwoWay {
+    crit_pos: usize,
+    period: usize,
+    // Whether `period` is the needle's actual period. When it isn't, it is a
+    // lower bound on the shift, and the prefix of the needle that already
+    // matched can't be remembered across shifts.
+    periodic: bool,
+}
+
+impl TwoWay {
+    fn new<B: Bytes>(needle: B) -> TwoWay {
+        let (pos_lt, period_lt) = maximal_suffix(needle, false);
+        let (pos_gt, period_gt) = maximal_suffix(needle, true);
+        let (crit_pos, period) = if pos_lt > pos_gt {
+            (pos_lt, period_lt)
+        } else {
+            (pos_gt, period_gt)
+        };
+        let periodic = period + crit_pos <= needle.len()
+            && (0..crit_pos).all(|i| needle.at(i) == needle.at(period + i));
+        if periodic {
+            TwoWay { crit_pos, period, periodic: true }
+        } else {
+            let period = cmp::max(crit_pos, needle.len() - crit_pos) + 1;
+            TwoWay { crit_pos, period, periodic: false }
+        }
+    }
+
+    fn find<B: Bytes>(
+        &self,
+        needle: B,
+        haystack: B,
+        comparisons: &mut usize,
+    ) -> Option<usize> {
+        let crit_pos = self.crit_pos;
+        let mut pos = 0;
+        // The length of the needle's prefix that is known to match at `pos`,
+        // which is only ever non-zero for a periodic needle.
+        let mut memory = 0;
+        'search: while haystack.len() - pos >= needle.len() {
+            let start = cmp::max(crit_pos, memory);
+            for i in start..needle.len() {
+                *comparisons += 1;
+                if needle.at(i) != haystack.at(pos + i) {
+                    pos += i - crit_pos + 1;
+                    memory = 0;
+                    continue 'search;
+                }
+            }
+            for i in (memory..crit_pos).rev() {
+                *comparisons += 1;
+                if needle.at(i) != haystack.at(pos + i) {
+                    pos += self.period;
+                    if self.periodic {
+                        memory = needle.len() - self.period;
+                    }
+                    continue 'search;
+                }
+            }
+            return Some(pos);
+        }
+        None
+    }
+}
+
+/// A byte string that Two-Way can search, which is either a slice or a
+/// slice read backward.
+trait Bytes: Copy {
+    fn len(self) -> usize;
+    fn at(self, i: usize) -> u8;
+}
+
+impl Bytes for &[u8] {
+    #[inline(always)]
+    fn len(self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    #[inline(always)]
+    fn at(self, i: usize) -> u8 {
+        self[i]
+    }
+}
+
+/// A slice read from its end to its start, so that searching forward in it
+/// finds the last occurrence of a reversed needle.
+#[derive(Clone, Copy)]
+struct Rev<'a>(&'a [u8]);
+
+impl<'a> Bytes for Rev<'a> {
+    #[inline(always)]
+    fn len(self) -> usize {
+        self.0.len()
+    }
+
+    #[inline(always)]
+    fn at(self, i: usize) -> u8 {
+        self.0[self.0.len() - 1 - i]
+    }
+}
+
+/// Return the start and period of the lexicographically greatest suffix of
+/// `needle`, ordering bytes in reverse when `reversed` is true.
+fn maximal_suffix<B: Bytes>(needle: B, reversed: bool) -> (usize, usize) {
+    let mut left = 0;
+    let mut right = 1;
+    let mut offset = 0;
+    let mut period = 1;
+    while right + offset < needle.len() {
+        let a = needle.at(right + offset);
+        let b = needle.at(left + offset);
+        if (a < b && !reversed) || (a > b && reversed) {
+            // The suffix at `right` is smaller, so the period is everything
+            // from `left` up to the end of the suffix compared so far.
+            right += offset + 1;
+            offset = 0;
+            period = right - left;
+        } else if a == b {
+            if offset + 1 == period {
+                right += offset + 1;
+                offset = 0;
+            } else {
+                offset += 1;
+            }
+        } else {
+            // The suffix at `right` is greater, so start over from it.
+            left = right;
+            right += 1;
+            offset = 0;
+            period = 1;
+        }
+    }
+    (left, period)
+}
+
+impl<'n> fmt::Debug for Finder<'n> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Finder").field("needle", &self.needle).finish()
+    }
+}
+
+/// An iterator over non-overlapping occurrences of a `Finder`'s needle.
+///
+/// This is created by `Finder::find_iter`.
+#[derive(Clone, Debug)]
+pub struct FindIter<'f, 'h> {
+    finder: &'f Finder<'f>,
+    haystack: &'h [u8],
+    // The index at which to start the next search. This is greater than the
+    // length of the haystack once the iterator is exhausted.
+    position: usize,
+}
+
+impl<'f, 'h> Iterator for FindIter<'f, 'h> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let haystack = self.haystack.get(self.position..)?;
+        match self.finder.find(haystack) {
+            None => {
+                self.position = self.haystack.len() + 1;
+                None
+            }
+            Some(i) => {
+                let start = self.position + i;
+                self.position = start + cmp::max(1, self.finder.needle.len());
+                Some(start)
+            }
+        }
+    }
+}
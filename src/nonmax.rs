@@ -0,0 +1,102 @@
+// This module defines a compact representation of match positions, for
+// callers that store a lot of them.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::num::NonZeroUsize;
+
+use memchr;
+
+/// A `usize` that is never equal to `usize::MAX`.
+///
+/// Reserving `usize::MAX` gives the compiler a niche to store `None` in, so
+/// `Option<NonMaxUsize>` is the same size as `usize`, while `Option<usize>`
+/// is twice that. No slice of bytes can have a byte at index `usize::MAX`,
+/// so every match position fits.
+///
+/// # Example
+///
+/// ```
+/// use std::mem::size_of;
+/// use memchr::NonMaxUsize;
+///
+/// assert_eq!(size_of::<Option<NonMaxUsize>>(), size_of::<usize>());
+/// assert_eq!(NonMaxUsize::new(5).map(|n| n.get()), Some(5));
+/// assert!(NonMaxUsize::new(std::usize::MAX).is_none());
+/// ```
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct NonMaxUsize(
+    // The value is stored with all of its bits flipped, which maps
+    // `usize::MAX` to the zero that `NonZeroUsize` forbids.
+    NonZeroUsize,
+);
+
+impl NonMaxUsize {
+    /// Create a new `NonMaxUsize`, or return `None` if `value` is
+    /// `usize::MAX`.
+    #[inline]
+    pub fn new(value: usize) -> Option<NonMaxUsize> {
+        NonZeroUsize::new(!value).map(NonMaxUsize)
+    }
+
+    /// Return the value as a `usize`.
+    #[inline]
+    pub fn get(self) -> usize {
+        !self.0.get()
+    }
+}
+
+impl From<NonMaxUsize> for usize {
+    #[inline]
+    fn from(value: NonMaxUsize) -> usize {
+        value.get()
+    }
+}
+
+impl PartialOrd for NonMaxUsize {
+    #[inline]
+    fn partial_cmp(&self, other: &NonMaxUsize) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NonMaxUsize {
+    #[inline]
+    fn cmp(&self, other: &NonMaxUsize) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+impl fmt::Debug for NonMaxUsize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl fmt::Display for NonMaxUsize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+/// Search for the first occurrence of a byte in a slice, and return its
+/// index as a `NonMaxUsize`.
+///
+/// This is the same as `memchr`, but its result is half the size, which
+/// matters when storing many results, for example, one per line of a large
+/// file.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_nz;
+///
+/// let found = memchr_nz(b'k', b"the quick brown fox");
+/// assert_eq!(found.map(usize::from), Some(8));
+/// ```
+#[inline]
+pub fn memchr_nz(needle: u8, haystack: &[u8]) -> Option<NonMaxUsize> {
+    // An index is always less than the length of the slice, which is at most
+    // `usize::MAX`, so converting it never fails.
+    memchr(needle, haystack).and_then(NonMaxUsize::new)
+}
@@ -0,0 +1,33 @@
+// This module defines conveniences for searching platform specific string
+// types whose underlying representation is a sequence of bytes.
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+
+use memchr;
+
+/// Search for the first occurrence of a byte in an `OsStr`.
+///
+/// This returns the index corresponding to the first occurrence of `needle`
+/// in the raw bytes of `haystack`, or `None` if one is not found.
+///
+/// This is only available on Unix, where an `OsStr` is an arbitrary sequence
+/// of bytes that can be inspected directly. On Windows, an `OsStr` is
+/// internally represented as potentially ill-formed UTF-16 (WTF-8), and its
+/// bytes are not exposed in a way that makes byte offsets meaningful.
+///
+/// # Example
+///
+/// This shows how to find the first path separator in a `Path`.
+///
+/// ```
+/// use std::path::Path;
+/// use memchr::memchr_os;
+///
+/// let path = Path::new("foo/bar/baz");
+/// assert_eq!(memchr_os(b'/', path.as_os_str()), Some(3));
+/// ```
+#[inline]
+pub fn memchr_os(needle: u8, haystack: &OsStr) -> Option<usize> {
+    memchr(needle, haystack.as_bytes())
+}
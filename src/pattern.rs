@@ -0,0 +1,103 @@
+// This module defines a trait for the different kinds of things that can be
+// searched for, so that a single pair of functions can search for any of
+// them.
+
+use {ByteSet, memchr, memchr_byteset, memmem, memrchr};
+
+/// A pattern that can be searched for in a byte slice.
+///
+/// This plays the same role for byte slices that `std::str::pattern::Pattern`
+/// plays for `str::find`: it lets `find` and `rfind` accept a single byte, a
+/// set of bytes or a substring. Each implementation delegates to the
+/// specialized routine for its kind of pattern, i.e., `memchr`,
+/// `memchr_byteset` or `memmem`.
+pub trait BytePattern {
+    /// Return the index of the first match of this pattern in `haystack`.
+    fn find_in(self, haystack: &[u8]) -> Option<usize>;
+
+    /// Return the index of the start of the last match of this pattern in
+    /// `haystack`.
+    fn rfind_in(self, haystack: &[u8]) -> Option<usize>;
+}
+
+/// A single byte, found with `memchr` and `memrchr`.
+impl BytePattern for u8 {
+    #[inline]
+    fn find_in(self, haystack: &[u8]) -> Option<usize> {
+        memchr(self, haystack)
+    }
+
+    #[inline]
+    fn rfind_in(self, haystack: &[u8]) -> Option<usize> {
+        memrchr(self, haystack)
+    }
+}
+
+/// Any byte in a set, found with `memchr_byteset`.
+impl BytePattern for &ByteSet {
+    #[inline]
+    fn find_in(self, haystack: &[u8]) -> Option<usize> {
+        memchr_byteset(self, haystack)
+    }
+
+    #[inline]
+    fn rfind_in(self, haystack: &[u8]) -> Option<usize> {
+        haystack.iter().rposition(|&b| self.contains(b))
+    }
+}
+
+/// A substring, found with `memmem`. Like `str::find`, an empty substring
+/// matches at the start of the haystack, and `rfind` finds it at the end.
+/// Both directions take `O(n + m)` time, whatever the input.
+impl BytePattern for &[u8] {
+    #[inline]
+    fn find_in(self, haystack: &[u8]) -> Option<usize> {
+        memmem(self, haystack)
+    }
+
+    #[inline]
+    fn rfind_in(self, haystack: &[u8]) -> Option<usize> {
+        ::memmem::rfind(self, haystack)
+    }
+}
+
+/// Search for the first match of a pattern in a slice.
+///
+/// The pattern may be a single byte, a `&ByteSet` or a substring as a
+/// `&[u8]`. This returns the index of the start of the first match, or `None`
+/// if there isn't one.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, find};
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(find(b'q', haystack), Some(4));
+/// assert_eq!(find(&ByteSet::from_bytes(b"xyz"), haystack), Some(18));
+/// assert_eq!(find(&b"brown"[..], haystack), Some(10));
+/// ```
+#[inline]
+pub fn find(pattern: impl BytePattern, haystack: &[u8]) -> Option<usize> {
+    pattern.find_in(haystack)
+}
+
+/// Search for the last match of a pattern in a slice.
+///
+/// This is like `find`, but returns the index of the start of the last
+/// match.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, rfind};
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(rfind(b'o', haystack), Some(17));
+/// assert_eq!(rfind(&ByteSet::from_bytes(b"ht"), haystack), Some(1));
+/// assert_eq!(rfind(&b"o"[..], haystack), Some(17));
+/// ```
+#[inline]
+pub fn rfind(pattern: impl BytePattern, haystack: &[u8]) -> Option<usize> {
+    pattern.rfind_in(haystack)
+}
@@ -0,0 +1,89 @@
+// This module defines a variant of memchr that prefetches the haystack ahead
+// of the search, for users who want to tune it to their memory hierarchy.
+
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd))]
+use core::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+use core::cmp;
+
+use memchr;
+
+/// The size, in bytes, of the cache lines that are prefetched.
+const CACHE_LINE_SIZE: usize = 64;
+
+/// The number of bytes searched at a time between rounds of prefetching.
+///
+/// Searching in blocks lets us issue a batch of prefetches and then hand a
+/// reasonably large slice to `memchr`, rather than paying for a call to
+/// `memchr` on every cache line.
+const BLOCK_SIZE: usize = 16 * CACHE_LINE_SIZE;
+
+/// Search for the first occurrence of a byte in a slice, while prefetching
+/// the haystack `prefetch_lines` cache lines ahead of the search.
+///
+/// This always returns the same result as `memchr`. The only difference is
+/// that, before searching each block of the haystack, this asks the CPU to
+/// start loading the memory that is `prefetch_lines` cache lines (of 64
+/// bytes each) further along. When `prefetch_lines` is `0`, no prefetching
+/// is done. Prefetching is only implemented on `x86_64`, and only when
+/// compiled with Rust 1.27 or newer; elsewhere, this is equivalent to
+/// `memchr`.
+///
+/// Hardware prefetchers already do a good job on linear scans, so `memchr`
+/// itself doesn't prefetch. This is an escape hatch for scanning large
+/// haystacks on systems where explicit prefetching has been measured to pay
+/// off, e.g., with large pages or on NUMA systems. Prefer `memchr` unless
+/// benchmarks say otherwise.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{memchr, memchr_tuned};
+///
+/// let haystack = vec![b'z'; 1 << 20];
+/// assert_eq!(memchr_tuned(b'a', &haystack, 8), None);
+/// assert_eq!(memchr_tuned(b'z', &haystack, 8), memchr(b'z', &haystack));
+/// ```
+pub fn memchr_tuned(
+    needle: u8,
+    haystack: &[u8],
+    prefetch_lines: usize,
+) -> Option<usize> {
+    if prefetch_lines == 0 {
+        return memchr(needle, haystack);
+    }
+    let distance = prefetch_lines.saturating_mul(CACHE_LINE_SIZE);
+    let mut at = 0;
+    while at < haystack.len() {
+        let end = cmp::min(haystack.len(), at + BLOCK_SIZE);
+        prefetch(haystack, at.saturating_add(distance), BLOCK_SIZE);
+        if let Some(i) = memchr(needle, &haystack[at..end]) {
+            return Some(at + i);
+        }
+        at = end;
+    }
+    None
+}
+
+/// Prefetch the cache lines covering `len` bytes of `haystack`, starting at
+/// `start`. Anything beyond the end of `haystack` is ignored.
+#[inline(always)]
+fn prefetch(haystack: &[u8], start: usize, len: usize) {
+    let end = cmp::min(haystack.len(), start.saturating_add(len));
+    let mut at = start;
+    while at < end {
+        prefetch_line(&haystack[at]);
+        at += CACHE_LINE_SIZE;
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd))]
+#[inline(always)]
+fn prefetch_line(byte: &u8) {
+    // SSE is always available on x86_64, and prefetching has no effect on
+    // the behavior of the program, only on its performance.
+    unsafe { _mm_prefetch(byte as *const u8 as *const i8, _MM_HINT_T0) }
+}
+
+#[cfg(not(all(target_arch = "x86_64", memchr_runtime_simd)))]
+#[inline(always)]
+fn prefetch_line(_: &u8) {}
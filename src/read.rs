@@ -0,0 +1,114 @@
+// This module defines a minimal byte source abstraction, so that streams of
+// bytes can be searched without depending on `std::io`.
+
+use core::fmt;
+#[cfg(feature = "use_std")]
+use std::error;
+#[cfg(feature = "use_std")]
+use std::io;
+
+use memchr;
+
+/// A source of bytes that can be read incrementally.
+///
+/// This is a stripped down version of `std::io::Read` that is available
+/// without the standard library. When the `use_std` feature is enabled, every
+/// `std::io::Read` implementation is also a `ReadLike`.
+pub trait ReadLike {
+    /// The type of error returned when reading fails.
+    type Error;
+
+    /// Read bytes into `buf`, returning how many bytes were read.
+    ///
+    /// Returning `0` indicates that the source is exhausted. Implementations
+    /// should retry transient failures themselves rather than reporting
+    /// them as errors.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+#[cfg(feature = "use_std")]
+impl<R: io::Read> ReadLike for R {
+    type Error = io::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match io::Read::read(self, buf) {
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                    continue;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// An error that occurs while searching a `ReadLike` byte source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScanError<E> {
+    /// The buffer given to the search was empty, so no bytes could be read.
+    EmptyBuffer,
+    /// The byte source returned an error.
+    Read(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ScanError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScanError::EmptyBuffer => {
+                write!(f, "buffer must have a length greater than zero")
+            }
+            ScanError::Read(ref err) => write!(f, "read error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<E: error::Error> error::Error for ScanError<E> {}
+
+/// Search for the first occurrence of a byte in a `ReadLike` byte source,
+/// using `buf` to hold each chunk that is read.
+///
+/// This returns the offset, relative to the current position of `reader`,
+/// of the first occurrence of `needle`, or `None` if the source is exhausted
+/// without finding one. At most `buf.len()` bytes are read at a time, and
+/// nothing is allocated, which makes this usable without the standard
+/// library.
+///
+/// Note that it is unspecified how many bytes are consumed from `reader`
+/// beyond the position of the match.
+///
+/// # Errors
+///
+/// This returns `ScanError::EmptyBuffer` if `buf` is empty, and
+/// `ScanError::Read` if `reader` fails.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_chunked;
+///
+/// let mut rdr = &b"the quick brown fox"[..];
+/// let mut buf = [0; 4];
+/// assert_eq!(memchr_chunked(b'k', &mut rdr, &mut buf).unwrap(), Some(8));
+/// ```
+pub fn memchr_chunked<R: ReadLike>(
+    needle: u8,
+    reader: &mut R,
+    buf: &mut [u8],
+) -> Result<Option<u64>, ScanError<R::Error>> {
+    if buf.is_empty() {
+        return Err(ScanError::EmptyBuffer);
+    }
+    let mut offset: u64 = 0;
+    loop {
+        let n = match reader.read(buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => n,
+            Err(err) => return Err(ScanError::Read(err)),
+        };
+        if let Some(i) = memchr(needle, &buf[..n]) {
+            return Ok(Some(offset + i as u64));
+        }
+        offset += n as u64;
+    }
+}
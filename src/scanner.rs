@@ -0,0 +1,71 @@
+use core::fmt;
+
+use fallback::Split;
+
+/// A haystack that is searched repeatedly for different bytes.
+///
+/// This groups many searches over the same haystack, for example, when a
+/// tokenizer looks for several different delimiters in sequence. Every search
+/// returns the same result as calling `memchr` directly.
+///
+/// A `Scanner` splits its haystack into an unaligned head, a run of aligned
+/// words and an unaligned tail once, when it is constructed. Each search
+/// then reuses that split and reads the words directly, one at a time,
+/// instead of working out the alignment of the haystack again. The words
+/// are read without SIMD, though, so to search a long haystack just once,
+/// calling `memchr` directly is usually faster.
+///
+/// # Example
+///
+/// ```
+/// use memchr::Scanner;
+///
+/// let scanner = Scanner::new(b"key=value;other=thing");
+/// assert_eq!(scanner.find(b'='), Some(3));
+/// assert_eq!(scanner.find(b';'), Some(9));
+/// assert_eq!(scanner.find_from(b'=', 10), Some(15));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Scanner<'a> {
+    split: Split<'a>,
+}
+
+impl<'a> Scanner<'a> {
+    /// Create a new scanner for the given haystack.
+    #[inline]
+    pub fn new(haystack: &'a [u8]) -> Scanner<'a> {
+        Scanner { split: Split::new(haystack) }
+    }
+
+    /// Return the haystack searched by this scanner.
+    #[inline]
+    pub fn haystack(&self) -> &'a [u8] {
+        self.split.haystack()
+    }
+
+    /// Search for the first occurrence of `needle` in the haystack.
+    #[inline]
+    pub fn find(&self, needle: u8) -> Option<usize> {
+        self.split.memchr_from(needle, 0)
+    }
+
+    /// Search for the first occurrence of `needle` at or after `start`.
+    ///
+    /// The index returned is relative to the start of the entire haystack.
+    /// If `start` is greater than or equal to the length of the haystack,
+    /// then this always returns `None`, following the crate's rules for
+    /// [out of range indices](index.html#out-of-range-indices).
+    #[inline]
+    pub fn find_from(&self, needle: u8, start: usize) -> Option<usize> {
+        if start >= self.haystack().len() {
+            return None;
+        }
+        self.split.memchr_from(needle, start)
+    }
+}
+
+impl<'a> fmt::Debug for Scanner<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scanner").field("haystack", &self.haystack()).finish()
+    }
+}
@@ -0,0 +1,29 @@
+// This module defines a memchr kernel written against the portable SIMD API
+// in `core::simd`. Unlike the kernels in the `x86` module, it isn't tied to a
+// particular architecture, so it can be used to get vectorized searching on
+// targets (like ARM or WASM) for which we don't have hand written intrinsics.
+//
+// `core::simd` is not yet stable, so this kernel is only available on nightly
+// with the `portable_simd` feature enabled.
+
+use core::simd::cmp::SimdPartialEq;
+use core::simd::Simd;
+
+// The number of bytes compared in a single vector. 32 is wide enough to
+// saturate 256-bit registers, and is legalized into two 128-bit operations
+// on targets that don't have them.
+const LANES: usize = 32;
+
+pub fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = Simd::<u8, LANES>::splat(n1);
+    let mut chunks = haystack.chunks_exact(LANES);
+    let mut at = 0;
+    for chunk in &mut chunks {
+        let mask = Simd::from_slice(chunk).simd_eq(vn1).to_bitmask();
+        if mask != 0 {
+            return Some(at + mask.trailing_zeros() as usize);
+        }
+        at += LANES;
+    }
+    chunks.remainder().iter().position(|&b| b == n1).map(|i| at + i)
+}
@@ -0,0 +1,472 @@
+// This module defines routines for breaking a haystack into the segments
+// between occurrences of a delimiter byte.
+
+#[cfg(feature = "use_std")]
+use core::ops::Range;
+
+#[cfg(feature = "use_std")]
+use count;
+use {Memchr, memchr, memrchr};
+
+/// Split `haystack` at the first occurrence of `delim`.
+///
+/// This returns `(token, rest)`, where `token` is everything before the
+/// first `delim` and `rest` is everything after it. The delimiter itself is
+/// in neither. When `delim` doesn't occur, `token` is all of `haystack` and
+/// `rest` is empty, which treats the end of the input as a final delimiter.
+/// Unlike a `split_once`, this always succeeds, so a tokenizer can call it
+/// repeatedly until `rest` is empty.
+///
+/// # Example
+///
+/// ```
+/// use memchr::next_token;
+///
+/// assert_eq!(next_token(b'\n', b"foo\nbar"), (&b"foo"[..], &b"bar"[..]));
+/// assert_eq!(next_token(b'\n', b"bar"), (&b"bar"[..], &b""[..]));
+/// ```
+#[inline]
+pub fn next_token(delim: u8, haystack: &[u8]) -> (&[u8], &[u8]) {
+    match memchr(delim, haystack) {
+        None => (haystack, &[]),
+        Some(i) => (&haystack[..i], &haystack[i + 1..]),
+    }
+}
+
+/// Split `haystack` in two at the first occurrence of `needle`.
+///
+/// This returns `Some((before, from))`, where `before` is everything before
+/// the first `needle` and `from` starts with `needle` itself, or `None` when
+/// `needle` doesn't occur. It is the same as finding the match with `memchr`
+/// and passing its index to `slice::split_at`, so `before.len()` is always
+/// the index of the match.
+///
+/// This differs from `next_token` (and from `split_once` in the standard
+/// library) in that the needle is kept: it is the first byte of the second
+/// slice rather than being dropped from both.
+///
+/// # Example
+///
+/// ```
+/// use memchr::split_at_byte;
+///
+/// let (name, ext) = split_at_byte(b'.', b"main.rs").unwrap();
+/// assert_eq!((name, ext), (&b"main"[..], &b".rs"[..]));
+/// assert_eq!(split_at_byte(b'.', b"Makefile"), None);
+/// ```
+#[inline]
+pub fn split_at_byte(needle: u8, haystack: &[u8]) -> Option<(&[u8], &[u8])> {
+    memchr(needle, haystack).map(|i| haystack.split_at(i))
+}
+
+/// Split `haystack` on every occurrence of `delim` and collect the pieces.
+///
+/// This returns the same pieces as `haystack.split(|&b| b == delim)`, so
+/// when `delim` occurs `n` times, the result has exactly `n + 1` pieces and a
+/// trailing delimiter produces a final empty piece. The vector is allocated
+/// once, with its capacity computed by `count`, which costs an extra pass
+/// over the haystack but never reallocates.
+///
+/// # Example
+///
+/// ```
+/// use memchr::split_collect;
+///
+/// let pieces = split_collect(b',', b"a,,b,");
+/// assert_eq!(pieces, vec![&b"a"[..], b"", b"b", b""]);
+/// ```
+#[cfg(feature = "use_std")]
+pub fn split_collect(delim: u8, haystack: &[u8]) -> Vec<&[u8]> {
+    let mut pieces = Vec::with_capacity(count(delim, haystack) + 1);
+    let mut rest = haystack;
+    while let Some(i) = memchr(delim, rest) {
+        pieces.push(&rest[..i]);
+        rest = &rest[i + 1..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+/// Split `haystack` on every occurrence of `delim` and collect the range of
+/// each piece.
+///
+/// This returns one range for each piece returned by `split_collect`, in the
+/// same order, such that `&haystack[range]` is that piece. So adjacent
+/// delimiters produce empty ranges, and a trailing delimiter produces a
+/// final empty range that starts and ends at `haystack.len()`. Unlike the
+/// pieces, the ranges don't borrow `haystack`, so they can be stored apart
+/// from it. The vector is allocated once, with its capacity computed by
+/// `count`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::field_ranges;
+///
+/// let ranges = field_ranges(b',', b"a,,bc,");
+/// assert_eq!(ranges, vec![0..1, 2..2, 3..5, 6..6]);
+/// ```
+#[cfg(feature = "use_std")]
+pub fn field_ranges(delim: u8, haystack: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::with_capacity(count(delim, haystack) + 1);
+    let mut start = 0;
+    for i in Memchr::new(delim, haystack) {
+        ranges.push(start..i);
+        start = i + 1;
+    }
+    ranges.push(start..haystack.len());
+    ranges
+}
+
+/// Return an iterator over the lengths of the segments of `haystack` that
+/// are separated by `delim`.
+///
+/// This yields the same lengths as
+/// `ByteStr(haystack).split(delim).map(|s| s.len())`, without producing the
+/// segments themselves. In particular, when `delim` occurs `n` times, this
+/// yields `n + 1` lengths, so a trailing delimiter produces a final length
+/// of `0` and an empty haystack produces a single length of `0`. The sum of
+/// the lengths plus the number of delimiters is always `haystack.len()`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::segment_lengths;
+///
+/// let lengths: Vec<usize> = segment_lengths(b',', b"ab,,cde,").collect();
+/// assert_eq!(lengths, vec![2, 0, 3, 0]);
+/// ```
+#[inline]
+pub fn segment_lengths<'a>(
+    delim: u8,
+    haystack: &'a [u8],
+) -> SegmentLengths<'a> {
+    SegmentLengths { delim, haystack, finished: false }
+}
+
+/// An iterator over the lengths of the segments between a delimiter.
+///
+/// This is created by `segment_lengths`.
+#[derive(Clone, Debug)]
+pub struct SegmentLengths<'a> {
+    delim: u8,
+    haystack: &'a [u8],
+    finished: bool,
+}
+
+impl<'a> Iterator for SegmentLengths<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        if self.finished {
+            return None;
+        }
+        match memchr(self.delim, self.haystack) {
+            None => {
+                self.finished = true;
+                Some(self.haystack.len())
+            }
+            Some(i) => {
+                self.haystack = &self.haystack[i + 1..];
+                Some(i)
+            }
+        }
+    }
+}
+
+/// Return an iterator over the delimiters in `haystack`, each paired with
+/// the segment that it terminates.
+///
+/// This yields `(i, segment)` for every occurrence of `delim`, where `i` is
+/// the index of that occurrence in `haystack` and `segment` is everything
+/// between it and the previous occurrence (or the start of `haystack`). Like
+/// `str::split_terminator`, every segment must be terminated by `delim`: a
+/// trailing delimiter yields its (possibly empty) segment as usual, while
+/// any bytes after the last delimiter are not yielded at all.
+///
+/// # Example
+///
+/// ```
+/// use memchr::split_terminator_with_pos;
+///
+/// let fields: Vec<(usize, &[u8])> =
+///     split_terminator_with_pos(b';', b"ab;;c;d").collect();
+/// assert_eq!(fields, vec![(2, &b"ab"[..]), (3, b""), (5, b"c")]);
+/// ```
+#[inline]
+pub fn split_terminator_with_pos<'a>(
+    delim: u8,
+    haystack: &'a [u8],
+) -> SplitTerminatorWithPos<'a> {
+    SplitTerminatorWithPos {
+        matches: Memchr::new(delim, haystack),
+        haystack,
+        start: 0,
+    }
+}
+
+/// An iterator over the delimiters in a haystack and the segments that they
+/// terminate.
+///
+/// This is created by `split_terminator_with_pos`.
+pub struct SplitTerminatorWithPos<'a> {
+    matches: Memchr<'a>,
+    haystack: &'a [u8],
+    // The index at which the segment terminated by the next match starts.
+    start: usize,
+}
+
+impl<'a> Iterator for SplitTerminatorWithPos<'a> {
+    type Item = (usize, &'a [u8]);
+
+    #[inline]
+    fn next(&mut self) -> Option<(usize, &'a [u8])> {
+        let i = self.matches.next()?;
+        let segment = &self.haystack[self.start..i];
+        self.start = i + 1;
+        Some((i, segment))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.matches.size_hint()
+    }
+}
+
+/// Return an iterator over the segments of `haystack` that are terminated
+/// by `delim`, with each segment including its delimiter.
+///
+/// This yields the same segments as
+/// `haystack.split_inclusive(|&b| b == delim)`. Every segment ends with
+/// `delim` except possibly the last, which holds any bytes after the last
+/// delimiter. A trailing delimiter ends the last segment, so it doesn't
+/// produce an extra empty segment, and an empty haystack yields nothing.
+/// Since no bytes are dropped, concatenating the segments reproduces
+/// `haystack` exactly.
+///
+/// # Example
+///
+/// ```
+/// use memchr::split_inclusive;
+///
+/// let lines: Vec<&[u8]> = split_inclusive(b'\n', b"foo\nbar\n").collect();
+/// assert_eq!(lines, vec![&b"foo\n"[..], b"bar\n"]);
+///
+/// let lines: Vec<&[u8]> = split_inclusive(b'\n', b"foo\nbar").collect();
+/// assert_eq!(lines, vec![&b"foo\n"[..], b"bar"]);
+/// ```
+#[inline]
+pub fn split_inclusive<'a>(
+    delim: u8,
+    haystack: &'a [u8],
+) -> SplitInclusive<'a> {
+    SplitInclusive { delim, haystack }
+}
+
+/// An iterator over the segments terminated by a delimiter, including the
+/// delimiter.
+///
+/// This is created by `split_inclusive`.
+#[derive(Clone, Debug)]
+pub struct SplitInclusive<'a> {
+    delim: u8,
+    haystack: &'a [u8],
+}
+
+impl<'a> Iterator for SplitInclusive<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.haystack.is_empty() {
+            return None;
+        }
+        let end = match memchr(self.delim, self.haystack) {
+            None => self.haystack.len(),
+            Some(i) => i + 1,
+        };
+        let (segment, rest) = self.haystack.split_at(end);
+        self.haystack = rest;
+        Some(segment)
+    }
+}
+
+/// Return an iterator over the key/value pairs in a `key=value;key=value`
+/// style haystack.
+///
+/// `haystack` is split into records on every occurrence of `rec_delim`, and
+/// each record is split into a key and a value on its first occurrence of
+/// `kv_delim`, neither of which includes the delimiter. Later occurrences
+/// of `kv_delim` are part of the value. A record without `kv_delim` is
+/// yielded as a key with an empty value, as in a bare flag. Empty records,
+/// like the one produced by a trailing `rec_delim`, are skipped.
+///
+/// # Example
+///
+/// ```
+/// use memchr::key_value_pairs;
+///
+/// let pairs: Vec<(&[u8], &[u8])> =
+///     key_value_pairs(b';', b'=', b"a=1;b=x=y;flag;").collect();
+/// assert_eq!(pairs, vec![
+///     (&b"a"[..], &b"1"[..]),
+///     (&b"b"[..], &b"x=y"[..]),
+///     (&b"flag"[..], &b""[..]),
+/// ]);
+/// ```
+#[inline]
+pub fn key_value_pairs<'a>(
+    rec_delim: u8,
+    kv_delim: u8,
+    haystack: &'a [u8],
+) -> KeyValuePairs<'a> {
+    KeyValuePairs { rec_delim, kv_delim, haystack }
+}
+
+/// An iterator over the key/value pairs in a haystack.
+///
+/// This is created by `key_value_pairs`.
+#[derive(Clone, Debug)]
+pub struct KeyValuePairs<'a> {
+    rec_delim: u8,
+    kv_delim: u8,
+    haystack: &'a [u8],
+}
+
+impl<'a> Iterator for KeyValuePairs<'a> {
+    type Item = (&'a [u8], &'a [u8]);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a [u8], &'a [u8])> {
+        while !self.haystack.is_empty() {
+            let (record, rest) = next_token(self.rec_delim, self.haystack);
+            self.haystack = rest;
+            if !record.is_empty() {
+                return Some(next_token(self.kv_delim, record));
+            }
+        }
+        None
+    }
+}
+
+/// Return an iterator over at most `n` segments of `haystack` separated by
+/// `delim`.
+///
+/// This yields the same segments as `haystack.splitn(n, |&b| b == delim)`:
+/// the first `n - 1` segments are found by splitting on `delim` with
+/// `memchr`, and the last segment is the rest of `haystack`, which may
+/// contain more occurrences of `delim`. When `delim` occurs fewer than
+/// `n - 1` times, every segment is yielded, just like a plain split. When
+/// `n` is `0`, nothing is yielded.
+///
+/// # Example
+///
+/// ```
+/// use memchr::splitn;
+///
+/// let parts: Vec<&[u8]> = splitn(2, b':', b"key:value:more").collect();
+/// assert_eq!(parts, vec![&b"key"[..], b"value:more"]);
+/// ```
+#[inline]
+pub fn splitn<'a>(n: usize, delim: u8, haystack: &'a [u8]) -> SplitN<'a> {
+    SplitN { n, delim, haystack }
+}
+
+/// An iterator over a limited number of segments separated by a delimiter.
+///
+/// This is created by `splitn`.
+#[derive(Clone, Debug)]
+pub struct SplitN<'a> {
+    // The number of segments that haven't been yielded yet.
+    n: usize,
+    delim: u8,
+    haystack: &'a [u8],
+}
+
+impl<'a> Iterator for SplitN<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        match self.n {
+            0 => None,
+            1 => {
+                self.n = 0;
+                Some(self.haystack)
+            }
+            _ => match memchr(self.delim, self.haystack) {
+                None => {
+                    self.n = 0;
+                    Some(self.haystack)
+                }
+                Some(i) => {
+                    self.n -= 1;
+                    let piece = &self.haystack[..i];
+                    self.haystack = &self.haystack[i + 1..];
+                    Some(piece)
+                }
+            },
+        }
+    }
+}
+
+/// Return an iterator over at most `n` segments of `haystack` separated by
+/// `delim`, starting from the end.
+///
+/// This is the reverse of `splitn`, and yields the same segments as
+/// `haystack.rsplitn(n, |&b| b == delim)`. Delimiters are found with
+/// `memrchr`, the segments are yielded from last to first, and the last
+/// segment yielded is the rest of the start of `haystack`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::rsplitn;
+///
+/// let parts: Vec<&[u8]> = rsplitn(2, b'.', b"archive.tar.gz").collect();
+/// assert_eq!(parts, vec![&b"gz"[..], b"archive.tar"]);
+/// ```
+#[inline]
+pub fn rsplitn<'a>(n: usize, delim: u8, haystack: &'a [u8]) -> RSplitN<'a> {
+    RSplitN { n, delim, haystack }
+}
+
+/// An iterator over a limited number of segments separated by a delimiter,
+/// starting from the end.
+///
+/// This is created by `rsplitn`.
+#[derive(Clone, Debug)]
+pub struct RSplitN<'a> {
+    // The number of segments that haven't been yielded yet.
+    n: usize,
+    delim: u8,
+    haystack: &'a [u8],
+}
+
+impl<'a> Iterator for RSplitN<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        match self.n {
+            0 => None,
+            1 => {
+                self.n = 0;
+                Some(self.haystack)
+            }
+            _ => match memrchr(self.delim, self.haystack) {
+                None => {
+                    self.n = 0;
+                    Some(self.haystack)
+                }
+                Some(i) => {
+                    self.n -= 1;
+                    let piece = &self.haystack[i + 1..];
+                    self.haystack = &self.haystack[..i];
+                    Some(piece)
+                }
+            },
+        }
+    }
+}
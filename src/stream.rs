@@ -0,0 +1,112 @@
+// This module defines routines for searching a stream of bytes, where the
+// haystack is not available all at once as a single slice.
+
+use std::io::{self, BufRead, Read};
+
+use memchr;
+use read::{ScanError, memchr_chunked};
+
+/// The default number of bytes read from a stream in each call to `read`.
+const DEFAULT_CHUNK_SIZE: usize = 64 * (1 << 10);
+
+/// Search for the first occurrence of a byte in a stream.
+///
+/// This returns the offset, relative to the current position of `reader`,
+/// of the first occurrence of `needle`, or `None` if the stream is exhausted
+/// without finding one.
+///
+/// This reads `reader` in fixed size chunks. To control the size of each
+/// read, use `memchr_stream_with`.
+///
+/// Note that it is unspecified how many bytes are consumed from `reader`
+/// beyond the position of the match.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use memchr::memchr_stream;
+///
+/// let mut rdr = Cursor::new(&b"the quick brown fox"[..]);
+/// assert_eq!(memchr_stream(b'k', &mut rdr).unwrap(), Some(8));
+/// ```
+#[inline]
+pub fn memchr_stream<R: Read>(
+    needle: u8,
+    reader: &mut R,
+) -> io::Result<Option<u64>> {
+    memchr_stream_with(needle, reader, DEFAULT_CHUNK_SIZE)
+}
+
+/// Like `memchr_stream`, but reads at most `chunk_size` bytes at a time.
+///
+/// A single buffer of `chunk_size` bytes is allocated up front and reused
+/// for every read. A chunk size that matches the read size preferred by the
+/// underlying I/O layer tends to give the best performance.
+///
+/// # Errors
+///
+/// This returns an error of kind `InvalidInput` if `chunk_size` is `0`. Any
+/// error returned by `reader` (other than `Interrupted`, which is retried) is
+/// returned as is.
+pub fn memchr_stream_with<R: Read>(
+    needle: u8,
+    reader: &mut R,
+    chunk_size: usize,
+) -> io::Result<Option<u64>> {
+    let mut buf = vec![0; chunk_size];
+    match memchr_chunked(needle, reader, &mut buf) {
+        Ok(found) => Ok(found),
+        Err(ScanError::Read(err)) => Err(err),
+        Err(ScanError::EmptyBuffer) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "chunk size must be greater than zero",
+        )),
+    }
+}
+
+/// Search for the first occurrence of a byte in the buffered data of a
+/// `BufRead`.
+///
+/// This fills `reader`'s buffer if it is empty and returns the index of the
+/// first occurrence of `needle` within it, or `None` if the buffered data
+/// does not contain `needle`. Nothing is consumed, so on a match, the
+/// matching bytes can be borrowed with another call to `fill_buf` (which
+/// returns the same data without reading) before being consumed.
+///
+/// Only the data currently buffered is searched. When this returns `None`,
+/// a match may still exist further along in the stream: the caller must
+/// `consume` the buffered data and call this again to search the next
+/// buffer. `Ok(None)` with an empty buffer means the stream is exhausted.
+///
+/// # Errors
+///
+/// Any error returned by `fill_buf` (other than `Interrupted`, which is
+/// retried) is returned as is.
+///
+/// # Example
+///
+/// ```
+/// use std::io::{BufRead, BufReader};
+/// use memchr::find_in_bufread;
+///
+/// let mut rdr = BufReader::new(&b"key=value"[..]);
+/// let i = find_in_bufread(b'=', &mut rdr).unwrap().unwrap();
+/// assert_eq!(&rdr.fill_buf().unwrap()[..i], b"key");
+/// rdr.consume(i + 1);
+/// assert_eq!(rdr.fill_buf().unwrap(), b"value");
+/// ```
+pub fn find_in_bufread<R: BufRead>(
+    needle: u8,
+    reader: &mut R,
+) -> io::Result<Option<usize>> {
+    loop {
+        match reader.fill_buf() {
+            Ok(buf) => return Ok(memchr(needle, buf)),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
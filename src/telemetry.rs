@@ -0,0 +1,320 @@
+// This module reports which kernel serves a call to memchr, which helps
+// diagnose performance problems without having to guess from CPU flags.
+// It is only compiled with the `telemetry` feature, so it costs nothing
+// otherwise. The `bench-internals` feature additionally permits running a
+// specific kernel, which the benchmarks use to measure each of them, and the
+// `profiling` feature counts the work done by the word at a time search.
+
+#[cfg(all(
+    feature = "bench-internals",
+    feature = "libc",
+    not(target_arch = "wasm32"),
+    not(target_env = "sgx"),
+))]
+use c;
+#[cfg(any(feature = "bench-internals", feature = "profiling"))]
+use fallback;
+use memchr;
+#[cfg(all(feature = "bench-internals", feature = "portable_simd"))]
+use simd;
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+use x86;
+
+/// A kernel that `memchr` may use to serve a search.
+///
+/// This is returned by `memchr_traced`. New variants may be added as new
+/// kernels are added.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KernelId {
+    /// The AVX-512 kernel, used for large haystacks on CPUs that support
+    /// AVX-512BW.
+    Avx512,
+    /// The AVX2 kernel.
+    Avx2,
+    /// The SSE2 kernel.
+    Sse2,
+    /// The kernel written with `core::simd`.
+    PortableSimd,
+    /// The platform's libc `memchr`.
+    Libc,
+    /// The word at a time (SWAR) fallback, reading four words per iteration.
+    SwarUnroll4,
+    /// The word at a time (SWAR) fallback.
+    Swar,
+}
+
+impl KernelId {
+    /// Every kernel, whether or not it is available.
+    ///
+    /// Use `kernel_available` to find out which of these `memchr_kernel`
+    /// can run. This is only available with the `bench-internals` feature.
+    #[cfg(feature = "bench-internals")]
+    pub const ALL: &'static [KernelId] = &[
+        KernelId::Avx512,
+        KernelId::Avx2,
+        KernelId::Sse2,
+        KernelId::PortableSimd,
+        KernelId::Libc,
+        KernelId::SwarUnroll4,
+        KernelId::Swar,
+    ];
+}
+
+/// Search for the first occurrence of a byte, and report which kernel was
+/// used to do it.
+///
+/// The index returned is always the same as `memchr(needle, haystack)`. The
+/// kernel is the one that `memchr` selects for `haystack` on the current
+/// CPU, before any special casing inside of the kernel. For example, the
+/// AVX2 kernel hands haystacks shorter than a single vector off to the SSE2
+/// kernel, but this still reports `KernelId::Avx2`. Similarly, an empty
+/// haystack is answered without running any kernel at all, but this still
+/// reports the kernel that would have been used.
+///
+/// This is only available with the `telemetry` feature.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_traced;
+///
+/// let (found, kernel) = memchr_traced(b'k', b"the quick brown fox");
+/// assert_eq!(found, Some(8));
+/// println!("served by {:?}", kernel);
+/// ```
+#[inline]
+pub fn memchr_traced(
+    needle: u8,
+    haystack: &[u8],
+) -> (Option<usize>, KernelId) {
+    (memchr(needle, haystack), kernel(haystack))
+}
+
+/// The work done by the word at a time (SWAR) search, as reported by
+/// `memchr_profiled`.
+///
+/// The search looks at the bytes before the first `usize` aligned word one
+/// at a time (the head), then at two aligned words per iteration, and then
+/// at the remaining bytes one at a time (the tail). When the main loop spots
+/// a match, the tail starts at the pair of words that contains it, so those
+/// bytes are counted twice. Without a match, every byte is examined exactly
+/// once, and `head_bytes + words * size_of::<usize>() + tail_bytes` is the
+/// length of the haystack.
+///
+/// This is only available with the `profiling` feature.
+#[cfg(feature = "profiling")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScanStats {
+    /// The number of aligned `usize` words examined by the main loop.
+    pub words: usize,
+    /// The number of bytes examined one at a time before the main loop.
+    pub head_bytes: usize,
+    /// The number of bytes examined one at a time after the main loop.
+    pub tail_bytes: usize,
+    /// Whether `memchr` itself would have served this search with one of
+    /// this crate's SIMD kernels rather than with the SWAR search.
+    pub simd: bool,
+}
+
+/// Search for the first occurrence of a byte with the word at a time (SWAR)
+/// search, and report the work it did.
+///
+/// The index returned is always the same as `memchr(needle, haystack)`. The
+/// counts are collected by the SWAR search as it runs, so this is slower
+/// than `memchr`, and on CPUs where `memchr` uses a SIMD kernel, they
+/// describe the SWAR search rather than the kernel `memchr` would use. They
+/// are useful for tuning buffer sizes and alignment: a search over small or
+/// poorly aligned buffers spends a larger share of its bytes in the head and
+/// tail, which are examined one byte at a time.
+///
+/// This is only available with the `profiling` feature.
+///
+/// # Example
+///
+/// ```
+/// use std::mem::size_of;
+///
+/// use memchr::memchr_profiled;
+///
+/// let haystack = [b'a'; 100];
+/// let (found, stats) = memchr_profiled(b'z', &haystack);
+/// assert_eq!(found, None);
+/// let word = size_of::<usize>();
+/// assert_eq!(stats.head_bytes + stats.words * word + stats.tail_bytes, 100);
+/// ```
+#[cfg(feature = "profiling")]
+pub fn memchr_profiled(
+    needle: u8,
+    haystack: &[u8],
+) -> (Option<usize>, ScanStats) {
+    let (found, counts) = fallback::memchr_counted(needle, haystack);
+    let stats = ScanStats {
+        words: counts.words,
+        head_bytes: counts.head_bytes,
+        tail_bytes: counts.tail_bytes,
+        simd: is_simd(kernel(haystack)),
+    };
+    (found, stats)
+}
+
+/// Returns true if and only if `kernel` is one of this crate's SIMD kernels.
+#[cfg(feature = "profiling")]
+fn is_simd(kernel: KernelId) -> bool {
+    match kernel {
+        KernelId::Avx512
+        | KernelId::Avx2
+        | KernelId::Sse2
+        | KernelId::PortableSimd => true,
+        KernelId::Libc | KernelId::SwarUnroll4 | KernelId::Swar => false,
+    }
+}
+
+/// Search for the first occurrence of a byte with a specific kernel.
+///
+/// This runs `kernel` even when `memchr` would choose a different one,
+/// which is useful for comparing the throughput of each kernel on the same
+/// machine. The index returned is always the same as
+/// `memchr(needle, haystack)`.
+///
+/// This is only available with the `bench-internals` feature, and isn't
+/// meant for use outside of benchmarks.
+///
+/// # Panics
+///
+/// This panics if `kernel` wasn't compiled into this build (e.g.,
+/// `KernelId::PortableSimd` without the `portable_simd` feature) or if the
+/// current CPU doesn't support it (e.g., `KernelId::Avx2` on a CPU without
+/// AVX2). `KernelId::Swar` and `KernelId::SwarUnroll4` are always
+/// available.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{KernelId, memchr_kernel};
+///
+/// let haystack = b"the quick brown fox";
+/// assert_eq!(memchr_kernel(KernelId::Swar, b'k', haystack), Some(8));
+/// ```
+#[cfg(feature = "bench-internals")]
+pub fn memchr_kernel(
+    kernel: KernelId,
+    needle: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    assert!(
+        kernel_available(kernel),
+        "the {:?} memchr kernel is not available in this build or on this CPU",
+        kernel,
+    );
+    match kernel {
+        #[cfg(all(
+            target_arch = "x86_64",
+            memchr_runtime_simd,
+            feature = "use_std",
+        ))]
+        KernelId::Avx512 | KernelId::Avx2 | KernelId::Sse2 => {
+            x86::memchr_with_kernel(kernel, needle, haystack)
+        }
+        #[cfg(feature = "portable_simd")]
+        KernelId::PortableSimd => simd::memchr(needle, haystack),
+        #[cfg(all(
+            feature = "libc",
+            not(target_arch = "wasm32"),
+            not(target_env = "sgx"),
+        ))]
+        KernelId::Libc => c::memchr(needle, haystack),
+        KernelId::SwarUnroll4 => fallback::memchr_unroll4(needle, haystack),
+        KernelId::Swar => fallback::memchr(needle, haystack),
+        #[allow(unreachable_patterns)]
+        _ => unreachable!(),
+    }
+}
+
+/// Returns true if and only if `memchr_kernel` can run `kernel`.
+///
+/// A kernel is available when it was compiled into this build and the
+/// current CPU supports it. Together with `KernelId::ALL`, this permits
+/// running every available kernel on the same input, which is what the
+/// differential fuzz target does.
+///
+/// This is only available with the `bench-internals` feature.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{KernelId, kernel_available, memchr_kernel};
+///
+/// for &kernel in KernelId::ALL {
+///     if kernel_available(kernel) {
+///         assert_eq!(memchr_kernel(kernel, b'k', b"quick"), Some(4));
+///     }
+/// }
+/// ```
+#[cfg(feature = "bench-internals")]
+pub fn kernel_available(kernel: KernelId) -> bool {
+    match kernel {
+        KernelId::Avx512 | KernelId::Avx2 | KernelId::Sse2 => {
+            cfg_if! {
+                if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+                    #[inline(always)]
+                    fn imp(kernel: KernelId) -> bool {
+                        x86::has_kernel(kernel)
+                    }
+                } else {
+                    #[inline(always)]
+                    fn imp(_: KernelId) -> bool {
+                        false
+                    }
+                }
+            }
+            imp(kernel)
+        }
+        KernelId::PortableSimd => cfg!(feature = "portable_simd"),
+        KernelId::Libc => cfg!(all(
+            feature = "libc",
+            not(target_arch = "wasm32"),
+            not(target_env = "sgx"),
+        )),
+        KernelId::SwarUnroll4 | KernelId::Swar => true,
+    }
+}
+
+/// Returns the kernel that `memchr` uses for `haystack`.
+///
+/// This must make the same choices as `memchr`, in the same order.
+fn kernel(haystack: &[u8]) -> KernelId {
+    cfg_if! {
+        if #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))] {
+            #[inline(always)]
+            fn imp(haystack: &[u8]) -> KernelId {
+                x86::memchr_kernel(haystack)
+            }
+        } else if #[cfg(feature = "portable_simd")] {
+            #[inline(always)]
+            fn imp(_: &[u8]) -> KernelId {
+                KernelId::PortableSimd
+            }
+        } else if #[cfg(all(
+            feature = "libc",
+            not(target_arch = "wasm32"),
+            not(target_os = "windows"),
+            not(target_env = "sgx"),
+        ))] {
+            #[inline(always)]
+            fn imp(_: &[u8]) -> KernelId {
+                KernelId::Libc
+            }
+        } else if #[cfg(feature = "fallback_unroll4")] {
+            #[inline(always)]
+            fn imp(_: &[u8]) -> KernelId {
+                KernelId::SwarUnroll4
+            }
+        } else {
+            #[inline(always)]
+            fn imp(_: &[u8]) -> KernelId {
+                KernelId::Swar
+            }
+        }
+    }
+    imp(haystack)
+}
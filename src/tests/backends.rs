@@ -0,0 +1,307 @@
+// Each kernel is tested against the shared test corpus on its own, but that
+// corpus is small. This runs every kernel available on the current machine
+// over the same large set of pseudo-random inputs and checks that they all
+// agree with the fallback (SWAR) implementation, so that a kernel that
+// drifts from the others fails immediately.
+
+use fallback;
+use naive;
+
+type Find1 = fn(u8, &[u8]) -> Option<usize>;
+type Find2 = fn(u8, u8, &[u8]) -> Option<usize>;
+type Find3 = fn(u8, u8, u8, &[u8]) -> Option<usize>;
+
+/// A backend's implementation of each routine, where routines that a
+/// backend doesn't provide are `None`.
+struct Backend {
+    name: &'static str,
+    memchr: Option<Find1>,
+    memchr2: Option<Find2>,
+    memchr3: Option<Find3>,
+    memrchr: Option<Find1>,
+    memrchr2: Option<Find2>,
+    memrchr3: Option<Find3>,
+    memchr_not: Option<Find1>,
+    contains_byte: Option<fn(u8, &[u8]) -> bool>,
+    count: Option<fn(u8, &[u8]) -> usize>,
+}
+
+impl Backend {
+    fn empty(name: &'static str) -> Backend {
+        Backend {
+            name,
+            memchr: None,
+            memchr2: None,
+            memchr3: None,
+            memrchr: None,
+            memrchr2: None,
+            memrchr3: None,
+            memchr_not: None,
+            contains_byte: None,
+            count: None,
+        }
+    }
+}
+
+fn backends() -> Vec<Backend> {
+    let mut backends = vec![];
+    backends.push(Backend {
+        memchr: Some(naive::memchr),
+        memchr2: Some(naive::memchr2),
+        memchr3: Some(naive::memchr3),
+        memrchr: Some(naive::memrchr),
+        memrchr2: Some(naive::memrchr2),
+        memrchr3: Some(naive::memrchr3),
+        ..Backend::empty("naive")
+    });
+    backends.push(Backend {
+        memchr: Some(fallback::memchr_unroll4),
+        ..Backend::empty("fallback_unroll4")
+    });
+    #[cfg(all(
+        feature = "libc",
+        not(target_arch = "wasm32"),
+        not(target_env = "sgx"),
+    ))]
+    backends.push(Backend {
+        memchr: Some(::c::memchr),
+        #[cfg(target_os = "linux")]
+        memrchr: Some(::c::memrchr),
+        ..Backend::empty("libc")
+    });
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    {
+        use x86;
+
+        backends.push(Backend {
+            memchr: Some(x86::memchr_sse2),
+            memchr2: Some(x86::memchr2_sse2),
+            memchr3: Some(x86::memchr3_sse2),
+            memrchr: Some(x86::memrchr_sse2),
+            memrchr2: Some(x86::memrchr2_sse2),
+            memrchr3: Some(x86::memrchr3_sse2),
+            memchr_not: Some(x86::memchr_not_sse2),
+            contains_byte: Some(x86::contains_byte_sse2),
+            count: Some(x86::count_sse2),
+            ..Backend::empty("sse2")
+        });
+        if x86::has_avx2() {
+            backends.push(Backend {
+                memchr: Some(x86::memchr_avx2),
+                memchr2: Some(x86::memchr2_avx2),
+                memchr3: Some(x86::memchr3_avx2),
+                memrchr: Some(x86::memrchr_avx2),
+                memrchr2: Some(x86::memrchr2_avx2),
+                memrchr3: Some(x86::memrchr3_avx2),
+                memchr_not: Some(x86::memchr_not_avx2),
+                contains_byte: Some(x86::contains_byte_avx2),
+                count: Some(x86::count_avx2),
+                ..Backend::empty("avx2")
+            });
+        }
+        #[cfg(memchr_runtime_avx512)]
+        {
+            if x86::has_avx512bw() {
+                backends.push(Backend {
+                    memchr: Some(x86::memchr_avx512),
+                    ..Backend::empty("avx512")
+                });
+            }
+        }
+    }
+    #[cfg(feature = "portable_simd")]
+    backends.push(Backend {
+        memchr: Some(::simd::memchr),
+        ..Backend::empty("portable_simd")
+    });
+    backends
+}
+
+/// A small xorshift generator, so that every run sees the same inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// A pseudo-random input: a haystack that starts at `offset` in `buf`, and
+/// the needles to search for in it.
+struct Input {
+    buf: Vec<u8>,
+    offset: usize,
+    needles: [u8; 3],
+}
+
+impl Input {
+    fn haystack(&self) -> &[u8] {
+        &self.buf[self.offset..]
+    }
+}
+
+fn inputs() -> Vec<Input> {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    let mut inputs = vec![];
+    for _ in 0..5_000 {
+        // Small alphabets make matches common, while the full alphabet
+        // makes them rare, so mix both.
+        let alphabet = [2, 4, 16, 256][rng.below(4)];
+        let len = rng.below(700);
+        let offset = rng.below(64);
+        let buf =
+            (0..offset + len).map(|_| rng.below(alphabet) as u8).collect();
+        let mut needles = [0; 3];
+        for n in needles.iter_mut() {
+            *n = rng.below(alphabet) as u8;
+        }
+        inputs.push(Input { buf, offset, needles });
+    }
+    inputs
+}
+
+#[test]
+fn backends_agree_with_fallback() {
+    let backends = backends();
+    for input in inputs() {
+        let [n1, n2, n3] = input.needles;
+        let h = input.haystack();
+        for b in &backends {
+            let check = |routine: &str, got: Option<usize>, expected| {
+                assert_eq!(
+                    expected, got,
+                    "{}::{} disagrees with fallback: needles: {:?}, \
+                     haystack (offset {}): {:?}",
+                    b.name, routine, input.needles, input.offset, h,
+                );
+            };
+            if let Some(f) = b.memchr {
+                check("memchr", f(n1, h), fallback::memchr(n1, h));
+            }
+            if let Some(f) = b.memchr2 {
+                let expected = fallback::memchr2(n1, n2, h);
+                check("memchr2", f(n1, n2, h), expected);
+            }
+            if let Some(f) = b.memchr3 {
+                let expected = fallback::memchr3(n1, n2, n3, h);
+                check("memchr3", f(n1, n2, n3, h), expected);
+            }
+            if let Some(f) = b.memrchr {
+                check("memrchr", f(n1, h), fallback::memrchr(n1, h));
+            }
+            if let Some(f) = b.memrchr2 {
+                let expected = fallback::memrchr2(n1, n2, h);
+                check("memrchr2", f(n1, n2, h), expected);
+            }
+            if let Some(f) = b.memrchr3 {
+                let expected = fallback::memrchr3(n1, n2, n3, h);
+                check("memrchr3", f(n1, n2, n3, h), expected);
+            }
+            if let Some(f) = b.memchr_not {
+                check("memchr_not", f(n1, h), fallback::memchr_not(n1, h));
+            }
+            if let Some(f) = b.contains_byte {
+                let expected = fallback::contains_byte(n1, h);
+                assert_eq!(expected, f(n1, h), "{}::contains_byte", b.name);
+            }
+            if let Some(f) = b.count {
+                let expected = fallback::count(n1, h);
+                assert_eq!(expected, f(n1, h), "{}::count", b.name);
+            }
+        }
+    }
+}
+
+// The count kernels add up per-byte counters after a fixed number of
+// iterations, which the small pseudo-random inputs above never reach. This
+// checks long haystacks where every byte matches (the most that a counter
+// can hold between additions) and where only some do.
+#[test]
+fn backends_count_long_haystacks() {
+    let backends = backends();
+    for len in (0..20_000).step_by(997).chain(16_320..16_400) {
+        let dense = vec![b'\n'; len];
+        let sparse: Vec<u8> =
+            (0..len).map(|i| if i % 37 == 0 { b'\n' } else { b'a' }).collect();
+        for haystack in &[dense, sparse] {
+            let expected = fallback::count(b'\n', haystack);
+            for b in &backends {
+                if let Some(f) = b.count {
+                    let got = f(b'\n', haystack);
+                    assert_eq!(
+                        expected, got,
+                        "{}::count, len {}", b.name, len,
+                    );
+                }
+            }
+            assert_eq!(expected, ::count(b'\n', haystack));
+        }
+    }
+}
+
+// `memchr` documents that its result never depends on the implementation
+// in use. This checks each backend against inputs where implementations are
+// most likely to differ: NUL bytes (which C string routines treat
+// specially), bytes with the high bit set (which differ in sign as a C
+// `char`) and a needle that occurs at every position.
+#[test]
+fn backends_are_deterministic_on_edge_cases() {
+    let mut haystacks: Vec<Vec<u8>> = vec![
+        vec![],
+        vec![0],
+        vec![0; 100],
+        b"abc\x00def\x00".to_vec(),
+        vec![0xFF; 100],
+        (0..=255).collect(),
+        (0..=255).rev().collect(),
+    ];
+    for len in 0..130 {
+        let mut haystack = vec![0x80; len];
+        haystack.push(0);
+        haystack.push(0x7F);
+        haystacks.push(haystack);
+    }
+    let needles = [0x00, 0x01, 0x7F, 0x80, 0xFE, 0xFF];
+    let backends = backends();
+    for haystack in &haystacks {
+        for &n1 in &needles {
+            let expected = haystack.iter().position(|&b| b == n1);
+            let rexpected = haystack.iter().rposition(|&b| b == n1);
+            for b in &backends {
+                if let Some(f) = b.memchr {
+                    let got = f(n1, haystack);
+                    assert_eq!(expected, got, "{}::memchr({})", b.name, n1);
+                }
+                if let Some(f) = b.memrchr {
+                    let got = f(n1, haystack);
+                    assert_eq!(rexpected, got, "{}::memrchr({})", b.name, n1);
+                }
+            }
+            assert_eq!(expected, ::memchr(n1, haystack));
+            assert_eq!(rexpected, ::memrchr(n1, haystack));
+        }
+    }
+}
+
+#[test]
+fn backends_are_registered() {
+    let names: Vec<&str> = backends().iter().map(|b| b.name).collect();
+    assert!(names.contains(&"naive"));
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    assert!(names.contains(&"sse2"));
+}
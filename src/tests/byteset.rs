@@ -0,0 +1,171 @@
+use {
+    ByteSet, first_forbidden_control, memchr_byteset, memchr_not_byteset,
+};
+
+use tests::memchr_tests;
+
+/// Returns the members of `set` by searching a haystack containing every
+/// possible byte with `find`.
+fn members<F>(set: &ByteSet, find: F) -> Vec<u8>
+where F: Fn(&ByteSet, &[u8]) -> Option<usize>
+{
+    let haystack: Vec<u8> = (0..256).map(|b| b as u8).collect();
+    let mut members = vec![];
+    let mut start = 0;
+    while let Some(i) = find(set, &haystack[start..]) {
+        members.push(haystack[start + i]);
+        start += i + 1;
+    }
+    members
+}
+
+#[test]
+fn byteset_membership() {
+    let mut set = ByteSet::new();
+    assert!(set.is_empty());
+    for b in 0..256 {
+        assert!(!set.contains(b as u8));
+    }
+
+    set.add(b'a');
+    set.add(0x00);
+    set.add(0x7F);
+    set.add(0x80);
+    set.add(0xFF);
+    assert_eq!(5, set.len());
+    assert_eq!(
+        vec![0x00, b'a', 0x7F, 0x80, 0xFF],
+        members(&set, memchr_byteset),
+    );
+
+    set.remove(b'a');
+    set.remove(b'b');
+    assert_eq!(4, set.len());
+    assert!(!set.contains(b'a'));
+}
+
+#[test]
+fn byteset_full() {
+    let all: Vec<u8> = (0..256).map(|b| b as u8).collect();
+    let set = ByteSet::from_bytes(&all);
+    assert_eq!(256, set.len());
+    assert_eq!(all, members(&set, memchr_byteset));
+    assert_eq!(Some(0), memchr_byteset(&set, &[0xAB; 100]));
+}
+
+#[test]
+fn byteset_empty() {
+    let set = ByteSet::new();
+    assert_eq!(None, memchr_byteset(&set, b""));
+    assert_eq!(None, memchr_byteset(&set, &[0, 1, 2, 0xFF]));
+    assert_eq!(None, memchr_byteset(&set, &[b'a'; 100]));
+}
+
+#[test]
+fn byteset_find() {
+    for test in memchr_tests() {
+        test.three(false, |n1, n2, n3, corpus| {
+            memchr_byteset(&ByteSet::from_bytes(&[n1, n2, n3]), corpus)
+        });
+    }
+}
+
+#[test]
+fn not_byteset_find() {
+    let set = ByteSet::from_bytes(b" \t\r\n");
+    assert_eq!(Some(0), memchr_not_byteset(&set, b"foo"));
+    assert_eq!(Some(4), memchr_not_byteset(&set, b" \t\r\nfoo"));
+    let mut haystack = vec![b' '; 40];
+    haystack.push(b'x');
+    assert_eq!(Some(40), memchr_not_byteset(&set, &haystack));
+    assert_eq!(None, memchr_not_byteset(&set, b""));
+    assert_eq!(None, memchr_not_byteset(&set, b" \t\r\n"));
+    assert_eq!(None, memchr_not_byteset(&set, &[b'\n'; 100]));
+}
+
+#[test]
+fn not_byteset_full_and_empty() {
+    let all: Vec<u8> = (0..256).map(|b| b as u8).collect();
+    assert_eq!(None, memchr_not_byteset(&ByteSet::from_bytes(&all), &all));
+    assert_eq!(Some(0), memchr_not_byteset(&ByteSet::new(), &all));
+    assert_eq!(None, memchr_not_byteset(&ByteSet::new(), b""));
+}
+
+#[test]
+fn forbidden_control_allowed_whitespace() {
+    let allow = ByteSet::from_bytes(b"\t\r\n");
+    assert_eq!(None, first_forbidden_control(&allow, b""));
+    assert_eq!(None, first_forbidden_control(&allow, b"a\tb\r\nc"));
+    assert_eq!(Some(3), first_forbidden_control(&allow, b"a\tb\x07c"));
+    assert_eq!(Some(0), first_forbidden_control(&allow, b"\x00"));
+    assert_eq!(Some(2), first_forbidden_control(&allow, b"\n\t\x1F"));
+}
+
+#[test]
+fn forbidden_control_excludes_other_bytes() {
+    let allow = ByteSet::new();
+    let others: Vec<u8> = (0x20..256).map(|b| b as u8).collect();
+    assert_eq!(None, first_forbidden_control(&allow, &others));
+    for b in 0..0x20 {
+        let mut haystack = others.clone();
+        haystack.insert(100, b);
+        assert_eq!(Some(100), first_forbidden_control(&allow, &haystack));
+    }
+}
+
+#[test]
+fn forbidden_control_after_allowed_run() {
+    let allow = ByteSet::from_bytes(b"\n");
+    let mut haystack = vec![b'\n'; 100];
+    assert_eq!(None, first_forbidden_control(&allow, &haystack));
+    haystack.push(0x07);
+    assert_eq!(Some(100), first_forbidden_control(&allow, &haystack));
+}
+
+quickcheck! {
+    fn qc_forbidden_control_matches_naive(
+        allowed: Vec<u8>, corpus: Vec<u8>
+    ) -> bool {
+        let allow = ByteSet::from_bytes(&allowed);
+        // Make about half of the bytes control bytes.
+        let corpus: Vec<u8> = corpus
+            .into_iter()
+            .map(|b| if b & 1 == 0 { b >> 3 } else { b })
+            .collect();
+        let expected =
+            corpus.iter().position(|&b| b < 0x20 && !allow.contains(b));
+        first_forbidden_control(&allow, &corpus) == expected
+    }
+
+    fn qc_not_byteset_matches_naive(bytes: Vec<u8>, corpus: Vec<u8>) -> bool {
+        let set = ByteSet::from_bytes(&bytes);
+        let expected = corpus.iter().position(|&b| !set.contains(b));
+        memchr_not_byteset(&set, &corpus) == expected
+    }
+
+    fn qc_byteset_contains_matches_naive(bytes: Vec<u8>, b: u8) -> bool {
+        ByteSet::from_bytes(&bytes).contains(b) == bytes.contains(&b)
+    }
+
+    fn qc_byteset_members_matches_fallback(bytes: Vec<u8>) -> bool {
+        let set = ByteSet::from_bytes(&bytes);
+        let expected = members(&set, |set, h| {
+            h.iter().position(|&b| set.contains(b))
+        });
+        members(&set, memchr_byteset) == expected
+    }
+
+    fn qc_byteset_matches_naive(bytes: Vec<u8>, corpus: Vec<u8>) -> bool {
+        let set = ByteSet::from_bytes(&bytes);
+        let expected = corpus.iter().position(|b| bytes.contains(b));
+        memchr_byteset(&set, &corpus) == expected
+    }
+
+    fn qc_byteset_small_set_matches_naive(
+        n1: u8, n2: u8, corpus: Vec<u8>
+    ) -> bool {
+        let set = ByteSet::from_bytes(&[n1, n2]);
+        let expected = corpus.iter().position(|&b| b == n1 || b == n2);
+        memchr_byteset(&set, &corpus) == expected
+    }
+}
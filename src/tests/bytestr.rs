@@ -0,0 +1,63 @@
+use std::str;
+
+use ByteStr;
+
+#[test]
+fn bytestr_find() {
+    let s = ByteStr(b"a,b;c,d");
+    assert_eq!(Some(1), s.find(b','));
+    assert_eq!(Some(5), s.rfind(b','));
+    assert_eq!(None, s.find(b'z'));
+    assert_eq!(None, s.rfind(b'z'));
+    assert!(s.contains(b';'));
+    assert!(!s.contains(b'z'));
+    assert!(!ByteStr(b"").contains(b'a'));
+}
+
+#[test]
+fn bytestr_split() {
+    let split = |s: &'static str| -> Vec<&'static [u8]> {
+        ByteStr(s.as_bytes()).split(b',').collect()
+    };
+    assert_eq!(vec![&b""[..]], split(""));
+    assert_eq!(vec![&b""[..], b""], split(","));
+    assert_eq!(vec![&b"a"[..], b"b", b"c"], split("a,b,c"));
+    assert_eq!(vec![&b""[..], b"a", b"", b"b", b""], split(",a,,b,"));
+}
+
+#[test]
+fn bytestr_lines() {
+    let lines = |s: &'static str| -> Vec<&'static [u8]> {
+        ByteStr(s.as_bytes()).lines().collect()
+    };
+    assert_eq!(Vec::<&[u8]>::new(), lines(""));
+    assert_eq!(vec![&b""[..]], lines("\n"));
+    assert_eq!(vec![&b"a"[..], b"b"], lines("a\nb"));
+    assert_eq!(vec![&b"a"[..], b"", b"b"], lines("a\r\n\r\nb\n"));
+    assert_eq!(vec![&b"a\rb"[..], b"c\r"], lines("a\rb\nc\r"));
+}
+
+quickcheck! {
+    fn qc_bytestr_split_matches_str(corpus: Vec<u8>) -> bool {
+        let corpus: String =
+            corpus.into_iter().map(|b| (b'a' + b % 3) as char).collect();
+        let got: Vec<&str> = ByteStr(corpus.as_bytes())
+            .split(b'a')
+            .map(|piece| str::from_utf8(piece).unwrap())
+            .collect();
+        got == corpus.split('a').collect::<Vec<&str>>()
+    }
+
+    fn qc_bytestr_lines_matches_str(corpus: Vec<u8>) -> bool {
+        let alphabet = [b'a', b'\r', b'\n'];
+        let corpus: String = corpus
+            .into_iter()
+            .map(|b| alphabet[b as usize % 3] as char)
+            .collect();
+        let got: Vec<&str> = ByteStr(corpus.as_bytes())
+            .lines()
+            .map(|line| str::from_utf8(line).unwrap())
+            .collect();
+        got == corpus.lines().collect::<Vec<&str>>()
+    }
+}
@@ -0,0 +1,96 @@
+// These tests pin the behavior of this crate to the platform's libc, which we
+// treat as the reference implementation. The oracle calls libc directly
+// (instead of going through our own wrappers in `c.rs`) so that any mistake
+// in our own pointer arithmetic can't cancel itself out.
+
+extern crate libc;
+
+use self::libc::{c_int, c_void, size_t};
+
+use fallback;
+use {memchr, memchr0};
+
+fn libc_memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let p = unsafe {
+        libc::memchr(
+            haystack.as_ptr() as *const c_void,
+            n1 as c_int,
+            haystack.len() as size_t,
+        )
+    };
+    if p.is_null() {
+        None
+    } else {
+        Some(p as usize - (haystack.as_ptr() as usize))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn libc_memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    if haystack.is_empty() {
+        return None;
+    }
+    let p = unsafe {
+        libc::memrchr(
+            haystack.as_ptr() as *const c_void,
+            n1 as c_int,
+            haystack.len() as size_t,
+        )
+    };
+    if p.is_null() {
+        None
+    } else {
+        Some(p as usize - (haystack.as_ptr() as usize))
+    }
+}
+
+/// Returns true if and only if `f` agrees with `oracle` on every suffix of
+/// `corpus`. Since each suffix starts at a different address, this exercises
+/// every possible alignment of the haystack.
+fn all_alignments<F, G>(corpus: &[u8], f: F, oracle: G) -> bool
+where F: Fn(&[u8]) -> Option<usize>,
+      G: Fn(&[u8]) -> Option<usize>,
+{
+    (0..corpus.len() + 1).all(|i| f(&corpus[i..]) == oracle(&corpus[i..]))
+}
+
+quickcheck! {
+    fn qc_memchr1_matches_libc(n1: u8, corpus: Vec<u8>) -> bool {
+        all_alignments(
+            &corpus,
+            |h| memchr(n1, h),
+            |h| libc_memchr(n1, h),
+        )
+    }
+
+    fn qc_memchr1_fallback_matches_libc(n1: u8, corpus: Vec<u8>) -> bool {
+        all_alignments(
+            &corpus,
+            |h| fallback::memchr(n1, h),
+            |h| libc_memchr(n1, h),
+        )
+    }
+
+    fn qc_memchr0_matches_libc(corpus: Vec<u8>) -> bool {
+        all_alignments(&corpus, memchr0, |h| libc_memchr(0, h))
+    }
+}
+
+#[cfg(target_os = "linux")]
+quickcheck! {
+    fn qc_memrchr1_matches_libc(n1: u8, corpus: Vec<u8>) -> bool {
+        all_alignments(
+            &corpus,
+            |h| ::memrchr(n1, h),
+            |h| libc_memrchr(n1, h),
+        )
+    }
+
+    fn qc_memrchr1_fallback_matches_libc(n1: u8, corpus: Vec<u8>) -> bool {
+        all_alignments(
+            &corpus,
+            |h| fallback::memrchr(n1, h),
+            |h| libc_memrchr(n1, h),
+        )
+    }
+}
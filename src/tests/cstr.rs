@@ -0,0 +1,26 @@
+use {cstr_len, cstr_len_raw};
+
+#[test]
+fn cstr_len_terminated() {
+    assert_eq!(Some(0), cstr_len(b"\x00"));
+    assert_eq!(Some(3), cstr_len(b"foo\x00"));
+    assert_eq!(Some(3), cstr_len(b"foo\x00bar\x00"));
+}
+
+#[test]
+fn cstr_len_unterminated() {
+    assert_eq!(None, cstr_len(b""));
+    assert_eq!(None, cstr_len(b"foo"));
+    assert_eq!(None, cstr_len(&[b'a'; 100]));
+}
+
+#[test]
+fn cstr_len_raw_bounded() {
+    let buf = b"foobar\x00baz";
+    unsafe {
+        assert_eq!(Some(6), cstr_len_raw(buf.as_ptr(), buf.len()));
+        assert_eq!(Some(6), cstr_len_raw(buf.as_ptr(), 7));
+        assert_eq!(None, cstr_len_raw(buf.as_ptr(), 6));
+        assert_eq!(None, cstr_len_raw(buf.as_ptr(), 0));
+    }
+}
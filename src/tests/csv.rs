@@ -0,0 +1,44 @@
+use csv_fields;
+
+fn fields(record: &[u8]) -> Vec<&[u8]> {
+    csv_fields(record).collect()
+}
+
+#[test]
+fn csv_fields_unquoted() {
+    assert_eq!(vec![&b""[..]], fields(b""));
+    assert_eq!(vec![&b""[..], b""], fields(b","));
+    assert_eq!(vec![&b"a"[..], b"bc", b""], fields(b"a,bc,"));
+}
+
+#[test]
+fn csv_fields_quoted_commas() {
+    assert_eq!(vec![&b"a,b"[..]], fields(br#""a,b""#));
+    assert_eq!(vec![&b"x"[..], b",,", b"y"], fields(br#"x,",,",y"#));
+    assert_eq!(vec![&b""[..], b""], fields(br#""","#));
+}
+
+#[test]
+fn csv_fields_escaped_quotes() {
+    assert_eq!(vec![&br#"a""b"#[..]], fields(br#""a""b""#));
+    assert_eq!(vec![&br#""""#[..]], fields(br#""""""#));
+    assert_eq!(
+        vec![&br#"say ""hi"", ok"#[..], b"z"],
+        fields(br#""say ""hi"", ok",z"#),
+    );
+}
+
+#[test]
+fn csv_fields_unclosed_quote() {
+    assert_eq!(vec![&b"a"[..], br#""b,c"#], fields(br#"a,"b,c"#));
+}
+
+quickcheck! {
+    fn qc_csv_fields_without_quotes_is_split(corpus: Vec<u8>) -> bool {
+        // Map every byte to one of `+,-.`, so that no quotes occur.
+        let corpus: Vec<u8> =
+            corpus.into_iter().map(|b| b'+' + b % 4).collect();
+        let expected: Vec<&[u8]> = corpus.split(|&b| b == b',').collect();
+        fields(&corpus) == expected
+    }
+}
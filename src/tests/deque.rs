@@ -0,0 +1,53 @@
+use std::collections::VecDeque;
+
+use memchr_deque;
+
+/// Returns a deque containing `bytes`, whose contents wrap around the end
+/// of its ring buffer after the first `split` bytes.
+fn wrapped(bytes: &[u8], split: usize) -> VecDeque<u8> {
+    let mut deque = VecDeque::with_capacity(bytes.len());
+    deque.extend(&bytes[split..]);
+    // Pushing to the front of a deque that starts at the beginning of its
+    // buffer wraps around to the end of the buffer.
+    for &b in bytes[..split].iter().rev() {
+        deque.push_front(b);
+    }
+    if split > 0 {
+        assert_eq!(split, deque.as_slices().0.len(), "deque didn't wrap");
+    }
+    deque
+}
+
+#[test]
+fn memchr_deque_contiguous() {
+    let deque: VecDeque<u8> = b"foo,bar".iter().cloned().collect();
+    assert_eq!(Some(3), memchr_deque(b',', &deque));
+    assert_eq!(None, memchr_deque(b';', &deque));
+    assert_eq!(None, memchr_deque(b',', &VecDeque::new()));
+}
+
+#[test]
+fn memchr_deque_across_boundary() {
+    let deque = wrapped(b"foo,bar,baz", 5);
+    assert_eq!(Some(3), memchr_deque(b',', &deque));
+    assert_eq!(Some(4), memchr_deque(b'b', &deque));
+    assert_eq!(None, memchr_deque(b';', &deque));
+}
+
+#[test]
+fn memchr_deque_second_slice_only() {
+    let deque = wrapped(b"foobar,baz", 4);
+    assert_eq!(Some(6), memchr_deque(b',', &deque));
+    assert_eq!(Some(b','), deque.get(6).cloned());
+    assert_eq!(Some(9), memchr_deque(b'z', &deque));
+}
+
+quickcheck! {
+    fn qc_memchr_deque_matches_position(
+        bytes: Vec<u8>, split: usize, n1: u8
+    ) -> bool {
+        let split = split % (bytes.len() + 1);
+        let deque = wrapped(&bytes, split);
+        memchr_deque(n1, &deque) == bytes.iter().position(|&b| b == n1)
+    }
+}
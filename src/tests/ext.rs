@@ -0,0 +1,18 @@
+use {MemchrExt, memchr, memrchr};
+
+#[test]
+fn ext_vec() {
+    let buf = b"a\nb\nc".to_vec();
+    assert_eq!(Some(1), buf.find_byte(b'\n'));
+    assert_eq!(Some(3), buf.rfind_byte(b'\n'));
+    assert_eq!(None, Vec::new().find_byte(b'\n'));
+    assert_eq!(None, Vec::new().rfind_byte(b'\n'));
+}
+
+quickcheck! {
+    fn qc_ext_matches_free_functions(n1: u8, corpus: Vec<u8>) -> bool {
+        corpus.find_byte(n1) == memchr(n1, &corpus)
+            && corpus.rfind_byte(n1) == memrchr(n1, &corpus)
+            && corpus[..].find_byte(n1) == memchr(n1, &corpus)
+    }
+}
@@ -0,0 +1,46 @@
+use chunk_size_line;
+
+#[test]
+fn chunk_size_line_simple() {
+    assert_eq!(Some((0, 3)), chunk_size_line(b"0\r\n\r\n"));
+    assert_eq!(Some((10, 3)), chunk_size_line(b"a\r\n0123456789\r\n"));
+    assert_eq!(Some((0xFFF, 5)), chunk_size_line(b"fFf\r\n"));
+    assert_eq!(Some((0x1A, 4)), chunk_size_line(b"1A\r\n"));
+}
+
+#[test]
+fn chunk_size_line_extensions() {
+    assert_eq!(Some((5, 14)), chunk_size_line(b"5;name=value\r\nhello"));
+    assert_eq!(Some((5, 9)), chunk_size_line(b"5 \t;ext\r\nhello"));
+    assert_eq!(Some((5, 11)), chunk_size_line(b"5;a=1;b=2\r\nhello"));
+    // A `\r\n` inside the data doesn't end the size line early.
+    assert_eq!(Some((2, 5)), chunk_size_line(b"2;x\r\n\r\n\r\n"));
+}
+
+#[test]
+fn chunk_size_line_incomplete() {
+    assert_eq!(None, chunk_size_line(b""));
+    assert_eq!(None, chunk_size_line(b"1a"));
+    assert_eq!(None, chunk_size_line(b"1a\r"));
+    assert_eq!(None, chunk_size_line(b"1a;ext\n"));
+}
+
+#[test]
+fn chunk_size_line_invalid() {
+    assert_eq!(None, chunk_size_line(b"\r\n"));
+    assert_eq!(None, chunk_size_line(b";ext\r\n"));
+    assert_eq!(None, chunk_size_line(b"1g\r\n"));
+    assert_eq!(None, chunk_size_line(b" 1\r\n"));
+    assert_eq!(None, chunk_size_line(b"+1\r\n"));
+    let huge = format!("{:x}0\r\n", usize::MAX);
+    assert_eq!(None, chunk_size_line(huge.as_bytes()));
+}
+
+quickcheck! {
+    fn qc_chunk_size_line_roundtrip(size: usize, data: Vec<u8>) -> bool {
+        let mut haystack = format!("{:x}\r\n", size).into_bytes();
+        let consumed = haystack.len();
+        haystack.extend_from_slice(&data);
+        chunk_size_line(&haystack) == Some((size, consumed))
+    }
+}
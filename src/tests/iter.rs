@@ -1,5 +1,8 @@
 use tests::memchr_tests;
-use {Memchr, Memchr2, Memchr3};
+use {
+    Memchr, Memchr2, Memchr3, MemchrCounted, match_gaps, memchr_grouped,
+    memrchr, memrchr_iter,
+};
 
 #[test]
 fn memchr1_iter() {
@@ -47,6 +50,164 @@ fn memrchr3_iter() {
     }
 }
 
+#[test]
+fn memrchr1_iter_match_at_zero() {
+    assert_eq!(vec![0], memrchr_iter(b'a', b"a").collect::<Vec<_>>());
+    assert_eq!(vec![1, 0], memrchr_iter(b'a', b"aa").collect::<Vec<_>>());
+    assert_eq!(vec![2, 0], memrchr_iter(b'a', b"aba").collect::<Vec<_>>());
+
+    // Once the match at index 0 is yielded, the iterator stays exhausted.
+    let mut iter = memrchr_iter(b'a', b"a");
+    assert_eq!(Some(0), iter.next());
+    assert_eq!(None, iter.next());
+    assert_eq!(None, iter.next());
+    assert_eq!(None, iter.next_back());
+}
+
+#[test]
+fn memrchr2_iter_match_at_zero() {
+    let iter = Memchr2::new(b'a', b'b', b"ba").rev();
+    assert_eq!(vec![1, 0], iter.collect::<Vec<_>>());
+    let iter = Memchr3::new(b'a', b'b', b'c', b"cba").rev();
+    assert_eq!(vec![2, 1, 0], iter.collect::<Vec<_>>());
+}
+
+#[test]
+fn memchr1_iter_counted() {
+    for test in memchr_tests() {
+        test.iter_one(false, MemchrCounted::new);
+        test.iter_one(true, |n1, corpus| MemchrCounted::new(n1, corpus).rev());
+    }
+}
+
+#[test]
+fn memchr1_iter_counted_len() {
+    let mut iter = MemchrCounted::new(b'a', b"abcabca");
+    assert_eq!(3, iter.len());
+    assert_eq!(Some(0), iter.next());
+    assert_eq!((2, Some(2)), iter.size_hint());
+    assert_eq!(Some(6), iter.next_back());
+    assert_eq!(1, iter.len());
+    assert_eq!(Some(3), iter.next());
+    assert_eq!(0, iter.len());
+    assert_eq!(None, iter.next());
+    assert_eq!(None, iter.next_back());
+    assert_eq!(0, iter.len());
+}
+
+// When nearly every byte matches, each search ends in the first vector (or
+// at the first byte), so this checks that every match is still found at
+// every alignment.
+#[test]
+fn memchr1_iter_dense() {
+    for gap in 1..70 {
+        let haystack: Vec<u8> = (0..300)
+            .map(|i| if i % gap == gap - 1 { b'b' } else { b'a' })
+            .collect();
+        for start in 0..33 {
+            let haystack = &haystack[start..];
+            let expected: Vec<usize> = positions1(b'a', haystack).collect();
+            let got: Vec<usize> = Memchr::new(b'a', haystack).collect();
+            assert_eq!(expected, got, "gap: {}, start: {}", gap, start);
+        }
+    }
+}
+
+// `next_back` collects the matches in up to 64 bytes at once, so this
+// checks dense matches at every alignment, against a naive reverse iterator
+// that calls `memrchr` once per match.
+#[test]
+fn memrchr1_iter_dense() {
+    for gap in 1..70 {
+        let haystack: Vec<u8> = (0..300)
+            .map(|i| if i % gap == gap - 1 { b'b' } else { b'a' })
+            .collect();
+        for start in 0..33 {
+            let haystack = &haystack[start..];
+            let expected = repeated_memrchr(b'a', haystack);
+            let got: Vec<usize> = Memchr::new(b'a', haystack).rev().collect();
+            assert_eq!(expected, got, "gap: {}, start: {}", gap, start);
+            let got: Vec<usize> = memrchr_iter(b'a', haystack).collect();
+            assert_eq!(expected, got, "gap: {}, start: {}", gap, start);
+        }
+    }
+}
+
+// Once `next_back` has collected matches, `next` must yield them and no
+// others, whichever end the iteration is resumed from.
+#[test]
+fn memchr1_iter_dense_both_ends() {
+    let haystack: Vec<u8> =
+        (0..500).map(|i| if i % 7 == 0 { b'b' } else { b'a' }).collect();
+    let expected: Vec<usize> = positions1(b'a', &haystack).collect();
+    for backs in 0..expected.len() + 1 {
+        let mut it = Memchr::new(b'a', &haystack);
+        let mut back: Vec<usize> = it.by_ref().rev().take(backs).collect();
+        let mut got: Vec<usize> = it.collect();
+        back.reverse();
+        got.extend(back);
+        assert_eq!(expected, got, "backs: {}", backs);
+    }
+    for take_side in &[[true, false], [false, true]] {
+        let it = Memchr::new(b'a', &haystack);
+        let got = double_ended_take(it, take_side.iter().cycle().cloned());
+        assert_eq!(expected, got);
+    }
+}
+
+#[test]
+fn memchr1_grouped() {
+    let haystack = b"a.a.....a.......aa......a.";
+    let groups: Vec<(usize, Vec<usize>)> =
+        memchr_grouped(b'a', haystack, 8).collect();
+    assert_eq!(
+        groups,
+        vec![(0, vec![0, 2]), (8, vec![0]), (16, vec![0, 1]), (24, vec![0])],
+    );
+}
+
+#[test]
+fn memchr1_grouped_skips_empty_windows() {
+    let mut haystack = vec![b'.'; 64];
+    haystack[3] = b'a';
+    haystack[60] = b'a';
+    let groups: Vec<(usize, Vec<usize>)> =
+        memchr_grouped(b'a', &haystack, 8).collect();
+    assert_eq!(groups, vec![(0, vec![3]), (56, vec![4])]);
+}
+
+#[test]
+fn memchr1_grouped_partial_last_window() {
+    // The last window covers only bytes 16..21.
+    let haystack = b"........a.......a...a";
+    let groups: Vec<(usize, Vec<usize>)> =
+        memchr_grouped(b'a', haystack, 8).collect();
+    assert_eq!(groups, vec![(8, vec![0]), (16, vec![0, 4])]);
+
+    let groups: Vec<(usize, Vec<usize>)> =
+        memchr_grouped(b'a', b"a.a", 8).collect();
+    assert_eq!(groups, vec![(0, vec![0, 2])]);
+    assert_eq!(memchr_grouped(b'a', b"", 8).count(), 0);
+    assert_eq!(memchr_grouped(b'a', b"........", 8).count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn memchr1_grouped_zero_group() {
+    memchr_grouped(b'a', b"abc", 0);
+}
+
+#[test]
+fn match_gaps_deltas() {
+    let gaps: Vec<usize> = match_gaps(b'a', b"a.a..a").collect();
+    assert_eq!(gaps, vec![0, 2, 3]);
+
+    let gaps: Vec<usize> = match_gaps(b'a', b"..aa.a..").collect();
+    assert_eq!(gaps, vec![2, 1, 2]);
+    assert_eq!(match_gaps(b'a', b"").count(), 0);
+    assert_eq!(match_gaps(b'a', b"....").count(), 0);
+}
+
 quickcheck! {
     fn qc_memchr_double_ended_iter(
         needle: u8, data: Vec<u8>, take_side: Vec<bool>
@@ -62,6 +223,21 @@ quickcheck! {
         all_found.iter().cloned().eq(positions1(needle, &data))
     }
 
+    fn qc_memchr_double_ended_iter_dense(
+        data: Vec<u8>, take_side: Vec<bool>
+    ) -> bool {
+        // A two byte alphabet, so that about half of all bytes match.
+        let data: Vec<u8> = data.iter().map(|&b| b & 1).collect();
+        let mut take_side = take_side;
+        if take_side.is_empty() { take_side.push(false) };
+
+        let iter = Memchr::new(1, &data);
+        let all_found = double_ended_take(
+            iter, take_side.iter().cycle().cloned());
+
+        all_found.iter().cloned().eq(positions1(1, &data))
+    }
+
     fn qc_memchr2_double_ended_iter(
         needle1: u8, needle2: u8, data: Vec<u8>, take_side: Vec<bool>
     ) -> bool {
@@ -136,6 +312,26 @@ quickcheck! {
         answer.rev().eq(Memchr3::new(needle1, needle2, needle3, &data).rev())
     }
 
+    fn qc_memchr1_iter_counted_exact_size(
+        needle: u8, data: Vec<u8>, take_side: Vec<bool>
+    ) -> bool {
+        let mut iter = MemchrCounted::new(needle, &data);
+        let mut remaining = positions1(needle, &data).count();
+        for &front in take_side.iter().chain(&[true]) {
+            if iter.len() != remaining
+                || iter.size_hint() != (remaining, Some(remaining))
+            {
+                return false;
+            }
+            let next = if front { iter.next() } else { iter.next_back() };
+            if next.is_none() {
+                return remaining == 0;
+            }
+            remaining -= 1;
+        }
+        iter.count() == remaining
+    }
+
     fn qc_memchr1_iter_size_hint(data: Vec<u8>) -> bool {
         // test that the size hint is within reasonable bounds
         let needle = 0;
@@ -156,6 +352,38 @@ quickcheck! {
     }
 }
 
+quickcheck! {
+    fn qc_memchr_grouped(needle: u8, data: Vec<u8>, group: u8) -> bool {
+        let group = group as usize % 16 + 1;
+        let mut expected: Vec<(usize, Vec<usize>)> = vec![];
+        for i in positions1(needle, &data) {
+            let start = i - i % group;
+            match expected.last_mut() {
+                Some(&mut (s, ref mut offsets)) if s == start => {
+                    offsets.push(i - start);
+                    continue;
+                }
+                _ => {}
+            }
+            expected.push((start, vec![i - start]));
+        }
+        let got: Vec<(usize, Vec<usize>)> =
+            memchr_grouped(needle, &data, group).collect();
+        expected == got
+    }
+
+    fn qc_match_gaps_sum_to_positions(needle: u8, data: Vec<u8>) -> bool {
+        let mut at = 0;
+        let positions: Vec<usize> = match_gaps(needle, &data)
+            .map(|gap| {
+                at += gap;
+                at
+            })
+            .collect();
+        positions == positions1(needle, &data).collect::<Vec<usize>>()
+    }
+}
+
 // take items from a DEI, taking front for each true and back for each false.
 // Return a vector with the concatenation of the fronts and the reverse of the
 // backs.
@@ -187,6 +415,18 @@ fn double_ended_take<I, J>(mut iter: I, take_side: J) -> Vec<I::Item>
     all_found
 }
 
+// return the indices of haystack that match the needle in reverse order, by
+// calling memrchr once per match
+fn repeated_memrchr(n1: u8, haystack: &[u8]) -> Vec<usize> {
+    let mut found = vec![];
+    let mut end = haystack.len();
+    while let Some(i) = memrchr(n1, &haystack[..end]) {
+        found.push(i);
+        end = i;
+    }
+    found
+}
+
 // return an iterator of the 0-based indices of haystack that match the needle
 fn positions1<'a>(
     n1: u8,
@@ -0,0 +1,205 @@
+use {
+    LineEnding, count_lines, detect_line_ending, line_col, nth_line,
+    nth_line_offset,
+};
+
+#[test]
+fn line_col_first_line() {
+    assert_eq!((1, 1), line_col(b"foo\nbar", 0));
+    assert_eq!((1, 3), line_col(b"foo\nbar", 2));
+    assert_eq!((1, 1), line_col(b"", 0));
+}
+
+#[test]
+fn line_col_after_newlines() {
+    let haystack = b"a\nbc\n\ndef\nghij";
+    assert_eq!((2, 1), line_col(haystack, 2));
+    assert_eq!((2, 2), line_col(haystack, 3));
+    assert_eq!((3, 1), line_col(haystack, 5));
+    assert_eq!((4, 3), line_col(haystack, 8));
+    assert_eq!((5, 4), line_col(haystack, 13));
+}
+
+#[test]
+fn line_col_at_newline() {
+    let haystack = b"ab\n\ncd\n";
+    assert_eq!((1, 3), line_col(haystack, 2));
+    assert_eq!((2, 1), line_col(haystack, 3));
+    assert_eq!((3, 3), line_col(haystack, 6));
+    assert_eq!((4, 1), line_col(haystack, 7));
+}
+
+#[test]
+fn line_col_clamps_offset() {
+    assert_eq!((2, 3), line_col(b"a\nbc", 4));
+    assert_eq!((2, 3), line_col(b"a\nbc", 100));
+    assert_eq!((1, 1), line_col(b"", usize::MAX));
+}
+
+#[test]
+fn nth_line_first() {
+    assert_eq!(Some(&b"foo"[..]), nth_line(0, b"foo\nbar"));
+    assert_eq!(Some(&b"foo"[..]), nth_line(0, b"foo"));
+    assert_eq!(Some(&b""[..]), nth_line(0, b"\nbar"));
+    assert_eq!(Some(0), nth_line_offset(0, b"foo"));
+    assert_eq!(None, nth_line(0, b""));
+    assert_eq!(None, nth_line_offset(0, b""));
+}
+
+#[test]
+fn nth_line_middle() {
+    let haystack = b"a\nbc\n\ndef\r\nghij";
+    assert_eq!(Some(&b"bc"[..]), nth_line(1, haystack));
+    assert_eq!(Some(&b""[..]), nth_line(2, haystack));
+    assert_eq!(Some(&b"def\r"[..]), nth_line(3, haystack));
+    assert_eq!(Some(2), nth_line_offset(1, haystack));
+    assert_eq!(Some(5), nth_line_offset(2, haystack));
+    assert_eq!(Some(6), nth_line_offset(3, haystack));
+}
+
+#[test]
+fn nth_line_last() {
+    // The last line is yielded whether or not it ends with a newline.
+    assert_eq!(Some(&b"ghij"[..]), nth_line(2, b"a\nb\nghij"));
+    assert_eq!(Some(&b"ghij"[..]), nth_line(2, b"a\nb\nghij\n"));
+    assert_eq!(Some(4), nth_line_offset(2, b"a\nb\nghij\n"));
+}
+
+#[test]
+fn nth_line_past_end() {
+    assert_eq!(None, nth_line(3, b"a\nb\nc"));
+    assert_eq!(None, nth_line(3, b"a\nb\nc\n"));
+    assert_eq!(None, nth_line_offset(3, b"a\nb\nc\n"));
+    assert_eq!(None, nth_line(usize::MAX, b"a\nb\nc"));
+    assert_eq!(None, nth_line(1, b"\n"));
+}
+
+#[test]
+fn detect_line_ending_lf() {
+    assert_eq!(LineEnding::Lf, detect_line_ending(b"a\nb\nc\n"));
+    assert_eq!(LineEnding::Lf, detect_line_ending(b"\n"));
+    assert_eq!(LineEnding::Lf, detect_line_ending(b"\n\n"));
+    // A lone `\r` isn't a line ending.
+    assert_eq!(LineEnding::Lf, detect_line_ending(b"a\rb\nc"));
+}
+
+#[test]
+fn detect_line_ending_crlf() {
+    assert_eq!(LineEnding::CrLf, detect_line_ending(b"a\r\nb\r\nc\r\n"));
+    assert_eq!(LineEnding::CrLf, detect_line_ending(b"\r\n"));
+    assert_eq!(LineEnding::CrLf, detect_line_ending(b"\r\r\n"));
+}
+
+#[test]
+fn detect_line_ending_mixed() {
+    assert_eq!(LineEnding::Mixed, detect_line_ending(b"a\r\nb\nc"));
+    assert_eq!(LineEnding::Mixed, detect_line_ending(b"\na\r\n"));
+    assert_eq!(LineEnding::Mixed, detect_line_ending(b"\r\n\n"));
+    let mut haystack = b"line\r\n".repeat(1000);
+    haystack.extend_from_slice(b"last\n");
+    assert_eq!(LineEnding::Mixed, detect_line_ending(&haystack));
+}
+
+#[test]
+fn detect_line_ending_none() {
+    assert_eq!(LineEnding::None, detect_line_ending(b""));
+    assert_eq!(LineEnding::None, detect_line_ending(b"no newlines"));
+    assert_eq!(LineEnding::None, detect_line_ending(b"a\rb\r"));
+}
+
+/// Count lines the way `bytecount::count` would be used to: one per `\n`,
+/// plus one for an unterminated last line.
+fn naive_count_lines(haystack: &[u8]) -> usize {
+    let newlines = haystack.iter().filter(|&&b| b == b'\n').count();
+    match haystack.last() {
+        Some(&b) if b != b'\n' => newlines + 1,
+        _ => newlines,
+    }
+}
+
+#[test]
+fn count_lines_terminated() {
+    assert_eq!(0, count_lines(b""));
+    assert_eq!(1, count_lines(b"\n"));
+    assert_eq!(2, count_lines(b"foo\nbar\n"));
+    assert_eq!(2, count_lines(b"\n\n"));
+    assert_eq!(2, count_lines(b"foo\r\nbar\r\n"));
+}
+
+#[test]
+fn count_lines_unterminated() {
+    assert_eq!(1, count_lines(b"foo"));
+    assert_eq!(2, count_lines(b"foo\nbar"));
+    assert_eq!(3, count_lines(b"\n\nbar"));
+}
+
+// A log file has mostly short lines, with the occasional long one. The
+// haystacks are long enough that the SIMD kernels add up their per-byte
+// counters several times, including for every byte being a `\n`.
+#[test]
+fn count_lines_logs() {
+    let mut log = vec![];
+    for i in 0..2_000 {
+        let len = if i % 64 == 0 { 5_000 } else { i % 200 };
+        log.extend((0..len).map(|j| b"ab\xC3\xA9 "[j % 5]));
+        log.push(b'\n');
+    }
+    for &end in &[log.len(), log.len() - 1, 10_000, 8_191, 129] {
+        let haystack = &log[..end];
+        assert_eq!(naive_count_lines(haystack), count_lines(haystack));
+    }
+    for len in 16_000..16_200 {
+        assert_eq!(len, count_lines(&vec![b'\n'; len]));
+    }
+}
+
+quickcheck! {
+    fn qc_count_lines_matches_naive(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4 + 9).collect();
+        count_lines(&corpus) == naive_count_lines(&corpus)
+    }
+
+    fn qc_line_col_matches_naive(corpus: Vec<u8>, offset: usize) -> bool {
+        let offset = offset % (corpus.len() + 1);
+        let (mut line, mut col) = (1, 1);
+        for &b in &corpus[..offset] {
+            if b == b'\n' {
+                line += 1;
+                col = 1;
+            } else {
+                col += 1;
+            }
+        }
+        line_col(&corpus, offset) == (line, col)
+    }
+
+    fn qc_detect_line_ending_matches_naive(corpus: Vec<u8>) -> bool {
+        let mut lf = false;
+        let mut crlf = false;
+        for (i, &b) in corpus.iter().enumerate() {
+            if b == b'\n' {
+                if i > 0 && corpus[i - 1] == b'\r' {
+                    crlf = true;
+                } else {
+                    lf = true;
+                }
+            }
+        }
+        let expected = match (lf, crlf) {
+            (true, true) => LineEnding::Mixed,
+            (true, false) => LineEnding::Lf,
+            (false, true) => LineEnding::CrLf,
+            (false, false) => LineEnding::None,
+        };
+        detect_line_ending(&corpus) == expected
+    }
+
+    fn qc_nth_line_matches_lines(corpus: Vec<u8>, n: usize) -> bool {
+        let mut lines: Vec<&[u8]> = corpus.split(|&b| b == b'\n').collect();
+        if corpus.is_empty() || corpus.last() == Some(&b'\n') {
+            lines.pop();
+        }
+        let n = n % (lines.len() + 1);
+        nth_line(n, &corpus) == lines.get(n).cloned()
+    }
+}
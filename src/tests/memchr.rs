@@ -1,8 +1,23 @@
+use std::cmp;
+use std::rc::Rc;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
 use fallback;
 use naive;
-use {memchr, memchr2, memchr3, memrchr, memrchr2, memrchr3};
+use {
+    Scanner, contains_byte, contains_byte_from_end, count, first_and_last,
+    first_non_printable, is_ascii, is_subsequence, looks_binary,
+    looks_binary_default, memchr, memchr0, memchr_aligned, memchr_ct,
+    memchr_early, memchr_in, memchr_iter, memchr_masked, memchr_mut,
+    memchr_not, memchr_of_iter, memchr_or_overrun, memchr_indices,
+    memchr_positions_u32, memchr_pred, memchr_progress, memchr_ref, memchr_run,
+    memchr_strided, memchr_u16_be, memchr_u16_le, memchr_utf8_checked, memchr2,
+    memchr2_which, memchr3, memrchr, memrchr2, memrchr3, memrchr_at,
+    memrchr_from_end, memrchr_not, mismatch, next_char_boundary,
+};
 
-use tests::memchr_tests;
+use tests::{BoundaryInput, memchr_tests};
 
 #[test]
 fn memchr1_find() {
@@ -18,6 +33,894 @@ fn memchr1_fallback_find() {
     }
 }
 
+#[test]
+fn memchr1_fallback_unroll4_find() {
+    for test in memchr_tests() {
+        test.one(false, fallback::memchr_unroll4);
+    }
+}
+
+#[cfg(feature = "portable_simd")]
+#[test]
+fn memchr1_simd_find() {
+    for test in memchr_tests() {
+        test.one(false, ::simd::memchr);
+    }
+}
+
+#[test]
+fn contains_byte_every_position() {
+    // Plant a single needle at every position of haystacks of various
+    // lengths, so that each part of the vectorized routine sees a match: the
+    // unaligned head, the unrolled loop, the single vector loop and the
+    // overlapping tail.
+    for len in 0..300 {
+        let mut haystack = vec![b'a'; len];
+        assert!(!contains_byte(b'z', &haystack));
+        assert!(!fallback::contains_byte(b'z', &haystack));
+        for i in 0..len {
+            haystack[i] = b'z';
+            assert!(contains_byte(b'z', &haystack), "len {}, i {}", len, i);
+            assert!(fallback::contains_byte(b'z', &haystack));
+            assert!(contains_byte(b'z', &haystack[i..]));
+            haystack[i] = b'a';
+        }
+    }
+}
+
+#[test]
+fn memchr_mut_find() {
+    for test in memchr_tests() {
+        test.one(false, |n1, corpus| {
+            let mut corpus = corpus.to_vec();
+            memchr_mut(n1, &mut corpus).map(|(i, rest)| {
+                assert_eq!(n1, rest[0]);
+                i
+            })
+        });
+    }
+}
+
+#[test]
+fn memchr_mut_rewrites_original() {
+    let mut buf = b"a=b;c=d".to_vec();
+    {
+        let (i, rest) = memchr_mut(b';', &mut buf).unwrap();
+        assert_eq!(3, i);
+        assert_eq!(b";c=d", &*rest);
+        rest[0] = b',';
+        rest[3] = b'e';
+    }
+    assert_eq!(b"a=b,c=e", &*buf);
+    assert_eq!(None, memchr_mut(b';', &mut buf));
+    assert_eq!(None, memchr_mut(b';', &mut []));
+}
+
+#[test]
+fn memchr_progress_consumed() {
+    assert_eq!((Some(0), 1), memchr_progress(b'a', b"abc"));
+    assert_eq!((Some(2), 3), memchr_progress(b'c', b"abc"));
+    assert_eq!((None, 3), memchr_progress(b'z', b"abc"));
+    assert_eq!((None, 0), memchr_progress(b'z', b""));
+}
+
+#[test]
+fn memchr_progress_resumes() {
+    let haystack = b"a,bc,,d";
+    let (mut found, mut pos) = (vec![], 0);
+    while pos < haystack.len() {
+        let (i, consumed) = memchr_progress(b',', &haystack[pos..]);
+        if let Some(i) = i {
+            found.push(pos + i);
+        }
+        pos += consumed;
+    }
+    assert_eq!(vec![1, 4, 5], found);
+    assert_eq!(haystack.len(), pos);
+}
+
+#[test]
+fn count_every_length() {
+    // Cover every combination of the unrolled loop, the single vector loop
+    // and the partial final vector, with matches in each of them.
+    for len in 0..300 {
+        let haystack: Vec<u8> =
+            (0..len).map(|i| if i % 3 == 0 { b'a' } else { b'b' }).collect();
+        let expected = naive_count(b'a', &haystack);
+        assert_eq!(expected, count(b'a', &haystack), "len: {}", len);
+        assert_eq!(expected, fallback::count(b'a', &haystack));
+        assert_eq!(len - expected, count(b'b', &haystack));
+        assert_eq!(0, count(b'c', &haystack));
+        for start in 1..cmp::min(len, 40) {
+            let expected = naive_count(b'a', &haystack[start..]);
+            assert_eq!(expected, count(b'a', &haystack[start..]));
+        }
+    }
+}
+
+#[test]
+fn concurrent_first_use() {
+    // Routines with runtime CPU feature detection pick their implementation
+    // on first use, so call them from several threads at once to check that
+    // racing initializations are benign. This won't necessarily be the very
+    // first use, since other tests run in parallel, but the threads do start
+    // simultaneously and cover several distinct cached routines.
+    const THREADS: usize = 8;
+    let haystack: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+    let haystack = Arc::new(haystack);
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let (haystack, barrier) = (haystack.clone(), barrier.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..100 {
+                    assert_eq!(Some(250), memchr3(250, 251, 252, &haystack));
+                    assert_eq!(Some(752), memrchr2(250, 251, &haystack));
+                    assert_eq!(Some(249), memchr2(249, 250, &haystack));
+                    assert_eq!(Some(756), memrchr3(1, 2, 3, &haystack));
+                    assert_eq!(4, count(0, &haystack));
+                    assert!(contains_byte(250, &haystack));
+                    assert_eq!(Some(250), memchr(0, &haystack[1..]));
+                    assert_eq!(Some(751), memrchr(249, &haystack));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+// When AVX2 is enabled at compile time, the dispatched routines call the
+// AVX2 implementations directly, without ever running runtime detection.
+#[cfg(all(
+    target_arch = "x86_64",
+    memchr_runtime_simd,
+    memchr_runtime_avx,
+    target_feature = "avx2",
+    feature = "use_std",
+))]
+#[test]
+fn static_avx2_skips_detection() {
+    use std::sync::atomic::Ordering;
+
+    assert_eq!(Some(1), memchr(b'b', b"abc"));
+    assert_eq!(Some(2), memrchr3(b'a', b'b', b'c', b"abc"));
+    assert_eq!(1, count(b'c', b"abc"));
+    assert_eq!(0, ::x86::DETECTIONS.load(Ordering::Relaxed));
+}
+
+#[test]
+fn memchr_strided_stride_one() {
+    for test in memchr_tests() {
+        test.one(false, |n1, corpus| memchr_strided(n1, corpus, 1));
+    }
+}
+
+#[test]
+fn memchr_strided_stride_two() {
+    assert_eq!(Some(0), memchr_strided(b'a', b"abab", 2));
+    assert_eq!(None, memchr_strided(b'b', b"abab", 2));
+    assert_eq!(Some(2), memchr_strided(b'z', b"azzb", 2));
+    assert_eq!(None, memchr_strided(b'z', b"", 2));
+}
+
+#[test]
+fn memchr_strided_stride_four() {
+    let haystack = b"abcdabcdzbczabcd";
+    assert_eq!(Some(8), memchr_strided(b'z', haystack, 4));
+    assert_eq!(None, memchr_strided(b'b', haystack, 4));
+    assert_eq!(Some(0), memchr_strided(b'd', &haystack[3..], 4));
+    assert_eq!(None, memchr_strided(b'a', b"zzz", ::std::usize::MAX));
+    assert_eq!(Some(0), memchr_strided(b'z', b"zzz", ::std::usize::MAX));
+}
+
+#[test]
+#[should_panic]
+fn memchr_strided_stride_zero() {
+    memchr_strided(b'a', b"abc", 0);
+}
+
+/// The stepped loop that `memchr_strided` uses when there is no SIMD path.
+fn scalar_strided(n1: u8, haystack: &[u8], stride: usize) -> Option<usize> {
+    (0..haystack.len()).step_by(stride).find(|&i| haystack[i] == n1)
+}
+
+// The AVX2 routine gathers 8 strided bytes at a time and finishes with a
+// scalar loop, so the lengths cover haystacks that end anywhere in the
+// last gather, and a match is planted at every multiple of the stride.
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+#[test]
+fn memchr_strided_avx2_matches_scalar() {
+    use x86;
+
+    if !x86::has_avx2() {
+        return;
+    }
+    let mut stride = 2;
+    while stride <= 64 {
+        for len in 0..20 * stride {
+            let mut haystack = vec![b'a'; len];
+            let got = x86::memchr_strided_avx2(b'z', &haystack, stride);
+            assert_eq!(None, got);
+            for at in (0..len).rev() {
+                haystack[at] = b'z';
+                assert_eq!(
+                    scalar_strided(b'z', &haystack, stride),
+                    x86::memchr_strided_avx2(b'z', &haystack, stride),
+                    "stride: {}, len: {}, at: {}",
+                    stride,
+                    len,
+                    at,
+                );
+            }
+        }
+        stride *= 2;
+    }
+    let haystack = vec![b'a'; 9 * x86::STRIDED_MAX];
+    let got = x86::memchr_strided_avx2(b'a', &haystack[1..], x86::STRIDED_MAX);
+    assert_eq!(Some(0), got);
+}
+
+#[test]
+fn memchr_aligned_align_one() {
+    for test in memchr_tests() {
+        test.one(false, |n1, corpus| memchr_aligned(n1, corpus, 1));
+    }
+}
+
+#[test]
+fn memchr_aligned_align_sixteen() {
+    let mut haystack = vec![b'a'; 100];
+    assert_eq!(None, memchr_aligned(b'z', &haystack, 16));
+    haystack[3] = b'z';
+    haystack[17] = b'z';
+    haystack[48] = b'z';
+    haystack[64] = b'z';
+    assert_eq!(Some(48), memchr_aligned(b'z', &haystack, 16));
+    assert_eq!(Some(64), memchr_aligned(b'z', &haystack, 32));
+    assert_eq!(Some(3), memchr_aligned(b'z', &haystack, 1));
+    assert_eq!(Some(0), memchr_aligned(b'z', &haystack[48..], 16));
+}
+
+#[test]
+fn memchr_aligned_only_misaligned() {
+    let mut haystack = vec![b'a'; 40];
+    haystack[15] = b'z';
+    haystack[31] = b'z';
+    haystack[39] = b'z';
+    assert_eq!(None, memchr_aligned(b'z', &haystack, 16));
+    assert_eq!(None, memchr_aligned(b'z', &haystack[..31], 16));
+    assert_eq!(None, memchr_aligned(b'z', b"", 16));
+}
+
+#[test]
+#[should_panic]
+fn memchr_aligned_align_zero() {
+    memchr_aligned(b'a', b"abc", 0);
+}
+
+#[test]
+#[should_panic]
+fn memchr_aligned_align_not_power_of_two() {
+    memchr_aligned(b'a', b"abc", 12);
+}
+
+#[test]
+fn memchr0_find() {
+    for test in memchr_tests() {
+        if test.needles != b"\x00" {
+            continue;
+        }
+        test.one(false, |_, corpus| memchr0(corpus));
+    }
+}
+
+#[test]
+fn memchr0_fallback_find() {
+    for test in memchr_tests() {
+        if test.needles != b"\x00" {
+            continue;
+        }
+        test.one(false, |_, corpus| fallback::memchr0(corpus));
+    }
+}
+
+#[test]
+fn memchr0_embedded_nul() {
+    assert_eq!(Some(3), memchr0(b"foo\x00bar\x00"));
+    assert_eq!(Some(3), fallback::memchr0(b"foo\x00bar\x00"));
+    assert_eq!(Some(0), memchr0(b"\x00"));
+}
+
+#[test]
+fn memchr0_no_nul() {
+    let haystack = vec![b'a'; 100];
+    assert_eq!(None, memchr0(&haystack));
+    assert_eq!(None, fallback::memchr0(&haystack));
+    assert_eq!(None, memchr0(b""));
+}
+
+#[test]
+fn memchr_masked_full_mask_find() {
+    for test in memchr_tests() {
+        test.one(false, |n1, corpus| memchr_masked(n1, 0xFF, corpus));
+    }
+}
+
+#[test]
+fn memchr_masked_case_insensitive() {
+    assert_eq!(Some(3), memchr_masked(b'q', 0xDF, b"THEQUICK"));
+    assert_eq!(Some(3), memchr_masked(b'Q', 0xDF, b"thequick"));
+    assert_eq!(None, memchr_masked(b'z', 0xDF, b"thequick"));
+
+    let mut haystack = vec![b'x'; 100];
+    haystack[77] = b'Z';
+    assert_eq!(Some(77), memchr_masked(b'z', 0xDF, &haystack));
+}
+
+#[test]
+fn memchr_masked_empty_mask() {
+    assert_eq!(Some(0), memchr_masked(b'a', 0x00, b"xyz"));
+    assert_eq!(None, memchr_masked(b'a', 0x00, b""));
+}
+
+#[test]
+fn looks_binary_text() {
+    let text = b"the quick brown fox\njumps over the lazy dog\n".repeat(500);
+    assert!(!looks_binary(&text, 100));
+    assert!(!looks_binary(&text, usize::MAX));
+    assert!(!looks_binary_default(&text));
+    assert!(!looks_binary(b"", 10));
+    assert!(!looks_binary_default(b""));
+}
+
+#[test]
+fn looks_binary_nul_in_prefix() {
+    let mut haystack = vec![b'a'; 10_000];
+    haystack[8191] = 0;
+    assert!(looks_binary(&haystack, 8192));
+    assert!(looks_binary(&haystack, usize::MAX));
+    assert!(looks_binary_default(&haystack));
+    assert!(looks_binary(b"\x00", 1));
+}
+
+#[test]
+fn looks_binary_nul_after_prefix() {
+    let mut haystack = vec![b'a'; 10_000];
+    haystack[8192] = 0;
+    assert!(!looks_binary(&haystack, 8192));
+    assert!(!looks_binary_default(&haystack));
+    assert!(looks_binary(&haystack, 8193));
+    assert!(!looks_binary(b"\x00", 0));
+}
+
+fn naive_memchr_u16(unit: [u8; 2], haystack: &[u8]) -> Option<usize> {
+    haystack.chunks_exact(2).position(|pair| pair == unit).map(|i| 2 * i)
+}
+
+#[test]
+fn memchr_u16_byte_order() {
+    let haystack = b"\x01\x02\x02\x01";
+    assert_eq!(Some(0), memchr_u16_le(0x0201, haystack));
+    assert_eq!(Some(2), memchr_u16_be(0x0201, haystack));
+    assert_eq!(Some(2), memchr_u16_le(0x0102, haystack));
+    assert_eq!(Some(0), memchr_u16_be(0x0102, haystack));
+    // A unit that only occurs across a unit boundary isn't found.
+    assert_eq!(None, memchr_u16_le(0x0202, haystack));
+    assert_eq!(None, memchr_u16_be(0x0202, haystack));
+}
+
+#[test]
+fn memchr_u16_odd_length() {
+    assert_eq!(None, memchr_u16_le(0x0061, b"a"));
+    assert_eq!(None, memchr_u16_le(0x0061, b""));
+    assert_eq!(Some(0), memchr_u16_le(0x0061, b"a\x00b"));
+    // The trailing byte and the byte after it would match, but there is no
+    // byte after it.
+    assert_eq!(None, memchr_u16_be(0x6200, b"\x00a\x00b"));
+    assert_eq!(None, memchr_u16_be(0x0062, b"\x00a\x00"));
+    let mut haystack = vec![0xFF; 101];
+    haystack[100] = 0x00;
+    assert_eq!(None, memchr_u16_le(0x00FF, &haystack));
+    assert_eq!(Some(0), memchr_u16_le(0xFFFF, &haystack));
+}
+
+#[test]
+fn memchr_u16_every_position() {
+    for len in 0..70 {
+        let haystack = vec![0xAB; len];
+        for i in (0..len.saturating_sub(1)).step_by(2) {
+            let mut haystack = haystack.clone();
+            haystack[i] = 0x12;
+            haystack[i + 1] = 0x34;
+            assert_eq!(Some(i), memchr_u16_le(0x3412, &haystack), "{}", i);
+            assert_eq!(Some(i), memchr_u16_be(0x1234, &haystack), "{}", i);
+            assert_eq!(None, memchr_u16_be(0x3412, &haystack), "{}", i);
+            if i >= 2 {
+                // The unit at an odd offset must never match.
+                assert_eq!(
+                    naive_memchr_u16([0xAB, 0x12], &haystack),
+                    memchr_u16_le(0x12AB, &haystack),
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn first_non_printable_range_ends() {
+    for &(b, printable) in &[
+        (0x00, false),
+        (0x1F, false),
+        (0x20, true),
+        (0x7E, true),
+        (0x7F, false),
+        (0x80, false),
+        (0xFF, false),
+    ] {
+        for len in 0..40 {
+            for i in 0..len {
+                let mut haystack = vec![b'a'; len];
+                haystack[i] = b;
+                let expected = if printable { None } else { Some(i) };
+                assert_eq!(
+                    expected,
+                    first_non_printable(&haystack),
+                    "byte: {:X}, len: {}, i: {}",
+                    b,
+                    len,
+                    i,
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn is_ascii_alignments() {
+    let mut haystack = vec![b'a'; 100];
+    assert!(is_ascii(&haystack));
+    for i in 0..haystack.len() {
+        haystack[i] = 0x80;
+        for align in 0..haystack.len() {
+            assert_eq!(align > i, is_ascii(&haystack[align..]));
+        }
+        haystack[i] = b'a';
+    }
+    assert!(is_ascii(b""));
+}
+
+#[test]
+fn next_char_boundary_multibyte() {
+    // é is 2 bytes, € is 3 bytes and 𐍈 is 4 bytes.
+    let haystack = "é€𐍈a".as_bytes();
+    let starts = [0, 2, 5, 9];
+    for from in 0..haystack.len() {
+        let expected = starts.iter().cloned().find(|&s| s >= from);
+        assert_eq!(expected, next_char_boundary(haystack, from));
+    }
+    assert_eq!(None, next_char_boundary(haystack, haystack.len()));
+    assert_eq!(None, next_char_boundary(haystack, haystack.len() + 1));
+}
+
+#[test]
+fn next_char_boundary_long_continuation_run() {
+    let mut haystack = vec![0x80; 100];
+    assert_eq!(None, next_char_boundary(&haystack, 0));
+    for i in 0..haystack.len() {
+        haystack[i] = b'a';
+        for from in 0..haystack.len() {
+            let expected = if from <= i { Some(i) } else { None };
+            assert_eq!(expected, next_char_boundary(&haystack, from));
+        }
+        haystack[i] = 0xBF;
+    }
+}
+
+#[test]
+fn memchr_in_smart_pointers() {
+    let bytes = &b"the quick brown fox"[..];
+    let boxed: Box<[u8]> = Box::from(bytes);
+    let rc: Rc<[u8]> = Rc::from(bytes);
+    let arc: Arc<[u8]> = Arc::from(bytes);
+    assert_eq!(Some(8), memchr_in(b'k', &boxed));
+    assert_eq!(Some(8), memchr_in(b'k', &rc));
+    assert_eq!(Some(8), memchr_in(b'k', &arc));
+    assert_eq!(None, memchr_in(b'z', &arc));
+    // The searches borrow the buffers, so nothing was cloned.
+    assert_eq!(1, Rc::strong_count(&rc));
+    assert_eq!(1, Arc::strong_count(&arc));
+}
+
+#[test]
+fn memchr_in_owned_and_borrowed() {
+    assert_eq!(Some(8), memchr_in(b'k', &b"the quick brown fox".to_vec()));
+    assert_eq!(Some(8), memchr_in(b'k', &"the quick brown fox".to_string()));
+    assert_eq!(Some(8), memchr_in(b'k', "the quick brown fox"));
+    assert_eq!(Some(8), memchr_in(b'k', &b"the quick brown fox"[..]));
+    assert_eq!(Some(1), memchr_in(b'b', b"abc"));
+    assert_eq!(None, memchr_in(b'a', ""));
+}
+
+#[test]
+fn memchr_utf8_checked_valid() {
+    let haystack = "é€𐍈=𐍈€é".as_bytes();
+    assert_eq!(Ok(Some(9)), memchr_utf8_checked(b'=', haystack));
+    assert_eq!(Ok(None), memchr_utf8_checked(b';', haystack));
+    assert_eq!(Ok(Some(0)), memchr_utf8_checked(b'=', b"=\xFF"));
+    assert_eq!(Ok(None), memchr_utf8_checked(b'=', b""));
+}
+
+#[test]
+fn memchr_utf8_checked_invalid_prefix() {
+    // A lone continuation byte, a truncated sequence and an overlong
+    // encoding, each before the delimiter.
+    let cases: &[(&[u8], usize)] = &[
+        (b"ab\x80=cd", 2),
+        (b"\xC3=\xA9", 0),
+        (b"a\xE2\x82=", 1),
+        (b"abc\xC0\xAF=", 3),
+    ];
+    for &(haystack, valid_up_to) in cases {
+        let err = memchr_utf8_checked(b'=', haystack).unwrap_err();
+        assert_eq!(valid_up_to, err.valid_up_to(), "{:?}", haystack);
+    }
+    // Without a delimiter, the entire haystack is validated.
+    let err = memchr_utf8_checked(b'=', b"abc\xFF").unwrap_err();
+    assert_eq!(3, err.valid_up_to());
+}
+
+#[test]
+#[should_panic]
+fn memchr_utf8_checked_non_ascii_needle() {
+    let _ = memchr_utf8_checked(0xC3, "é".as_bytes());
+}
+
+// The fallback splits the haystack into an unaligned head, aligned words and
+// an unaligned tail. This covers every split that a haystack of up to a few
+// loop iterations can have, with zero, one or two matches in every place,
+// against the byte at a time implementation.
+#[test]
+fn fallback_memchr_boundary_lengths() {
+    for align in 0..16 {
+        for len in 0..100 {
+            let mut buf = vec![0u8; align + len];
+            for i in 0..len + 1 {
+                for j in i..len + 1 {
+                    let h = &mut buf[align..];
+                    h.iter_mut().for_each(|b| *b = 0);
+                    if i < len {
+                        h[i] = 1;
+                    }
+                    if j < len {
+                        h[j] = 1;
+                    }
+                    assert_eq!(naive::memchr(1, h), fallback::memchr(1, h));
+                    assert_eq!(naive::memrchr(1, h), fallback::memrchr(1, h));
+                }
+            }
+        }
+    }
+}
+
+// This checks that indices beyond `u32::MAX` survive every layer between
+// the public API and the kernels, including the libc call. The buffer is
+// allocated zeroed, so only the pages that are written to are committed, but
+// it still needs more than 4 GiB of address space and is slow to search, so
+// this only runs with `cargo test -- --ignored`.
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[ignore]
+fn memchr_beyond_4gib() {
+    let at = (1usize << 32) + 17;
+    let mut haystack = vec![0u8; at + 100];
+    haystack[at] = b'a';
+
+    assert_eq!(Some(at), memchr(b'a', &haystack));
+    assert_eq!(Some(at), memrchr(b'a', &haystack));
+    assert_eq!(Some(at), fallback::memchr(b'a', &haystack));
+    assert_eq!(Some(at), fallback::memrchr(b'a', &haystack));
+    assert_eq!(Some(at), memchr2(b'a', b'b', &haystack));
+    assert_eq!(Some(at), memrchr3(b'a', b'b', b'c', &haystack));
+    assert_eq!(1, count(b'a', &haystack));
+    #[cfg(all(
+        feature = "libc",
+        not(target_arch = "wasm32"),
+        not(target_env = "sgx"),
+    ))]
+    assert_eq!(Some(at), ::c::memchr(b'a', &haystack));
+}
+
+// Haystacks this large are searched with AVX-512 when it's available.
+#[test]
+fn memchr1_large_haystack() {
+    let len = (1 << 17) + 37;
+    let mut haystack = vec![b'z'; len];
+    assert_eq!(None, memchr(b'a', &haystack));
+    for &i in &[0, 1, 63, 64, 255, 256, 70_000, len - 64, len - 37, len - 1] {
+        haystack[i] = b'a';
+        for align in 0..3 {
+            let expected = if i >= align { Some(i - align) } else { None };
+            assert_eq!(expected, memchr(b'a', &haystack[align..]));
+        }
+        haystack[i] = b'z';
+    }
+}
+
+#[test]
+fn memchr_not_long_runs() {
+    // Cover every length and alignment that the unrolled loop, the single
+    // vector loop and the overlapping tail can see, with the differing byte
+    // at every position.
+    let mut haystack = vec![0; 300];
+    for align in 0..65 {
+        for len in 0..(haystack.len() - align) {
+            let run = &haystack[align..align + len];
+            assert_eq!(None, memchr_not(0, run));
+            assert_eq!(None, fallback::memchr_not(0, run));
+        }
+    }
+    for i in 0..haystack.len() {
+        haystack[i] = b'x';
+        for align in 0..cmp::min(i + 1, 65) {
+            let run = &haystack[align..];
+            assert_eq!(Some(i - align), memchr_not(0, run));
+            assert_eq!(Some(i - align), fallback::memchr_not(0, run));
+        }
+        haystack[i] = 0;
+    }
+}
+
+#[test]
+fn memrchr_not_long_runs() {
+    let mut haystack = vec![0; 300];
+    for align in 0..65 {
+        for len in 0..(haystack.len() - align) {
+            assert_eq!(None, memrchr_not(0, &haystack[align..align + len]));
+        }
+    }
+    for i in 0..haystack.len() {
+        haystack[i] = b'x';
+        for end in i + 1..cmp::min(i + 66, haystack.len() + 1) {
+            assert_eq!(Some(i), memrchr_not(0, &haystack[..end]));
+        }
+        haystack[i] = 0;
+    }
+}
+
+#[test]
+fn memchr_not_all_needles() {
+    for n1 in 0..256 {
+        let n1 = n1 as u8;
+        let mut haystack = vec![n1; 100];
+        assert_eq!(None, memchr_not(n1, &haystack));
+        haystack[77] = n1.wrapping_add(1);
+        assert_eq!(Some(77), memchr_not(n1, &haystack));
+        haystack[77] = n1 ^ 0x80;
+        assert_eq!(Some(77), memchr_not(n1, &haystack));
+    }
+}
+
+// Matches bytes in either 0x80..=0x8F or 0xF0..=0xFF.
+fn in_ranges(b: u8) -> bool {
+    b & 0xF0 == 0x80 || b >= 0xF0
+}
+
+#[test]
+fn memchr_pred_ranges() {
+    let coarse = |word: usize| word & fallback::HI_USIZE != 0;
+    let mut haystack = vec![b'a'; 100];
+    assert_eq!(None, memchr_pred(coarse, in_ranges, &haystack));
+    for &b in &[0x80, 0x8F, 0xF0, 0xFF] {
+        for i in 0..haystack.len() {
+            // Bytes with their high bit set that aren't in the ranges make
+            // the coarse test pass without being a match.
+            haystack[0] = 0xC0;
+            haystack[i] = b;
+            for align in 0..cmp::min(i + 1, 10) {
+                let got = memchr_pred(coarse, in_ranges, &haystack[align..]);
+                assert_eq!(Some(i - align), got);
+            }
+            haystack[i] = b'a';
+        }
+    }
+}
+
+#[test]
+fn memchr_pred_coarse_skips_words() {
+    // A coarse test that rejects everything means only the bytes at the end
+    // that don't fill a word are ever checked.
+    let haystack = [0xFF; 20];
+    let word = ::std::mem::size_of::<usize>();
+    let tail = haystack.len() - haystack.len() % word;
+    assert_eq!(Some(tail), memchr_pred(|_| false, in_ranges, &haystack));
+    assert_eq!(None, memchr_pred(|_| false, in_ranges, &haystack[..tail]));
+    assert_eq!(None, memchr_pred(|_| true, in_ranges, b""));
+}
+
+#[test]
+fn memchr_run_lengths() {
+    assert_eq!(Some((2, 3)), memchr_run(b'a', b"xxaaax"));
+    assert_eq!(Some((2, 1)), memchr_run(b'a', b"xxaxaa"));
+    assert_eq!(Some((0, 2)), memchr_run(b'a', b"aa"));
+    assert_eq!(None, memchr_run(b'a', b"xxx"));
+    assert_eq!(None, memchr_run(b'a', b""));
+
+    let mut haystack = vec![b'a'; 300];
+    haystack[0] = b'x';
+    haystack[250] = b'x';
+    assert_eq!(Some((1, 249)), memchr_run(b'a', &haystack));
+}
+
+#[test]
+fn first_and_last_single_occurrence() {
+    assert_eq!(Some((0, 0)), first_and_last(b'a', b"a"));
+    assert_eq!(Some((2, 2)), first_and_last(b'a', b"xxaxx"));
+    assert_eq!(None, first_and_last(b'a', b"xxxxx"));
+    assert_eq!(None, first_and_last(b'a', b""));
+}
+
+#[test]
+fn first_and_last_distinct() {
+    assert_eq!(Some((0, 1)), first_and_last(b'a', b"aa"));
+    assert_eq!(Some((1, 5)), first_and_last(b'a', b"xaxaxax"));
+
+    let mut haystack = vec![b'x'; 300];
+    haystack[7] = b'a';
+    haystack[290] = b'a';
+    assert_eq!(Some((7, 290)), first_and_last(b'a', &haystack));
+}
+
+#[test]
+fn memchr_ct_find() {
+    for test in memchr_tests() {
+        test.one(false, memchr_ct);
+    }
+}
+
+#[test]
+fn memchr_ct_every_byte() {
+    let haystack: Vec<u8> = (0..256).map(|b| b as u8).collect();
+    for b in 0..256 {
+        assert_eq!(Some(b), memchr_ct(b as u8, &haystack));
+    }
+    assert_eq!(Some(0), memchr_ct(0, &[0; 100]));
+    assert_eq!(Some(99), memchr_ct(0, &[&[1; 99][..], &[0; 10]].concat()));
+    assert_eq!(None, memchr_ct(0, &[0xFF; 100]));
+}
+
+#[test]
+fn memchr_early_find() {
+    for test in memchr_tests() {
+        test.one(false, memchr_early);
+    }
+}
+
+#[test]
+fn memchr_early_around_probe() {
+    // Cover matches inside the probe, right after it and far beyond it.
+    for len in 0..100 {
+        let mut haystack = vec![0; len];
+        assert_eq!(None, memchr_early(1, &haystack));
+        for i in 0..len {
+            haystack[i] = 1;
+            assert_eq!(Some(i), memchr_early(1, &haystack));
+            haystack[i] = 0;
+        }
+    }
+}
+
+#[cfg(memchr_maybe_uninit)]
+#[test]
+fn memchr_maybe_uninit_initialized_prefix() {
+    use std::mem::MaybeUninit;
+
+    use memchr_maybe_uninit;
+
+    let bytes: Vec<u8> = (0..200).map(|i| (i % 7) as u8).collect();
+    let mut buf = vec![MaybeUninit::<u8>::uninit(); 300];
+    for (dst, &src) in buf.iter_mut().zip(&bytes) {
+        *dst = MaybeUninit::new(src);
+    }
+    for init_len in 0..bytes.len() + 1 {
+        for n1 in 0..8 {
+            let expected = memchr(n1, &bytes[..init_len]);
+            let got = unsafe { memchr_maybe_uninit(n1, &buf, init_len) };
+            assert_eq!(expected, got);
+        }
+    }
+}
+
+#[cfg(memchr_maybe_uninit)]
+#[test]
+#[should_panic]
+fn memchr_maybe_uninit_init_len_too_long() {
+    use std::mem::MaybeUninit;
+
+    use memchr_maybe_uninit;
+
+    let buf = [MaybeUninit::new(0u8); 4];
+    unsafe {
+        memchr_maybe_uninit(0, &buf, 5);
+    }
+}
+
+#[test]
+fn memchr_of_iter_find() {
+    for test in memchr_tests() {
+        test.one(false, |n1, corpus| {
+            memchr_of_iter(n1, corpus.iter().cloned())
+        });
+    }
+}
+
+#[test]
+fn memchr_of_iter_every_position() {
+    for len in 0..40 {
+        let mut haystack = vec![0; len];
+        assert_eq!(None, memchr_of_iter(1, haystack.iter().cloned()));
+        for i in 0..len {
+            haystack[i] = 1;
+            let got = memchr_of_iter(1, haystack.iter().cloned());
+            assert_eq!(Some(i), got);
+            haystack[i] = 0;
+        }
+    }
+}
+
+#[test]
+fn is_subsequence_present() {
+    assert!(is_subsequence(b"abc", b"axbyc"));
+    assert!(is_subsequence(b"abc", b"abc"));
+    assert!(is_subsequence(b"aa", b"xaxa"));
+    assert!(is_subsequence(b"mcr", b"src/memchr.rs"));
+}
+
+#[test]
+fn is_subsequence_absent() {
+    assert!(!is_subsequence(b"acb", b"axbyc"));
+    assert!(!is_subsequence(b"aa", b"xax"));
+    assert!(!is_subsequence(b"abd", b"axbyc"));
+    assert!(!is_subsequence(b"a", b""));
+}
+
+#[test]
+fn is_subsequence_empty_needle() {
+    assert!(is_subsequence(b"", b""));
+    assert!(is_subsequence(b"", b"abc"));
+}
+
+#[test]
+fn is_subsequence_needle_longer_than_haystack() {
+    assert!(!is_subsequence(b"abcd", b"abc"));
+    assert!(!is_subsequence(b"aaaa", b"aaa"));
+}
+
+#[test]
+fn mismatch_equal_lengths() {
+    assert_eq!(None, mismatch(b"", b""));
+    assert_eq!(None, mismatch(b"abc", b"abc"));
+    assert_eq!(Some(0), mismatch(b"abc", b"xbc"));
+    assert_eq!(Some(2), mismatch(b"abc", b"abx"));
+    let a = vec![7; 300];
+    for i in 0..a.len() {
+        let mut b = a.clone();
+        b[i] = 8;
+        assert_eq!(Some(i), mismatch(&a, &b));
+        assert_eq!(Some(i), mismatch(&b, &a));
+    }
+}
+
+#[test]
+fn mismatch_unequal_lengths() {
+    assert_eq!(Some(0), mismatch(b"", b"a"));
+    assert_eq!(Some(3), mismatch(b"abc", b"abcdef"));
+    assert_eq!(Some(3), mismatch(b"abcdef", b"abc"));
+    assert_eq!(Some(1), mismatch(b"abcdef", b"axc"));
+}
+
 #[test]
 fn memchr2_find() {
     for test in memchr_tests() {
@@ -32,6 +935,26 @@ fn memchr2_fallback_find() {
     }
 }
 
+#[test]
+fn memchr2_which_first_needle() {
+    assert_eq!(Some((1, b'(')), memchr2_which(b'(', b')', b"f(x)"));
+    assert_eq!(Some((0, b'(')), memchr2_which(b'(', b')', b"()"));
+}
+
+#[test]
+fn memchr2_which_second_needle() {
+    assert_eq!(Some((1, b')')), memchr2_which(b'(', b')', b"x)(("));
+    assert_eq!(Some((0, b')')), memchr2_which(b'(', b')', b")("));
+    assert_eq!(None, memchr2_which(b'(', b')', b"xyz"));
+    assert_eq!(None, memchr2_which(b'(', b')', b""));
+}
+
+#[test]
+fn memchr2_which_equal_needles() {
+    assert_eq!(Some((2, b'a')), memchr2_which(b'a', b'a', b"xya"));
+    assert_eq!(None, memchr2_which(b'a', b'a', b"xyz"));
+}
+
 #[test]
 fn memchr3_find() {
     for test in memchr_tests() {
@@ -60,6 +983,215 @@ fn memrchr1_fallback_find() {
     }
 }
 
+#[test]
+fn memrchr_from_end_last_byte() {
+    assert_eq!(Some(0), memrchr_from_end(b'a', b"a"));
+    assert_eq!(Some(0), memrchr_from_end(b'a', b"zzzza"));
+    assert_eq!(Some(0), memrchr_from_end(b'a', b"azaza"));
+}
+
+#[test]
+fn memrchr_from_end_first_byte() {
+    assert_eq!(Some(4), memrchr_from_end(b'a', b"azzzz"));
+
+    let mut haystack = vec![b'z'; 100];
+    haystack[0] = b'a';
+    assert_eq!(Some(99), memrchr_from_end(b'a', &haystack));
+}
+
+#[test]
+fn memrchr_from_end_not_found() {
+    assert_eq!(None, memrchr_from_end(b'a', b""));
+    assert_eq!(None, memrchr_from_end(b'a', b"zzzz"));
+}
+
+#[test]
+fn memchr_or_overrun_within_limit() {
+    assert_eq!(Ok(0), memchr_or_overrun(b'a', b"abc", 1));
+    assert_eq!(Ok(4), memchr_or_overrun(b'\n', b"abcd\nefg", 5));
+    assert_eq!(Ok(4), memchr_or_overrun(b'\n', b"abcd\nefg", 100));
+}
+
+#[test]
+fn memchr_or_overrun_beyond_limit() {
+    assert_eq!(Err(4), memchr_or_overrun(b'\n', b"abcd\nefg", 4));
+    assert_eq!(Err(0), memchr_or_overrun(b'a', b"abc", 0));
+    let mut haystack = vec![0; 1000];
+    haystack[900] = 1;
+    assert_eq!(Err(900), memchr_or_overrun(1, &haystack, 900));
+    assert_eq!(Ok(900), memchr_or_overrun(1, &haystack, 901));
+}
+
+#[test]
+fn memchr_or_overrun_absent() {
+    assert_eq!(Err(0), memchr_or_overrun(b'a', b"", 10));
+    assert_eq!(Err(3), memchr_or_overrun(b'z', b"abc", 3));
+    assert_eq!(Err(3), memchr_or_overrun(b'z', b"abc", ::std::usize::MAX));
+}
+
+#[test]
+fn contains_byte_from_end_every_position() {
+    for len in 0..130 {
+        let mut haystack = vec![b'a'; len];
+        assert!(!contains_byte_from_end(b'z', &haystack));
+        for i in 0..len {
+            haystack[i] = b'z';
+            assert_eq!(
+                contains_byte(b'z', &haystack[..i]),
+                contains_byte_from_end(b'z', &haystack[..i]),
+            );
+            assert!(contains_byte_from_end(b'z', &haystack[i..]));
+            haystack[i] = b'a';
+        }
+    }
+}
+
+#[test]
+fn memchr_positions_u32_matches_iter() {
+    for test in memchr_tests() {
+        test.iter_one(false, |n1, corpus| {
+            memchr_positions_u32(n1, corpus).into_iter().map(|i| i as usize)
+        });
+    }
+}
+
+#[test]
+fn memchr_indices_u64() {
+    for test in memchr_tests() {
+        test.iter_one(false, |n1, corpus| {
+            let mut indices: Vec<u64> = vec![];
+            memchr_indices(n1, corpus, &mut indices);
+            indices.into_iter().map(|i| i as usize)
+        });
+    }
+}
+
+#[test]
+fn memchr_indices_appends() {
+    let mut indices: Vec<u32> = vec![7];
+    memchr_indices(b'a', b"banana", &mut indices);
+    assert_eq!(vec![7, 1, 3, 5], indices);
+    memchr_indices(b'z', b"banana", &mut indices);
+    assert_eq!(vec![7, 1, 3, 5], indices);
+}
+
+#[test]
+fn memchr_indices_overflow() {
+    let mut haystack = vec![b'.'; 300];
+    haystack[255] = b'a';
+    let mut indices: Vec<u8> = vec![];
+    memchr_indices(b'a', &haystack, &mut indices);
+    assert_eq!(vec![255], indices);
+
+    haystack[256] = b'a';
+    let mut indices: Vec<u8> = vec![];
+    let result = ::std::panic::catch_unwind(move || {
+        memchr_indices(b'a', &haystack, &mut indices);
+    });
+    assert!(result.is_err());
+}
+
+// Only the index of a match needs to fit in a `u32`, not the length of the
+// haystack, so this allocates a zeroed buffer just over 4 GiB. Zeroed
+// allocations are normally backed by pages that are only touched when
+// written, so this doesn't use much memory, but it still needs the address
+// space and is slow to search, so this only runs with
+// `cargo test -- --ignored`.
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[ignore]
+fn memchr_positions_u32_bound() {
+    let limit = u32::MAX as usize;
+    let mut haystack = vec![0u8; limit + 2];
+    haystack[limit] = 1;
+    assert_eq!(vec![u32::MAX], memchr_positions_u32(1, &haystack));
+    haystack[limit + 1] = 1;
+    let result = ::std::panic::catch_unwind(|| {
+        memchr_positions_u32(1, &haystack)
+    });
+    assert!(result.is_err());
+}
+
+// The same bound as above, for `u32` indices appended by `memchr_indices`.
+// This needs just as much address space, so it only runs with
+// `cargo test -- --ignored` too.
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[ignore]
+fn memchr_indices_u32_bound() {
+    let limit = u32::MAX as usize;
+    let mut haystack = vec![0u8; limit + 2];
+    haystack[limit] = 1;
+    let mut indices: Vec<u32> = vec![];
+    memchr_indices(1, &haystack, &mut indices);
+    assert_eq!(vec![u32::MAX], indices);
+    haystack[limit + 1] = 1;
+    let result = ::std::panic::catch_unwind(move || {
+        let mut indices: Vec<u32> = vec![];
+        memchr_indices(1, &haystack, &mut indices);
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn memchr_ref_points_into_haystack() {
+    let haystack = b"the quick brown fox";
+    let found = memchr_ref(b'o', haystack).unwrap();
+    let idx = memchr(b'o', haystack).unwrap();
+    assert_eq!(b'o', *found);
+    assert_eq!(haystack.as_ptr() as usize + idx, found as *const u8 as usize);
+    assert_eq!(None, memchr_ref(b'z', haystack));
+    assert_eq!(None, memchr_ref(b'z', b""));
+}
+
+#[test]
+fn memrchr_at_start() {
+    assert_eq!(Some(0), memrchr_at(b'a', b"abca", 0));
+    assert_eq!(None, memrchr_at(b'b', b"abca", 0));
+}
+
+#[test]
+fn memrchr_at_last_index() {
+    assert_eq!(Some(3), memrchr_at(b'a', b"abca", 3));
+    assert_eq!(Some(0), memrchr_at(b'a', b"abca", 2));
+    assert_eq!(Some(1), memrchr_at(b'b', b"abca", 3));
+}
+
+#[test]
+fn memrchr_at_beyond_len() {
+    assert_eq!(Some(3), memrchr_at(b'a', b"abca", 4));
+    assert_eq!(Some(3), memrchr_at(b'a', b"abca", ::std::usize::MAX));
+    assert_eq!(None, memrchr_at(b'z', b"abca", 100));
+    assert_eq!(None, memrchr_at(b'a', b"", 0));
+    assert_eq!(None, memrchr_at(b'a', b"", 100));
+}
+
+// Every routine that takes an index clamps it when it is out of range,
+// rather than panicking, as described in the crate documentation.
+#[test]
+fn out_of_range_indices_are_clamped() {
+    let haystack = "aé,b".as_bytes();
+    let len = haystack.len();
+    let scanner = Scanner::new(haystack);
+    for &oob in &[len, len + 1, len + 100, ::std::usize::MAX] {
+        // A forward search starting at `oob` starts at the end.
+        assert_eq!(None, scanner.find_from(b'b', oob));
+        assert_eq!(None, next_char_boundary(haystack, oob));
+        // A reverse search ending at `oob` ends at the last byte.
+        assert_eq!(
+            memrchr_at(b'a', haystack, len - 1),
+            memrchr_at(b'a', haystack, oob)
+        );
+        assert_eq!(memrchr(b'b', haystack), memrchr_at(b'b', haystack, oob));
+    }
+    for &at in &[0, 1, 100, ::std::usize::MAX] {
+        assert_eq!(None, Scanner::new(b"").find_from(b'a', at));
+        assert_eq!(None, next_char_boundary(b"", at));
+        assert_eq!(None, memrchr_at(b'a', b"", at));
+        assert_eq!(Err(0), memchr_or_overrun(b'a', b"", at));
+    }
+}
+
 #[test]
 fn memrchr2_find() {
     for test in memchr_tests() {
@@ -88,10 +1220,302 @@ fn memrchr3_fallback_find() {
     }
 }
 
+// The fallback routines walk raw pointers from both ends of the haystack, so
+// run them over every window of a buffer to exercise each combination of
+// start alignment and end offset, with a needle at either edge.
+#[test]
+fn fallback_pointer_walks_stay_in_bounds() {
+    let buf: Vec<u8> = (0..80).map(|i| b'a' + (i % 7) as u8).collect();
+    for start in 0..buf.len() {
+        for end in start..buf.len() + 1 {
+            let window = &buf[start..end];
+            let n1 = window.first().map_or(b'z', |&b| b);
+            let n2 = window.last().map_or(b'z', |&b| b);
+            let fwd = window.iter().position(|&b| b == n2);
+            let rev = window.iter().rposition(|&b| b == n1);
+            assert_eq!(fwd, fallback::memchr(n2, window));
+            assert_eq!(rev, fallback::memrchr(n1, window));
+            assert_eq!(fwd, fallback::memchr2(n2, b'z', window));
+            assert_eq!(rev, fallback::memrchr2(n1, b'z', window));
+            assert_eq!(fwd, fallback::memchr3(n2, b'z', b'y', window));
+            assert_eq!(rev, fallback::memrchr3(n1, b'z', b'y', window));
+        }
+    }
+}
+
+fn naive_count(n1: u8, haystack: &[u8]) -> usize {
+    haystack.iter().filter(|&&b| b == n1).count()
+}
+
 quickcheck! {
+    fn qc_memchr_ref_matches_memchr(needle: u8, corpus: Vec<u8>) -> bool {
+        let found = memchr_ref(needle, &corpus).map(|b| b as *const u8);
+        let expected =
+            memchr(needle, &corpus).map(|i| corpus[i..].as_ptr());
+        found == expected
+    }
+
+    fn qc_contains_byte_matches_naive(n1: u8, corpus: Vec<u8>) -> bool {
+        contains_byte(n1, &corpus) == naive::memchr(n1, &corpus).is_some()
+    }
+
+    fn qc_memchr_positions_u32_matches_iter(n1: u8, corpus: Vec<u8>) -> bool {
+        let expected: Vec<u32> =
+            memchr_iter(n1, &corpus).map(|i| i as u32).collect();
+        memchr_positions_u32(n1, &corpus) == expected
+    }
+
+    fn qc_contains_byte_from_end_matches_contains_byte(
+        n1: u8, corpus: Vec<u8>
+    ) -> bool {
+        contains_byte_from_end(n1, &corpus) == contains_byte(n1, &corpus)
+    }
+
     fn qc_memchr1_matches_naive(n1: u8, corpus: Vec<u8>) -> bool {
         memchr(n1, &corpus) == naive::memchr(n1, &corpus)
     }
+
+    fn qc_count_matches_naive(n1: u8, corpus: Vec<u8>) -> bool {
+        let expected = naive_count(n1, &corpus);
+        count(n1, &corpus) == expected
+            && fallback::count(n1, &corpus) == expected
+    }
+
+    fn qc_count_boundary_matches_naive(input: BoundaryInput) -> bool {
+        let (n1, corpus) = (input.needle, input.haystack());
+        let expected = naive_count(n1, corpus);
+        count(n1, corpus) == expected
+            && fallback::count(n1, corpus) == expected
+    }
+
+    fn qc_memchr_strided_matches_naive(
+        n1: u8, corpus: Vec<u8>, stride: usize
+    ) -> bool {
+        let stride = stride % 8 + 1;
+        let expected = corpus
+            .iter()
+            .enumerate()
+            .position(|(i, &b)| i % stride == 0 && b == n1);
+        memchr_strided(n1, &corpus, stride) == expected
+    }
+
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    fn qc_memchr_strided_avx2_matches_scalar(
+        n1: u8, corpus: Vec<u8>, stride: u8
+    ) -> bool {
+        use x86;
+
+        let stride = 1 << (stride % 6 + 1);
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let n1 = n1 % 4;
+        if !x86::has_avx2() {
+            return true;
+        }
+        x86::memchr_strided_avx2(n1, &corpus, stride)
+            == scalar_strided(n1, &corpus, stride)
+    }
+
+    fn qc_memchr_aligned_matches_strided(
+        n1: u8, corpus: Vec<u8>, align: u8
+    ) -> bool {
+        let align = 1 << (align % 5);
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let n1 = n1 % 4;
+        memchr_aligned(n1, &corpus, align)
+            == memchr_strided(n1, &corpus, align)
+    }
+
+    fn qc_memchr_of_iter_matches_memchr(input: BoundaryInput) -> bool {
+        let (n1, corpus) = (input.needle, input.haystack());
+        let iter = corpus.iter().cloned();
+        let collected: Vec<u8> = iter.clone().collect();
+        memchr_of_iter(n1, iter) == memchr(n1, &collected)
+    }
+
+    fn qc_memchr_early_matches_memchr(input: BoundaryInput) -> bool {
+        let (n1, corpus) = (input.needle, input.haystack());
+        memchr_early(n1, corpus) == memchr(n1, corpus)
+    }
+
+    fn qc_memchr_ct_matches_memchr(n1: u8, corpus: Vec<u8>) -> bool {
+        memchr_ct(n1, &corpus) == memchr(n1, &corpus)
+    }
+
+    fn qc_mismatch_matches_naive(a: Vec<u8>, b: Vec<u8>, n: usize) -> bool {
+        // Make a common prefix likely, so that differences are found deep
+        // into the slices.
+        let mut b = b;
+        let n = n % (a.len() + 1);
+        b.splice(0..0, a[..n].iter().cloned());
+        let shorter = cmp::min(a.len(), b.len());
+        let expected = match a.iter().zip(&b).position(|(x, y)| x != y) {
+            None if a.len() == b.len() => None,
+            None => Some(shorter),
+            found => found,
+        };
+        mismatch(&a, &b) == expected
+    }
+
+    fn qc_first_and_last_matches_naive(input: BoundaryInput) -> bool {
+        let (n1, corpus) = (input.needle, input.haystack());
+        let expected = naive::memchr(n1, corpus)
+            .map(|first| (first, naive::memrchr(n1, corpus).unwrap()));
+        first_and_last(n1, corpus) == expected
+    }
+
+    fn qc_memchr1_boundary_matches_naive(input: BoundaryInput) -> bool {
+        let (n1, corpus) = (input.needle, input.haystack());
+        let expected = naive::memchr(n1, corpus);
+        memchr(n1, corpus) == expected
+            && fallback::memchr(n1, corpus) == expected
+            && fallback::memchr_unroll4(n1, corpus) == expected
+    }
+}
+
+#[cfg(feature = "portable_simd")]
+quickcheck! {
+    fn qc_memchr1_simd_matches_fallback(n1: u8, corpus: Vec<u8>) -> bool {
+        ::simd::memchr(n1, &corpus) == fallback::memchr(n1, &corpus)
+    }
+}
+
+quickcheck! {
+    fn qc_memchr0_matches_naive(corpus: Vec<u8>) -> bool {
+        memchr0(&corpus) == naive::memchr(0, &corpus)
+            && fallback::memchr0(&corpus) == naive::memchr(0, &corpus)
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_masked_matches_naive(
+        n1: u8, mask: u8, corpus: Vec<u8>
+    ) -> bool {
+        let expected = corpus.iter().position(|&b| b & mask == n1 & mask);
+        memchr_masked(n1, mask, &corpus) == expected
+    }
+
+    fn qc_memchr_masked_full_mask_matches_memchr(
+        n1: u8, corpus: Vec<u8>
+    ) -> bool {
+        memchr_masked(n1, 0xFF, &corpus) == memchr(n1, &corpus)
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_u16_matches_naive(needle: u16, corpus: Vec<u8>) -> bool {
+        // Keep the alphabet small so that matches actually happen.
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let needle =
+            u16::from_le_bytes([needle as u8 % 4, (needle >> 8) as u8 % 4]);
+        let le = naive_memchr_u16(needle.to_le_bytes(), &corpus);
+        let be = naive_memchr_u16(needle.to_be_bytes(), &corpus);
+        memchr_u16_le(needle, &corpus) == le
+            && memchr_u16_be(needle, &corpus) == be
+    }
+
+    fn qc_first_non_printable_matches_naive(corpus: Vec<u8>) -> bool {
+        first_non_printable(&corpus)
+            == corpus.iter().position(|&b| !(0x20..=0x7E).contains(&b))
+    }
+
+    fn qc_first_non_printable_in_printable(
+        corpus: Vec<u8>, planted: u8
+    ) -> bool {
+        // Most random bytes aren't printable, so map the corpus into the
+        // printable range and plant a single byte.
+        let mut corpus: Vec<u8> =
+            corpus.into_iter().map(|b| 0x20 + b % 0x5F).collect();
+        corpus.push(planted);
+        corpus.extend_from_slice(b"printable tail");
+        first_non_printable(&corpus)
+            == corpus.iter().position(|&b| !(0x20..=0x7E).contains(&b))
+    }
+
+    fn qc_is_ascii_matches_naive(corpus: Vec<u8>) -> bool {
+        is_ascii(&corpus) == corpus.iter().all(|&b| b < 0x80)
+    }
+
+    fn qc_next_char_boundary_matches_naive(
+        corpus: Vec<u8>, from: usize
+    ) -> bool {
+        let from = from % (corpus.len() + 1);
+        let expected = corpus[from..]
+            .iter()
+            .position(|&b| b & 0xC0 != 0x80)
+            .map(|i| from + i);
+        next_char_boundary(&corpus, from) == expected
+    }
+
+    fn qc_memchr_utf8_checked_matches_naive(
+        needle: u8, corpus: Vec<u8>
+    ) -> bool {
+        let needle = needle & 0x7F;
+        let i = corpus.iter().position(|&b| b == needle);
+        let prefix = &corpus[..i.unwrap_or(corpus.len())];
+        let expected = ::std::str::from_utf8(prefix).map(|_| i);
+        memchr_utf8_checked(needle, &corpus) == expected
+    }
+
+    fn qc_memchr2_which_matches_naive(
+        n1: u8, n2: u8, corpus: Vec<u8>
+    ) -> bool {
+        let expected = corpus
+            .iter()
+            .position(|&b| b == n1 || b == n2)
+            .map(|i| (i, corpus[i]));
+        memchr2_which(n1, n2, &corpus) == expected
+    }
+
+    fn qc_is_subsequence_matches_naive(
+        needle: Vec<u8>, corpus: Vec<u8>
+    ) -> bool {
+        // A small alphabet, so that subsequences are common.
+        let needle: Vec<u8> =
+            needle.into_iter().take(8).map(|b| b % 4).collect();
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let mut it = corpus.iter();
+        let expected = needle.iter().all(|b| it.any(|c| c == b));
+        is_subsequence(&needle, &corpus) == expected
+    }
+
+    fn qc_memrchr_not_matches_naive(input: BoundaryInput) -> bool {
+        let n1 = input.needle;
+        let corpus: Vec<u8> = input
+            .haystack()
+            .iter()
+            .map(|&b| if b == n1 { !n1 } else { n1 })
+            .collect();
+        memrchr_not(n1, &corpus) == corpus.iter().rposition(|&b| b != n1)
+    }
+
+    fn qc_memchr_not_matches_fallback(input: BoundaryInput) -> bool {
+        // Flip the usual density around, so that most bytes are the needle.
+        let n1 = input.needle;
+        let corpus: Vec<u8> = input
+            .haystack()
+            .iter()
+            .map(|&b| if b == n1 { !n1 } else { n1 })
+            .collect();
+        let expected = corpus.iter().position(|&b| b != n1);
+        memchr_not(n1, &corpus) == expected
+            && fallback::memchr_not(n1, &corpus) == expected
+    }
+
+    fn qc_memchr_pred_matches_naive(corpus: Vec<u8>) -> bool {
+        let expected = corpus.iter().position(|&b| in_ranges(b));
+        let coarse = |word: usize| word & fallback::HI_USIZE != 0;
+        memchr_pred(coarse, in_ranges, &corpus) == expected
+            && memchr_pred(|_| true, in_ranges, &corpus) == expected
+    }
+
+    fn qc_is_ascii_accepts_ascii(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b & 0x7F).collect();
+        is_ascii(&corpus)
+    }
 }
 
 quickcheck! {
@@ -113,6 +1537,20 @@ quickcheck! {
     fn qc_memrchr1_matches_naive(n1: u8, corpus: Vec<u8>) -> bool {
         memrchr(n1, &corpus) == naive::memrchr(n1, &corpus)
     }
+
+    fn qc_memrchr_at_matches_naive(
+        n1: u8, corpus: Vec<u8>, at: usize
+    ) -> bool {
+        let end = cmp::min(at.saturating_add(1), corpus.len());
+        memrchr_at(n1, &corpus, at) == naive::memrchr(n1, &corpus[..end])
+    }
+
+    fn qc_memrchr1_boundary_matches_naive(input: BoundaryInput) -> bool {
+        let (n1, corpus) = (input.needle, input.haystack());
+        let expected = naive::memrchr(n1, corpus);
+        memrchr(n1, corpus) == expected
+            && fallback::memrchr(n1, corpus) == expected
+    }
 }
 
 quickcheck! {
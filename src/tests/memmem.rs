@@ -0,0 +1,393 @@
+use {Finder, ends_with, memmem, rfind_suffix};
+
+fn naive_memmem(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn naive_find_iter(needle: &[u8], haystack: &[u8]) -> Vec<usize> {
+    let mut matches = vec![];
+    let mut start = 0;
+    while start <= haystack.len() {
+        match naive_memmem(needle, &haystack[start..]) {
+            None => break,
+            Some(i) => {
+                matches.push(start + i);
+                start += i + ::std::cmp::max(1, needle.len());
+            }
+        }
+    }
+    matches
+}
+
+const MEMMEM_TESTS: &[(&str, &str, Option<usize>)] = &[
+    ("", "", Some(0)),
+    ("", "abc", Some(0)),
+    ("a", "", None),
+    ("a", "a", Some(0)),
+    ("a", "bba", Some(2)),
+    ("abc", "ab", None),
+    ("abc", "abc", Some(0)),
+    ("abc", "zzabcabc", Some(2)),
+    ("abc", "abababc", Some(4)),
+    ("aab", "aaaaab", Some(3)),
+    ("xyz", "the quick brown fox", None),
+    ("fox", "the quick brown fox", Some(16)),
+    ("the", "the quick brown fox", Some(0)),
+    ("needle", "haystack with a needle in it", Some(16)),
+];
+
+#[test]
+fn memmem_table() {
+    for &(needle, haystack, expected) in MEMMEM_TESTS {
+        let (needle, haystack) = (needle.as_bytes(), haystack.as_bytes());
+        assert_eq!(
+            expected,
+            memmem(needle, haystack),
+            "needle: {:?}, haystack: {:?}",
+            needle,
+            haystack,
+        );
+    }
+}
+
+#[test]
+fn finder_reuse() {
+    let finder = Finder::new(b"abc");
+    assert_eq!(b"abc", finder.needle());
+    for &haystack in &["abc", "xabc", "ab", "", "ababc"] {
+        assert_eq!(
+            naive_memmem(b"abc", haystack.as_bytes()),
+            finder.find(haystack.as_bytes()),
+        );
+    }
+}
+
+#[test]
+fn finder_find_iter() {
+    let finder = Finder::new(b"aa");
+    assert_eq!(vec![0, 2], finder.find_iter(b"aaaaa").collect::<Vec<_>>());
+    assert_eq!(vec![1, 4], finder.find_iter(b"baabaab").collect::<Vec<_>>());
+    assert_eq!(
+        Vec::<usize>::new(),
+        finder.find_iter(b"ababa").collect::<Vec<_>>(),
+    );
+
+    let finder = Finder::new(b"");
+    assert_eq!(vec![0, 1, 2], finder.find_iter(b"ab").collect::<Vec<_>>());
+    assert_eq!(vec![0], finder.find_iter(b"").collect::<Vec<_>>());
+}
+
+#[test]
+fn memmem_html() {
+    let html = b"<ul><li><a href=\"/x\">x</a></li><li><a href=\"/y\">y</a>";
+    assert_eq!(Some(22), memmem(b"</a>", html));
+    assert_eq!(Some(26), memmem(b"</li>", html));
+    assert_eq!(None, memmem(b"</ul>", html));
+}
+
+// Needles longer than `x86::MEMMEM_SHORT_MAX` always use the skip table,
+// so this makes sure that it's tested even when the SIMD routine is used for
+// short needles.
+#[test]
+fn memmem_long_needle() {
+    let needle = b"aaaaaaaaaaaaaaaaaaaab";
+    let mut haystack = vec![b'a'; 300];
+    assert_eq!(None, memmem(needle, &haystack));
+    haystack.extend_from_slice(b"b");
+    assert_eq!(Some(280), memmem(needle, &haystack));
+    assert_eq!(Some(280), Finder::new(needle).find(&haystack));
+}
+
+// Each of these makes the skip table alone do about `needle.len()`
+// comparisons per byte of haystack: the last byte of the needle matches at
+// almost every position, and the rest of the needle only mismatches near
+// its end. The search must still stay linear in the haystack.
+#[test]
+fn memmem_adversarial_is_linear() {
+    use memmem::scalar_comparisons;
+
+    let n = 100_000;
+    for &m in &[3, 20, 100, 1_000] {
+        let mut aba = vec![b'a'; m];
+        aba[m - 2] = b'b';
+        let mut ab = vec![b'a'; m];
+        ab[m - 1] = b'b';
+        let mut ba = vec![b'a'; m];
+        ba[0] = b'b';
+        let periodic: Vec<u8> = (0..m).map(|i| b"aab"[i % 3]).collect();
+        let cases = vec![
+            (aba, vec![b'a'; n]),
+            (ab, vec![b'a'; n]),
+            (ba, vec![b'a'; n]),
+            (periodic, (0..n).map(|i| b"aab"[i % 3 / 2]).collect()),
+        ];
+        for (needle, haystack) in cases {
+            let (found, comparisons) = scalar_comparisons(&needle, &haystack);
+            assert_eq!(naive_memmem(&needle, &haystack), found);
+            assert!(
+                comparisons <= 6 * haystack.len() + needle.len(),
+                "{} comparisons for a needle of {} bytes",
+                comparisons,
+                m,
+            );
+        }
+    }
+}
+
+#[test]
+fn two_way_matches_naive() {
+    use memmem::two_way;
+
+    let needles: &[&[u8]] =
+        &[b"ab", b"aab", b"aba", b"abab", b"abaab", b"baaab", b"aaaaba"];
+    let mut haystack = vec![];
+    for i in 0..300 {
+        haystack.push(b"ab"[(i * 7 / 5 + i / 11) % 2]);
+    }
+    for &needle in needles {
+        for start in 0..50 {
+            let haystack = &haystack[start..];
+            assert_eq!(
+                naive_memmem(needle, haystack),
+                two_way(needle, haystack),
+                "needle: {:?}",
+                needle,
+            );
+        }
+    }
+}
+
+/// Returns a haystack over a small alphabet of `len` bytes, with `needle`
+/// planted at `at`.
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+fn planted(needle: &[u8], len: usize, at: usize) -> Vec<u8> {
+    let mut haystack: Vec<u8> =
+        (0..len).map(|i| b"ab"[(i * 7 / 3) % 2]).collect();
+    haystack[at..at + needle.len()].copy_from_slice(needle);
+    haystack
+}
+
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+#[test]
+fn memmem_short_avx2_matches_naive() {
+    use x86;
+
+    if !x86::has_avx2() {
+        return;
+    }
+    for nlen in 2..x86::MEMMEM_SHORT_MAX + 1 {
+        let mut needle: Vec<u8> = (0..nlen).map(|i| b"ab"[i % 2]).collect();
+        needle[nlen - 1] = b'z';
+        for len in nlen..nlen + 100 {
+            for &at in &[0, (len - nlen) / 2, len - nlen] {
+                let haystack = planted(&needle, len, at);
+                assert_eq!(
+                    naive_memmem(&needle, &haystack),
+                    x86::memmem_short_avx2(&needle, &haystack),
+                    "needle: {:?}, haystack: {:?}",
+                    needle,
+                    haystack,
+                );
+            }
+        }
+        for len in 0..nlen {
+            let haystack = vec![b'a'; len];
+            assert_eq!(None, x86::memmem_short_avx2(&needle, &haystack));
+        }
+    }
+}
+
+// The SSE 4.2 routine is only used on CPUs without AVX2, so it's tested
+// directly here. Its chunks are 16 bytes, so the lengths cover candidates
+// that run off the end of a chunk, and haystacks with a short tail.
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+#[test]
+fn memmem_short_sse42_matches_naive() {
+    use x86;
+
+    if !x86::has_sse42() {
+        return;
+    }
+    for nlen in 2..x86::MEMMEM_SHORT_MAX + 1 {
+        let mut needle: Vec<u8> = (0..nlen).map(|i| b"ab"[i % 2]).collect();
+        needle[nlen - 1] = b'z';
+        for len in nlen..nlen + 100 {
+            for &at in &[0, (len - nlen) / 2, len - nlen] {
+                let haystack = planted(&needle, len, at);
+                assert_eq!(
+                    naive_memmem(&needle, &haystack),
+                    x86::memmem_short_sse42(&needle, &haystack),
+                    "needle: {:?}, haystack: {:?}",
+                    needle,
+                    haystack,
+                );
+            }
+        }
+        for len in 0..nlen {
+            let haystack = vec![b'a'; len];
+            assert_eq!(None, x86::memmem_short_sse42(&needle, &haystack));
+        }
+    }
+}
+
+#[test]
+fn ends_with_exact_match() {
+    assert!(ends_with(b"abc", b"abc"));
+    assert!(ends_with(b"c", b"abc"));
+    assert!(ends_with(b"", b"abc"));
+    assert!(ends_with(b"", b""));
+    assert_eq!(Some(0), rfind_suffix(b"abc", b"abc"));
+    assert_eq!(Some(3), rfind_suffix(b"", b"abc"));
+}
+
+#[test]
+fn ends_with_longer_suffix() {
+    assert!(!ends_with(b"xabc", b"abc"));
+    assert!(!ends_with(b"a", b""));
+    assert_eq!(None, rfind_suffix(b"xabc", b"abc"));
+    assert_eq!(None, rfind_suffix(b"a", b""));
+}
+
+#[test]
+fn ends_with_mismatch() {
+    assert!(!ends_with(b"abd", b"xxabc"));
+    assert!(!ends_with(b"bbc", b"xxabc"));
+    // The last byte of the suffix occurs in the tail, but not at the end.
+    assert!(!ends_with(b"cab", b"xxabc"));
+}
+
+#[test]
+fn rfind_suffix_near_end() {
+    let mut haystack = vec![0; 1000];
+    haystack[10..14].copy_from_slice(b"PK\x05\x06");
+    haystack[980..984].copy_from_slice(b"PK\x05\x06");
+    haystack[990] = 0x06;
+    assert_eq!(Some(980), rfind_suffix(b"PK\x05\x06", &haystack));
+    assert_eq!(Some(10), rfind_suffix(b"PK\x05\x06", &haystack[..983]));
+    assert_eq!(None, rfind_suffix(b"PK\x05\x06", &haystack[..13]));
+    assert_eq!(Some(0), rfind_suffix(b"aa", b"aa"));
+    assert_eq!(Some(2), rfind_suffix(b"aa", b"aaaa"));
+}
+
+quickcheck! {
+    fn qc_ends_with_matches_slice(suffix: Vec<u8>, haystack: Vec<u8>) -> bool {
+        let suffix: Vec<u8> =
+            suffix.into_iter().take(3).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        ends_with(&suffix, &haystack) == haystack.ends_with(&suffix)
+    }
+
+    fn qc_rfind_suffix_matches_naive(
+        suffix: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        let suffix: Vec<u8> =
+            suffix.into_iter().take(3).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        let expected = if suffix.is_empty() {
+            Some(haystack.len())
+        } else {
+            haystack.windows(suffix.len()).rposition(|w| w == &*suffix)
+        };
+        rfind_suffix(&suffix, &haystack) == expected
+    }
+
+    fn qc_memmem_matches_naive(needle: Vec<u8>, haystack: Vec<u8>) -> bool {
+        memmem(&needle, &haystack) == naive_memmem(&needle, &haystack)
+    }
+
+    fn qc_memmem_finds_planted_needle(
+        prefix: Vec<u8>, needle: Vec<u8>, suffix: Vec<u8>
+    ) -> bool {
+        let mut haystack = prefix.clone();
+        haystack.extend_from_slice(&needle);
+        haystack.extend_from_slice(&suffix);
+        let found = memmem(&needle, &haystack);
+        let planted = match found {
+            None => false,
+            Some(i) => i <= prefix.len(),
+        };
+        planted && found == naive_memmem(&needle, &haystack)
+    }
+
+    fn qc_finder_find_iter_matches_naive(
+        needle: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        // Keep the alphabet small so that matches actually happen.
+        let needle: Vec<u8> =
+            needle.into_iter().take(3).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        let finder = Finder::new(&needle);
+        finder.find_iter(&haystack).collect::<Vec<_>>()
+            == naive_find_iter(&needle, &haystack)
+    }
+
+    fn qc_two_way_matches_naive(needle: Vec<u8>, haystack: Vec<u8>) -> bool {
+        let needle: Vec<u8> =
+            needle.into_iter().take(8).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        if needle.len() < 2 {
+            return true;
+        }
+        ::memmem::two_way(&needle, &haystack)
+            == naive_memmem(&needle, &haystack)
+    }
+
+    fn qc_two_way_rev_matches_naive(
+        needle: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        let needle: Vec<u8> =
+            needle.into_iter().take(8).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        if needle.is_empty() {
+            return true;
+        }
+        let expected =
+            haystack.windows(needle.len()).rposition(|w| w == &needle[..]);
+        ::memmem::two_way_rev(&needle, &haystack) == expected
+    }
+
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    fn qc_memmem_short_avx2_matches_naive(
+        needle: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        use x86;
+
+        let max = x86::MEMMEM_SHORT_MAX;
+        let needle: Vec<u8> =
+            needle.into_iter().take(max).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        if needle.len() < 2 || !x86::has_avx2() {
+            return true;
+        }
+        x86::memmem_short_avx2(&needle, &haystack)
+            == naive_memmem(&needle, &haystack)
+    }
+
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    fn qc_memmem_short_sse42_matches_naive(
+        needle: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        use x86;
+
+        let max = x86::MEMMEM_SHORT_MAX;
+        let needle: Vec<u8> =
+            needle.into_iter().take(max).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        if needle.len() < 2 || !x86::has_sse42() {
+            return true;
+        }
+        x86::memmem_short_sse42(&needle, &haystack)
+            == naive_memmem(&needle, &haystack)
+    }
+}
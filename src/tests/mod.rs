@@ -1,7 +1,40 @@
 use std::iter::repeat;
+use std::mem::size_of;
 
+use quickcheck::{Arbitrary, Gen};
+
+mod backends;
+mod byteset;
+mod bytestr;
+#[cfg(all(
+    feature = "libc",
+    not(target_arch = "wasm32"),
+    not(target_env = "sgx"),
+))]
+mod c;
+mod cstr;
+mod csv;
+#[cfg(feature = "use_std")]
+mod deque;
+mod ext;
+mod http;
 mod iter;
+mod lines;
 mod memchr;
+mod memmem;
+mod nonmax;
+#[cfg(all(unix, feature = "use_std"))]
+mod os;
+mod pattern;
+mod prefetch;
+mod read;
+mod scanner;
+mod split;
+#[cfg(feature = "use_std")]
+mod stream;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+mod transform;
 
 /// Create a sequence of tests that should be run by memchr implementations.
 fn memchr_tests() -> Vec<MemchrTest> {
@@ -408,3 +441,57 @@ impl MemchrTest {
             .collect()
     }
 }
+
+/// A haystack whose length is close to a multiple of the fallback's main loop
+/// size (two words), along with a needle to search for in it.
+///
+/// Arbitrary `Vec<u8>` values rarely have a length that lands exactly on
+/// the point where a routine switches from its unrolled loop to its tail, or
+/// start at a pointer with a particular alignment. This input does both on
+/// purpose, which is where off-by-one bugs tend to hide.
+#[derive(Clone, Debug)]
+struct BoundaryInput {
+    /// The bytes backing the haystack. The haystack proper starts at
+    /// `offset`, which lets us control its alignment.
+    buf: Vec<u8>,
+    offset: usize,
+    /// The byte to search for. It occurs in the haystack zero or more times.
+    needle: u8,
+}
+
+impl BoundaryInput {
+    fn haystack(&self) -> &[u8] {
+        &self.buf[self.offset..]
+    }
+}
+
+impl Arbitrary for BoundaryInput {
+    fn arbitrary<G: Gen>(g: &mut G) -> BoundaryInput {
+        // The main loop of the fallback reads two words at a time, while the
+        // SIMD routines read 16, 32, 64 or 128 bytes at a time. All of these
+        // are multiples of two words, so we pick a length within a few bytes
+        // of one of them.
+        let pair = 2 * size_of::<usize>();
+        let pairs = g.next_u32() as usize % 17;
+        let delta = (g.next_u32() % 7) as isize - 3;
+        let len = ((pairs * pair) as isize + delta).max(0) as usize;
+        let offset = g.next_u32() as usize % 32;
+
+        let needle = g.next_u32() as u8;
+        let other = needle.wrapping_add(1);
+        let mut buf = vec![other; offset + len];
+        // Plant the needle at most a few times, favoring the very start and
+        // end of the haystack.
+        if len > 0 {
+            for _ in 0..g.next_u32() % 3 {
+                let at = match g.next_u32() % 4 {
+                    0 => 0,
+                    1 => len - 1,
+                    _ => g.next_u32() as usize % len,
+                };
+                buf[offset + at] = needle;
+            }
+        }
+        BoundaryInput { buf, offset, needle }
+    }
+}
@@ -0,0 +1,43 @@
+use std::mem::size_of;
+
+use {NonMaxUsize, memchr, memchr_nz};
+
+use tests::memchr_tests;
+
+#[test]
+fn nonmax_has_niche() {
+    assert_eq!(size_of::<usize>(), size_of::<NonMaxUsize>());
+    assert_eq!(size_of::<usize>(), size_of::<Option<NonMaxUsize>>());
+    assert!(size_of::<Option<usize>>() > size_of::<Option<NonMaxUsize>>());
+}
+
+#[test]
+fn nonmax_conversions() {
+    for &n in &[0, 1, 255, 1 << 30, ::std::usize::MAX - 1] {
+        let nz = NonMaxUsize::new(n).unwrap();
+        assert_eq!(n, nz.get());
+        assert_eq!(n, usize::from(nz));
+        assert_eq!(n.to_string(), format!("{:?}", nz));
+    }
+    assert_eq!(None, NonMaxUsize::new(::std::usize::MAX));
+}
+
+#[test]
+fn nonmax_ordering() {
+    let (a, b) = (NonMaxUsize::new(1).unwrap(), NonMaxUsize::new(2).unwrap());
+    assert!(a < b);
+    assert_eq!(Some(a), vec![b, a].into_iter().min());
+}
+
+#[test]
+fn memchr_nz_find() {
+    for test in memchr_tests() {
+        test.one(false, |n1, corpus| memchr_nz(n1, corpus).map(usize::from));
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_nz_matches_memchr(n1: u8, corpus: Vec<u8>) -> bool {
+        memchr_nz(n1, &corpus).map(|n| n.get()) == memchr(n1, &corpus)
+    }
+}
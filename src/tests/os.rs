@@ -0,0 +1,18 @@
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+use memchr_os;
+
+#[test]
+fn memchr_os_path_separator() {
+    let mut path = PathBuf::from("usr");
+    path.push("local");
+    path.push("bin");
+    assert_eq!(Some(3), memchr_os(b'/', path.as_os_str()));
+}
+
+#[test]
+fn memchr_os_not_found() {
+    assert_eq!(None, memchr_os(b'/', OsStr::new("filename.txt")));
+    assert_eq!(None, memchr_os(b'/', OsStr::new("")));
+}
@@ -0,0 +1,98 @@
+use {ByteSet, find, rfind};
+
+#[test]
+fn find_byte() {
+    assert_eq!(Some(1), find(b'b', b"abcb"));
+    assert_eq!(Some(3), rfind(b'b', b"abcb"));
+    assert_eq!(None, find(b'z', b"abcb"));
+    assert_eq!(None, rfind(b'z', b""));
+}
+
+#[test]
+fn find_byteset() {
+    let set = ByteSet::from_bytes(b"cb");
+    assert_eq!(Some(1), find(&set, b"abcb"));
+    assert_eq!(Some(3), rfind(&set, b"abcb"));
+    assert_eq!(None, find(&ByteSet::new(), b"abcb"));
+    assert_eq!(None, rfind(&ByteSet::new(), b"abcb"));
+}
+
+#[test]
+fn find_substring() {
+    let haystack = b"abcabcab";
+    assert_eq!(Some(0), find(&b"abc"[..], haystack));
+    assert_eq!(Some(3), rfind(&b"abc"[..], haystack));
+    assert_eq!(Some(6), rfind(&b"ab"[..], haystack));
+    assert_eq!(Some(0), rfind(&b"abcabcab"[..], haystack));
+    assert_eq!(None, rfind(&b"abcabcabc"[..], haystack));
+    assert_eq!(None, find(&b"cc"[..], haystack));
+    assert_eq!(None, rfind(&b"cc"[..], haystack));
+}
+
+#[test]
+fn find_empty_substring() {
+    assert_eq!(Some(0), find(&b""[..], b"abc"));
+    assert_eq!(Some(3), rfind(&b""[..], b"abc"));
+    assert_eq!(Some(0), rfind(&b""[..], b""));
+}
+
+// These mirror the adversarial inputs for `Finder`: the last byte of the
+// needle matches at almost every position, and the rest of the needle only
+// mismatches near its start. The search must still stay linear in the
+// haystack.
+#[test]
+fn rfind_substring_adversarial_is_linear() {
+    use memmem::rfind_comparisons;
+
+    let n = 100_000;
+    for &m in &[3, 20, 100, 1_000] {
+        let mut aba = vec![b'a'; m];
+        aba[1] = b'b';
+        let mut ba = vec![b'a'; m];
+        ba[0] = b'b';
+        let mut ab = vec![b'a'; m];
+        ab[m - 1] = b'b';
+        let periodic: Vec<u8> = (0..m).map(|i| b"baa"[i % 3]).collect();
+        let cases = vec![
+            (aba, vec![b'a'; n]),
+            (ba, vec![b'a'; n]),
+            (ab, vec![b'a'; n]),
+            (periodic, (0..n).map(|i| b"baa"[i % 3]).collect()),
+        ];
+        for (needle, haystack) in cases {
+            let expected =
+                haystack.windows(needle.len()).rposition(|w| w == &needle[..]);
+            let (found, comparisons) = rfind_comparisons(&needle, &haystack);
+            assert_eq!(expected, found);
+            assert!(
+                comparisons <= 6 * haystack.len() + needle.len(),
+                "{} comparisons for a needle of {} bytes",
+                comparisons,
+                m,
+            );
+        }
+    }
+}
+
+quickcheck! {
+    fn qc_rfind_substring_matches_naive(
+        needle: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        let needle: Vec<u8> =
+            needle.into_iter().take(3).map(|b| b % 2).collect();
+        let haystack: Vec<u8> = haystack.into_iter().map(|b| b % 2).collect();
+        let expected = if needle.is_empty() {
+            Some(haystack.len())
+        } else {
+            haystack.windows(needle.len()).rposition(|w| w == &needle[..])
+        };
+        rfind(&needle[..], &haystack) == expected
+    }
+
+    fn qc_rfind_byteset_matches_naive(
+        set: Vec<u8>, haystack: Vec<u8>
+    ) -> bool {
+        let expected = haystack.iter().rposition(|b| set.contains(b));
+        rfind(&ByteSet::from_bytes(&set), &haystack) == expected
+    }
+}
@@ -0,0 +1,32 @@
+use {memchr, memchr_tuned};
+
+#[test]
+fn tuned_matches_memchr() {
+    let mut haystack = vec![b'z'; 100_000];
+    for &at in &[0, 1, 63, 64, 1023, 1024, 1025, 50_000, 99_999] {
+        haystack[at] = b'a';
+        for &lines in &[0, 1, 2, 8, 16, 17, 100, 10_000, ::std::usize::MAX] {
+            assert_eq!(
+                Some(at),
+                memchr_tuned(b'a', &haystack, lines),
+                "at: {}, lines: {}",
+                at,
+                lines,
+            );
+        }
+        haystack[at] = b'z';
+    }
+    assert_eq!(None, memchr_tuned(b'a', &haystack, 4));
+    assert_eq!(None, memchr_tuned(b'a', &[], 4));
+}
+
+quickcheck! {
+    fn qc_tuned_matches_memchr(
+        n1: u8, corpus: Vec<u8>, lines: usize
+    ) -> bool {
+        // Repeat the corpus so that it spans several blocks.
+        let corpus: Vec<u8> =
+            corpus.iter().cloned().cycle().take(corpus.len() * 40).collect();
+        memchr_tuned(n1, &corpus, lines % 32) == memchr(n1, &corpus)
+    }
+}
@@ -0,0 +1,83 @@
+use {ReadLike, ScanError, memchr_chunked};
+
+/// A byte source that doesn't implement `std::io::Read`, which yields at
+/// most `max` bytes per read and fails once `fail_at` bytes have been read.
+struct Source<'a> {
+    data: &'a [u8],
+    max: usize,
+    fail_at: Option<usize>,
+    pos: usize,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Broken;
+
+impl<'a> ReadLike for Source<'a> {
+    type Error = Broken;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Broken> {
+        match self.fail_at {
+            Some(at) if self.pos >= at => return Err(Broken),
+            _ => {}
+        }
+        let rest = &self.data[self.pos..];
+        let n = *[buf.len(), self.max, rest.len()].iter().min().unwrap();
+        buf[..n].copy_from_slice(&rest[..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn source<'a>(data: &'a [u8], max: usize) -> Source<'a> {
+    Source { data, max, fail_at: None, pos: 0 }
+}
+
+#[test]
+fn chunked_finds_across_reads() {
+    let data = b"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzza";
+    for max in 1..data.len() + 2 {
+        for buf_len in 1..data.len() + 2 {
+            let mut buf = vec![0; buf_len];
+            assert_eq!(
+                Ok(Some(data.len() as u64 - 1)),
+                memchr_chunked(b'a', &mut source(data, max), &mut buf),
+                "max: {}, buf_len: {}",
+                max,
+                buf_len,
+            );
+        }
+    }
+}
+
+#[test]
+fn chunked_not_found() {
+    let mut buf = [0; 8];
+    let mut src = source(b"zzzzzzzzzzzzzzzzzzzz", 3);
+    assert_eq!(Ok(None), memchr_chunked(b'a', &mut src, &mut buf));
+    let mut src = source(b"", 3);
+    assert_eq!(Ok(None), memchr_chunked(b'a', &mut src, &mut buf));
+}
+
+#[test]
+fn chunked_empty_buffer() {
+    let mut src = source(b"a", 3);
+    assert_eq!(
+        Err(ScanError::EmptyBuffer),
+        memchr_chunked(b'a', &mut src, &mut []),
+    );
+}
+
+#[test]
+fn chunked_read_error() {
+    let mut buf = [0; 4];
+    let mut src = source(b"zzzzzzzzzzzza", 4);
+    src.fail_at = Some(8);
+    assert_eq!(
+        Err(ScanError::Read(Broken)),
+        memchr_chunked(b'a', &mut src, &mut buf),
+    );
+    // A match that precedes the failure is still found.
+    let mut src = source(b"zzzzzazzzzzz", 4);
+    src.fail_at = Some(8);
+    assert_eq!(Ok(Some(5)), memchr_chunked(b'a', &mut src, &mut buf));
+}
@@ -0,0 +1,54 @@
+use {Scanner, memchr};
+
+#[test]
+fn scanner_find_matches_memchr() {
+    let haystack = b"the quick brown fox jumps over the lazy dog";
+    let scanner = Scanner::new(haystack);
+    for b in 0..256 {
+        assert_eq!(memchr(b as u8, haystack), scanner.find(b as u8));
+    }
+}
+
+#[test]
+fn scanner_find_from() {
+    let haystack = b"a,b;c,d;e";
+    let scanner = Scanner::new(haystack);
+    assert_eq!(Some(1), scanner.find_from(b',', 0));
+    assert_eq!(Some(1), scanner.find_from(b',', 1));
+    assert_eq!(Some(5), scanner.find_from(b',', 2));
+    assert_eq!(Some(7), scanner.find_from(b';', 4));
+    assert_eq!(None, scanner.find_from(b',', 6));
+    assert_eq!(Some(8), scanner.find_from(b'e', 8));
+    assert_eq!(None, scanner.find_from(b'e', 9));
+    assert_eq!(None, scanner.find_from(b'e', 100));
+}
+
+// The scanner's haystack starts at every offset into the buffer, so at
+// every alignment, and each search starts at every position in it, so that
+// the search starts in the unaligned head, at and between aligned words, and
+// in the tail.
+#[test]
+fn scanner_find_from_every_alignment() {
+    let buf: Vec<u8> = (0..66).map(|i| b"abcdefgh"[i * 5 % 7]).collect();
+    for offset in 0..16 {
+        let haystack = &buf[offset..offset + 50];
+        let scanner = Scanner::new(haystack);
+        for start in 0..haystack.len() + 1 {
+            for &n1 in b"aegz" {
+                let expected =
+                    memchr(n1, &haystack[start..]).map(|i| start + i);
+                assert_eq!(expected, scanner.find_from(n1, start));
+            }
+        }
+    }
+}
+
+quickcheck! {
+    fn qc_scanner_find_from_matches_memchr(
+        n1: u8, corpus: Vec<u8>, start: usize
+    ) -> bool {
+        let start = if corpus.is_empty() { 0 } else { start % corpus.len() };
+        let expected = memchr(n1, &corpus[start..]).map(|i| start + i);
+        Scanner::new(&corpus).find_from(n1, start) == expected
+    }
+}
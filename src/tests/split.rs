@@ -0,0 +1,314 @@
+use {
+    ByteStr, field_ranges, key_value_pairs, next_token, rsplitn,
+    segment_lengths, split_at_byte, split_collect, split_inclusive,
+    split_terminator_with_pos, splitn,
+};
+
+fn lengths(delim: u8, haystack: &[u8]) -> Vec<usize> {
+    segment_lengths(delim, haystack).collect()
+}
+
+#[test]
+fn segment_lengths_matches_split() {
+    assert_eq!(vec![0], lengths(b',', b""));
+    assert_eq!(vec![0, 0], lengths(b',', b","));
+    assert_eq!(vec![3], lengths(b',', b"abc"));
+    assert_eq!(vec![1, 1, 0], lengths(b',', b"a,b,"));
+    assert_eq!(vec![0, 1, 0, 2], lengths(b',', b",a,,bc"));
+}
+
+fn naive_split(delim: u8, haystack: &[u8]) -> Vec<&[u8]> {
+    haystack.split(|&b| b == delim).collect()
+}
+
+#[test]
+fn split_collect_matches_slice_split() {
+    for &haystack in &[&b""[..], b",", b"abc", b"a,b,", b",a,,bc", b",,,"] {
+        assert_eq!(naive_split(b',', haystack), split_collect(b',', haystack));
+    }
+}
+
+#[test]
+fn split_collect_trailing_delimiter() {
+    let pieces = split_collect(b'\n', b"foo\nbar\n");
+    assert_eq!(vec![&b"foo"[..], b"bar", b""], pieces);
+    assert!(pieces.capacity() >= 3);
+}
+
+#[test]
+fn field_ranges_match_split() {
+    for &haystack in &[&b""[..], b",", b"abc", b"a,b,", b",a,,bc", b",,,"] {
+        let ranges = field_ranges(b',', haystack);
+        let pieces: Vec<&[u8]> =
+            ranges.iter().map(|r| &haystack[r.clone()]).collect();
+        assert_eq!(naive_split(b',', haystack), pieces, "{:?}", haystack);
+    }
+}
+
+#[test]
+fn field_ranges_adjacent_and_trailing_delimiters() {
+    assert_eq!(vec![0..0], field_ranges(b',', b""));
+    assert_eq!(vec![0..0, 1..1], field_ranges(b',', b","));
+    assert_eq!(vec![0..3], field_ranges(b',', b"abc"));
+    assert_eq!(vec![0..1, 2..2, 3..3, 4..6], field_ranges(b',', b"a,,,bc"));
+    let ranges = field_ranges(b'\n', b"foo\nbar\n");
+    assert_eq!(vec![0..3, 4..7, 8..8], ranges);
+    assert!(ranges.capacity() >= 3);
+}
+
+#[test]
+fn next_token_delimiter_present() {
+    let (token, rest) = next_token(b'\n', b"foo\nbar\nbaz");
+    assert_eq!((&b"foo"[..], &b"bar\nbaz"[..]), (token, rest));
+    assert_eq!((&b"foo"[..], &b""[..]), next_token(b'\n', b"foo\n"));
+}
+
+#[test]
+fn next_token_delimiter_absent() {
+    assert_eq!((&b"foo"[..], &b""[..]), next_token(b'\n', b"foo"));
+    assert_eq!((&b""[..], &b""[..]), next_token(b'\n', b""));
+}
+
+#[test]
+fn next_token_delimiter_first() {
+    assert_eq!((&b""[..], &b"foo"[..]), next_token(b'\n', b"\nfoo"));
+    assert_eq!((&b""[..], &b""[..]), next_token(b'\n', b"\n"));
+}
+
+fn with_pos(delim: u8, haystack: &[u8]) -> Vec<(usize, &[u8])> {
+    split_terminator_with_pos(delim, haystack).collect()
+}
+
+#[test]
+fn split_terminator_with_pos_trailing_delimiter() {
+    assert_eq!(vec![(0, &b""[..])], with_pos(b',', b","));
+    assert_eq!(vec![(1, &b"a"[..]), (2, b"")], with_pos(b',', b"a,,"));
+    assert_eq!(
+        vec![(3, &b"foo"[..]), (7, b"bar")],
+        with_pos(b'\n', b"foo\nbar\n"),
+    );
+}
+
+#[test]
+fn split_terminator_with_pos_unterminated() {
+    assert!(with_pos(b',', b"").is_empty());
+    assert!(with_pos(b',', b"abc").is_empty());
+    assert_eq!(vec![(1, &b"a"[..])], with_pos(b',', b"a,bc"));
+    assert_eq!(
+        vec![(3, &b"foo"[..]), (7, b"bar")],
+        with_pos(b'\n', b"foo\nbar\nbaz"),
+    );
+}
+
+fn inclusive(delim: u8, haystack: &[u8]) -> Vec<&[u8]> {
+    split_inclusive(delim, haystack).collect()
+}
+
+#[test]
+fn split_inclusive_trailing_delimiter() {
+    assert_eq!(vec![&b","[..]], inclusive(b',', b","));
+    assert_eq!(vec![&b"a,"[..], b","], inclusive(b',', b"a,,"));
+    assert_eq!(
+        vec![&b"foo\n"[..], b"bar\n"],
+        inclusive(b'\n', b"foo\nbar\n"),
+    );
+}
+
+#[test]
+fn split_inclusive_unterminated() {
+    assert!(inclusive(b',', b"").is_empty());
+    assert_eq!(vec![&b"abc"[..]], inclusive(b',', b"abc"));
+    assert_eq!(
+        vec![&b"foo\n"[..], b"bar\n", b"baz"],
+        inclusive(b'\n', b"foo\nbar\nbaz"),
+    );
+}
+
+fn pairs(haystack: &[u8]) -> Vec<(&[u8], &[u8])> {
+    key_value_pairs(b';', b'=', haystack).collect()
+}
+
+#[test]
+fn key_value_pairs_basic() {
+    assert!(pairs(b"").is_empty());
+    assert_eq!(vec![(&b"a"[..], &b"1"[..])], pairs(b"a=1"));
+    assert_eq!(
+        vec![(&b"a"[..], &b"1"[..]), (&b"bc"[..], &b"23"[..])],
+        pairs(b"a=1;bc=23"),
+    );
+    assert_eq!(vec![(&b""[..], &b""[..])], pairs(b"="));
+    assert_eq!(vec![(&b"a"[..], &b""[..])], pairs(b"a="));
+    assert_eq!(vec![(&b""[..], &b"1"[..])], pairs(b"=1"));
+}
+
+#[test]
+fn key_value_pairs_without_kv_delim() {
+    assert_eq!(vec![(&b"flag"[..], &b""[..])], pairs(b"flag"));
+    assert_eq!(
+        vec![(&b"a"[..], &b"1"[..]), (&b"flag"[..], &b""[..])],
+        pairs(b"a=1;flag"),
+    );
+}
+
+#[test]
+fn key_value_pairs_value_with_kv_delim() {
+    assert_eq!(vec![(&b"a"[..], &b"b=c"[..])], pairs(b"a=b=c"));
+}
+
+#[test]
+fn key_value_pairs_skips_empty_records() {
+    assert!(pairs(b";;;").is_empty());
+    assert_eq!(
+        vec![(&b"a"[..], &b"1"[..]), (&b"b"[..], &b"2"[..])],
+        pairs(b";a=1;;b=2;"),
+    );
+}
+
+#[test]
+fn split_at_byte_keeps_needle() {
+    let (a, b) = split_at_byte(b',', b"ab,cd,ef").unwrap();
+    assert_eq!((a, b), (&b"ab"[..], &b",cd,ef"[..]));
+    let (a, b) = split_at_byte(b',', b",x").unwrap();
+    assert_eq!((a, b), (&b""[..], &b",x"[..]));
+    let (a, b) = split_at_byte(b',', b"x,").unwrap();
+    assert_eq!((a, b), (&b"x"[..], &b","[..]));
+}
+
+#[test]
+fn split_at_byte_absent() {
+    assert_eq!(None, split_at_byte(b',', b"abc"));
+    assert_eq!(None, split_at_byte(b',', b""));
+}
+
+fn split_n(n: usize, haystack: &[u8]) -> Vec<&[u8]> {
+    splitn(n, b':', haystack).collect()
+}
+
+fn rsplit_n(n: usize, haystack: &[u8]) -> Vec<&[u8]> {
+    rsplitn(n, b':', haystack).collect()
+}
+
+#[test]
+fn splitn_one_yields_whole_input() {
+    assert_eq!(vec![&b"a:b:c"[..]], split_n(1, b"a:b:c"));
+    assert_eq!(vec![&b"a:b:c"[..]], rsplit_n(1, b"a:b:c"));
+    assert_eq!(vec![&b""[..]], split_n(1, b""));
+    assert_eq!(vec![&b""[..]], rsplit_n(1, b""));
+}
+
+#[test]
+fn splitn_zero_yields_nothing() {
+    assert!(split_n(0, b"a:b").is_empty());
+    assert!(rsplit_n(0, b"a:b").is_empty());
+}
+
+#[test]
+fn splitn_more_than_delimiters() {
+    assert_eq!(vec![&b"a"[..], b"b", b"c"], split_n(10, b"a:b:c"));
+    assert_eq!(vec![&b"c"[..], b"b", b"a"], rsplit_n(10, b"a:b:c"));
+    assert_eq!(vec![&b"a"[..], b""], split_n(10, b"a:"));
+    assert_eq!(vec![&b""[..], b"a"], rsplit_n(10, b"a:"));
+}
+
+#[test]
+fn splitn_remainder_keeps_delimiters() {
+    assert_eq!(vec![&b"a"[..], b"b:c:d"], split_n(2, b"a:b:c:d"));
+    assert_eq!(vec![&b"a"[..], b"b", b"c:d"], split_n(3, b"a:b:c:d"));
+    assert_eq!(vec![&b"d"[..], b"a:b:c"], rsplit_n(2, b"a:b:c:d"));
+    assert_eq!(vec![&b"d"[..], b"c", b"a:b"], rsplit_n(3, b"a:b:c:d"));
+}
+
+quickcheck! {
+    fn qc_split_at_byte_matches_memchr(needle: u8, corpus: Vec<u8>) -> bool {
+        match (split_at_byte(needle, &corpus), ::memchr(needle, &corpus)) {
+            (None, None) => true,
+            (Some((a, b)), Some(i)) => {
+                a.len() == i
+                    && b[0] == needle
+                    && a.len() + b.len() == corpus.len()
+            }
+            _ => false,
+        }
+    }
+
+    fn qc_splitn_matches_slice(n: usize, corpus: Vec<u8>) -> bool {
+        let n = n % 6;
+        let corpus: Vec<u8> = corpus
+            .into_iter()
+            .map(|b| if b % 4 == 0 { b':' } else { b })
+            .collect();
+        let expected: Vec<&[u8]> = corpus.splitn(n, |&b| b == b':').collect();
+        let rexpected: Vec<&[u8]> =
+            corpus.rsplitn(n, |&b| b == b':').collect();
+        split_n(n, &corpus) == expected && rsplit_n(n, &corpus) == rexpected
+    }
+
+    fn qc_split_terminator_with_pos_matches_split(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let fields = with_pos(0, &corpus);
+        let mut expected: Vec<&[u8]> = corpus.split(|&b| b == 0).collect();
+        // The final piece is never terminated by a delimiter.
+        expected.pop();
+        let segments: Vec<&[u8]> = fields.iter().map(|&(_, s)| s).collect();
+        segments == expected
+            && fields.iter().all(|&(i, s)| {
+                corpus[i] == 0 && corpus[i - s.len()..i] == *s
+            })
+    }
+
+    fn qc_split_collect_matches_slice_split(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        split_collect(0, &corpus) == naive_split(0, &corpus)
+    }
+
+    fn qc_field_ranges_matches_split(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let pieces: Vec<&[u8]> = field_ranges(0, &corpus)
+            .into_iter()
+            .map(|r| &corpus[r])
+            .collect();
+        pieces == naive_split(0, &corpus)
+    }
+
+    fn qc_segment_lengths_matches_split(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let expected: Vec<usize> =
+            ByteStr(&corpus).split(0).map(|s| s.len()).collect();
+        lengths(0, &corpus) == expected
+    }
+
+    fn qc_next_token_matches_split(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let mut tokens = vec![];
+        let mut rest = &corpus[..];
+        loop {
+            let (token, next) = next_token(0, rest);
+            tokens.push(token);
+            if next.is_empty() {
+                break;
+            }
+            rest = next;
+        }
+        let mut expected: Vec<&[u8]> = corpus.split(|&b| b == 0).collect();
+        // A trailing delimiter leaves an empty rest, and so ends the loop
+        // without yielding the empty token that split yields.
+        if corpus.last() == Some(&0) {
+            expected.pop();
+        }
+        tokens == expected
+    }
+
+    fn qc_split_inclusive_matches_slice(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 4).collect();
+        let expected: Vec<&[u8]> =
+            corpus.split_inclusive(|&b| b == 0).collect();
+        inclusive(0, &corpus) == expected
+    }
+
+    fn qc_segment_lengths_sum_to_len(delim: u8, corpus: Vec<u8>) -> bool {
+        let lengths = lengths(delim, &corpus);
+        let delims = corpus.iter().filter(|&&b| b == delim).count();
+        lengths.len() == delims + 1
+            && lengths.iter().sum::<usize>() + delims == corpus.len()
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+use {find_in_bufread, memchr_stream, memchr_stream_with};
+
+/// A reader that returns at most one byte per call to `read`, and reports
+/// an interruption before every byte.
+struct Trickle<R> {
+    rdr: R,
+    interrupt: bool,
+}
+
+impl<R: Read> Read for Trickle<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "retry"));
+        }
+        let len = if buf.is_empty() { 0 } else { 1 };
+        self.rdr.read(&mut buf[..len])
+    }
+}
+
+fn haystack() -> Vec<u8> {
+    let mut haystack = vec![b'z'; 1_000_000];
+    haystack[654_321] = b'a';
+    haystack[654_322] = b'a';
+    haystack
+}
+
+#[test]
+fn stream_default_chunk_size() {
+    let mut rdr = Cursor::new(haystack());
+    assert_eq!(Some(654_321), memchr_stream(b'a', &mut rdr).unwrap());
+}
+
+#[test]
+fn stream_chunk_sizes_agree() {
+    let haystack = haystack();
+    for &chunk_size in &[1, 7, 4096, 256 * (1 << 10), 2_000_000] {
+        let mut rdr = Cursor::new(&haystack);
+        assert_eq!(
+            Some(654_321),
+            memchr_stream_with(b'a', &mut rdr, chunk_size).unwrap(),
+            "chunk size: {}",
+            chunk_size,
+        );
+    }
+}
+
+#[test]
+fn stream_short_reads() {
+    let mut rdr = Trickle { rdr: Cursor::new(b"abcdef"), interrupt: false };
+    assert_eq!(Some(4), memchr_stream_with(b'e', &mut rdr, 4).unwrap());
+}
+
+#[test]
+fn stream_not_found() {
+    let mut rdr = Cursor::new(haystack());
+    assert_eq!(None, memchr_stream_with(b'b', &mut rdr, 1000).unwrap());
+    let mut rdr = Cursor::new(vec![]);
+    assert_eq!(None, memchr_stream(b'b', &mut rdr).unwrap());
+}
+
+#[test]
+fn stream_zero_chunk_size() {
+    let mut rdr = Cursor::new(haystack());
+    let err = memchr_stream_with(b'a', &mut rdr, 0).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+}
+
+#[test]
+fn find_in_bufread_does_not_consume() {
+    let mut rdr = BufReader::new(Cursor::new(&b"foo\nbar\n"[..]));
+    assert_eq!(Some(3), find_in_bufread(b'\n', &mut rdr).unwrap());
+    assert_eq!(Some(3), find_in_bufread(b'\n', &mut rdr).unwrap());
+    assert_eq!(b"foo", &rdr.fill_buf().unwrap()[..3]);
+
+    rdr.consume(4);
+    assert_eq!(Some(3), find_in_bufread(b'\n', &mut rdr).unwrap());
+    rdr.consume(4);
+    assert_eq!(None, find_in_bufread(b'\n', &mut rdr).unwrap());
+    assert!(rdr.fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn find_in_bufread_only_searches_buffer() {
+    let haystack = haystack();
+    let mut rdr = BufReader::with_capacity(1000, Cursor::new(&haystack[..]));
+    let mut offset = 0;
+    let found = loop {
+        if let Some(i) = find_in_bufread(b'a', &mut rdr).unwrap() {
+            break Some(offset + i);
+        }
+        let len = rdr.fill_buf().unwrap().len();
+        if len == 0 {
+            break None;
+        }
+        assert!(len <= 1000);
+        rdr.consume(len);
+        offset += len;
+    };
+    assert_eq!(Some(654_321), found);
+}
+
+#[test]
+fn find_in_bufread_retries_interrupted() {
+    let rdr = Trickle { rdr: &b"ab"[..], interrupt: false };
+    let mut rdr = BufReader::new(rdr);
+    assert_eq!(Some(0), find_in_bufread(b'a', &mut rdr).unwrap());
+    rdr.consume(1);
+    assert_eq!(Some(0), find_in_bufread(b'b', &mut rdr).unwrap());
+}
@@ -0,0 +1,211 @@
+#[cfg(feature = "bench-internals")]
+use {kernel_available, memchr_kernel};
+#[cfg(feature = "bench-internals")]
+use tests::memchr_tests;
+#[cfg(feature = "profiling")]
+use core::mem::size_of;
+#[cfg(feature = "profiling")]
+use {ScanStats, memchr_profiled};
+use {KernelId, memchr, memchr_traced};
+
+#[test]
+fn traced_matches_memchr() {
+    for &(needle, haystack) in &[
+        (b'a', &b""[..]),
+        (b'a', b"a"),
+        (b'z', b"the quick brown fox"),
+        (b'k', b"the quick brown fox"),
+    ] {
+        let (found, _) = memchr_traced(needle, haystack);
+        assert_eq!(memchr(needle, haystack), found);
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd, feature = "use_std"))]
+#[test]
+fn traced_kernel_matches_cpu_features() {
+    let small = vec![0; 100];
+    let large = vec![0; 1 << 20];
+    let expected = if cfg!(memchr_runtime_avx)
+        && is_x86_feature_detected!("avx2")
+    {
+        KernelId::Avx2
+    } else if cfg!(memchr_runtime_sse2) {
+        KernelId::Sse2
+    } else {
+        KernelId::Swar
+    };
+    assert_eq!(expected, memchr_traced(1, &small).1);
+
+    let expected_large = if cfg!(memchr_runtime_avx512)
+        && is_x86_feature_detected!("avx512f")
+        && is_x86_feature_detected!("avx512bw")
+    {
+        KernelId::Avx512
+    } else {
+        expected
+    };
+    assert_eq!(expected_large, memchr_traced(1, &large).1);
+}
+
+#[cfg(not(all(
+    target_arch = "x86_64",
+    memchr_runtime_simd,
+    feature = "use_std",
+)))]
+#[test]
+fn traced_kernel_is_portable() {
+    let kernel = memchr_traced(1, b"abc").1;
+    assert!(
+        [
+            KernelId::PortableSimd,
+            KernelId::Libc,
+            KernelId::SwarUnroll4,
+            KernelId::Swar,
+        ]
+        .contains(&kernel),
+        "unexpected kernel: {:?}",
+        kernel,
+    );
+}
+
+/// Returns the kernels that `memchr_kernel` can run in this build on the
+/// current CPU.
+#[cfg(feature = "bench-internals")]
+fn available_kernels() -> Vec<KernelId> {
+    let mut kernels = vec![KernelId::Swar, KernelId::SwarUnroll4];
+    #[cfg(all(
+        feature = "libc",
+        not(target_arch = "wasm32"),
+        not(target_env = "sgx"),
+    ))]
+    kernels.push(KernelId::Libc);
+    #[cfg(feature = "portable_simd")]
+    kernels.push(KernelId::PortableSimd);
+    #[cfg(all(
+        target_arch = "x86_64",
+        memchr_runtime_simd,
+        feature = "use_std",
+    ))]
+    {
+        use x86;
+
+        if cfg!(memchr_runtime_sse2) {
+            kernels.push(KernelId::Sse2);
+        }
+        if x86::has_avx2() {
+            kernels.push(KernelId::Avx2);
+        }
+        #[cfg(memchr_runtime_avx512)]
+        {
+            if x86::has_avx512bw() {
+                kernels.push(KernelId::Avx512);
+            }
+        }
+    }
+    kernels
+}
+
+#[cfg(feature = "bench-internals")]
+#[test]
+fn each_kernel_matches_memchr() {
+    for kernel in available_kernels() {
+        for test in memchr_tests() {
+            test.one(false, |n1, corpus| memchr_kernel(kernel, n1, corpus));
+        }
+    }
+}
+
+#[cfg(feature = "bench-internals")]
+#[test]
+fn kernel_available_matches_build() {
+    let expected = available_kernels();
+    for &kernel in KernelId::ALL {
+        assert_eq!(
+            expected.contains(&kernel),
+            kernel_available(kernel),
+            "{:?}",
+            kernel,
+        );
+    }
+}
+
+#[cfg(all(feature = "bench-internals", not(feature = "portable_simd")))]
+#[test]
+#[should_panic(expected = "PortableSimd")]
+fn unavailable_kernel_panics() {
+    memchr_kernel(KernelId::PortableSimd, b'a', b"abc");
+}
+
+/// Returns the number of bytes before the first `usize` aligned address in
+/// `haystack`, or its length if it has no aligned address.
+#[cfg(feature = "profiling")]
+fn misaligned_prefix(haystack: &[u8]) -> usize {
+    let word = size_of::<usize>();
+    let misalign = (word - haystack.as_ptr() as usize % word) % word;
+    ::std::cmp::min(misalign, haystack.len())
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn profiled_stats_cover_haystack_without_match() {
+    let word = size_of::<usize>();
+    let buf = vec![b'a'; 300];
+    for start in 0..2 * word {
+        for end in start..buf.len() {
+            let haystack = &buf[start..end];
+            let (found, stats) = memchr_profiled(b'z', haystack);
+            assert_eq!(None, found);
+            let head = misaligned_prefix(haystack);
+            let pairs = (haystack.len() - head) / (2 * word);
+            let expected = ScanStats {
+                words: 2 * pairs,
+                head_bytes: head,
+                tail_bytes: haystack.len() - head - 2 * pairs * word,
+                simd: stats.simd,
+            };
+            assert_eq!(expected, stats, "start: {}, end: {}", start, end);
+        }
+    }
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn profiled_stats_count_rescanned_words() {
+    let word = size_of::<usize>();
+    let mut buf = vec![b'a'; 300];
+    buf[200] = b'z';
+    for start in 0..2 * word {
+        let haystack = &buf[start..];
+        let i = 200 - start;
+        let (found, stats) = memchr_profiled(b'z', haystack);
+        assert_eq!(Some(i), found);
+        // The main loop stops at the pair that contains the match, and the
+        // tail then scans it again up to the match.
+        let head = misaligned_prefix(haystack);
+        let pairs = (i - head) / (2 * word);
+        let expected = ScanStats {
+            words: 2 * (pairs + 1),
+            head_bytes: head,
+            tail_bytes: i - head - 2 * pairs * word + 1,
+            simd: stats.simd,
+        };
+        assert_eq!(expected, stats, "start: {}", start);
+    }
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn profiled_stats_stop_in_head() {
+    let buf = [b'z'; 64];
+    let word = size_of::<usize>();
+    let start =
+        (0..word).find(|&i| misaligned_prefix(&buf[i..]) > 0).unwrap();
+    let (found, stats) = memchr_profiled(b'z', &buf[start..]);
+    assert_eq!(Some(0), found);
+    assert_eq!((1, 0, 0), (stats.head_bytes, stats.words, stats.tail_bytes));
+
+    let (found, stats) = memchr_profiled(b'z', b"");
+    assert_eq!(None, found);
+    assert_eq!((0, 0, 0), (stats.head_bytes, stats.words, stats.tail_bytes));
+}
@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+
+use {
+    collapse_runs, replace_byte, trim_ascii_whitespace, unpad_record,
+    unpad_records,
+};
+
+fn naive_collapse_runs(byte: u8, haystack: &[u8]) -> Vec<u8> {
+    let mut collapsed: Vec<u8> = vec![];
+    for &b in haystack {
+        if b != byte || collapsed.last() != Some(&byte) {
+            collapsed.push(b);
+        }
+    }
+    collapsed
+}
+
+#[test]
+fn collapse_runs_leading_and_trailing() {
+    assert_eq!(b" a b ", &*collapse_runs(b' ', b"   a b    "));
+    assert_eq!(b"a", &*collapse_runs(b' ', b"a"));
+    assert_eq!(b" ", &*collapse_runs(b' ', b" "));
+    assert_eq!(b" ", &*collapse_runs(b' ', &[b' '; 1000]));
+    assert_eq!(b"", &*collapse_runs(b' ', b""));
+}
+
+#[test]
+fn collapse_runs_without_runs_is_unchanged() {
+    let haystack = b"a b c d\x00e";
+    assert_eq!(&haystack[..], &*collapse_runs(b' ', haystack));
+    assert_eq!(&haystack[..], &*collapse_runs(b'z', haystack));
+}
+
+#[test]
+fn collapse_runs_long_runs() {
+    let mut haystack = vec![0; 200];
+    haystack.extend_from_slice(b"abc");
+    haystack.extend(vec![0; 300]);
+    haystack.push(b'd');
+    assert_eq!(b"\x00abc\x00d", &*collapse_runs(0, &haystack));
+}
+
+#[test]
+fn replace_byte_absent_is_borrowed() {
+    let haystack = b"a b c";
+    match replace_byte(b'\t', b' ', haystack) {
+        Cow::Borrowed(got) => assert_eq!(haystack.as_ptr(), got.as_ptr()),
+        Cow::Owned(_) => panic!("expected a borrowed haystack"),
+    }
+    assert!(is_borrowed(replace_byte(b'\t', b' ', b"")));
+    // Replacing a byte with itself changes nothing either.
+    assert!(is_borrowed(replace_byte(b' ', b' ', haystack)));
+}
+
+#[test]
+fn replace_byte_present_is_owned() {
+    let replaced = replace_byte(b'\t', b' ', b"\ta\tb\t\tc\t");
+    assert_eq!(b" a b  c ", &*replaced);
+    assert!(!is_borrowed(replaced));
+
+    let mut haystack = vec![b'x'; 300];
+    haystack[299] = b'y';
+    let replaced = replace_byte(b'y', b'x', &haystack);
+    assert_eq!(vec![b'x'; 300], &*replaced);
+    assert!(!is_borrowed(replaced));
+}
+
+fn is_borrowed(cow: Cow<[u8]>) -> bool {
+    match cow {
+        Cow::Borrowed(_) => true,
+        Cow::Owned(_) => false,
+    }
+}
+
+#[test]
+fn unpad_record_all_sentinel() {
+    assert_eq!(b"", unpad_record(0, b""));
+    assert_eq!(b"", unpad_record(0, b"\x00"));
+    assert_eq!(b"", unpad_record(0, &[0; 100]));
+}
+
+#[test]
+fn unpad_record_without_sentinel_is_unchanged() {
+    assert_eq!(b"foo", unpad_record(0, b"foo"));
+    assert_eq!(b"\x00foo", unpad_record(0, b"\x00foo"));
+    assert_eq!(b"a\x00\x00b", unpad_record(0, b"a\x00\x00b"));
+}
+
+#[test]
+fn unpad_record_long_padding() {
+    let mut record = b"record".to_vec();
+    record.extend_from_slice(&[b' '; 100]);
+    assert_eq!(b"record", unpad_record(b' ', &record));
+    assert_eq!(b"record", unpad_record(b' ', &record[..7]));
+}
+
+#[test]
+fn unpad_records_fixed_width() {
+    let records: Vec<&[u8]> =
+        unpad_records(0, b"ab\x00\x00\x00\x00\x00\x00abcd", 4).collect();
+    assert_eq!(records, vec![&b"ab"[..], b"", b"abcd"]);
+    assert_eq!(0, unpad_records(0, b"", 4).count());
+}
+
+#[test]
+fn unpad_records_short_last_record() {
+    let records: Vec<&[u8]> = unpad_records(b' ', b"ab  cd  e ", 4).collect();
+    assert_eq!(records, vec![&b"ab"[..], b"cd", b"e"]);
+}
+
+#[test]
+#[should_panic]
+fn unpad_records_zero_width() {
+    unpad_records(b' ', b"abc", 0);
+}
+
+fn naive_trim(haystack: &[u8]) -> &[u8] {
+    let is_ws = |b: &u8| b" \t\n\r".contains(b);
+    let start = haystack.iter().position(|b| !is_ws(b));
+    let end = haystack.iter().rposition(|b| !is_ws(b));
+    match (start, end) {
+        (Some(start), Some(end)) => &haystack[start..end + 1],
+        _ => &haystack[..0],
+    }
+}
+
+#[test]
+fn trim_ascii_whitespace_all_whitespace() {
+    assert_eq!(b"", trim_ascii_whitespace(b""));
+    assert_eq!(b"", trim_ascii_whitespace(b" "));
+    assert_eq!(b"", trim_ascii_whitespace(b" \t\r\n"));
+    assert_eq!(b"", trim_ascii_whitespace(&[b' '; 100]));
+}
+
+#[test]
+fn trim_ascii_whitespace_no_whitespace() {
+    assert_eq!(b"a", trim_ascii_whitespace(b"a"));
+    assert_eq!(b"foo bar", trim_ascii_whitespace(b"foo bar"));
+    // Form feed and vertical tab aren't trimmed.
+    assert_eq!(b"\x0Cfoo\x0B", trim_ascii_whitespace(b"\x0Cfoo\x0B"));
+}
+
+#[test]
+fn trim_ascii_whitespace_one_sided() {
+    assert_eq!(b"foo", trim_ascii_whitespace(b"  \tfoo"));
+    assert_eq!(b"foo", trim_ascii_whitespace(b"foo\r\n"));
+    let mut haystack = vec![b'\n'; 100];
+    haystack.extend_from_slice(b"a b");
+    assert_eq!(b"a b", trim_ascii_whitespace(&haystack));
+    haystack.reverse();
+    assert_eq!(b"b a", trim_ascii_whitespace(&haystack));
+}
+
+#[test]
+fn trim_ascii_whitespace_long_runs() {
+    for len in 0..40 {
+        for i in 0..len {
+            let mut haystack = vec![b' '; len];
+            haystack[i] = b'x';
+            assert_eq!(b"x", trim_ascii_whitespace(&haystack));
+            for j in i..len {
+                haystack[j] = b'x';
+                let trimmed = trim_ascii_whitespace(&haystack);
+                assert_eq!(&haystack[i..j + 1], trimmed);
+            }
+        }
+    }
+}
+
+quickcheck! {
+    fn qc_trim_ascii_whitespace_matches_naive(corpus: Vec<u8>) -> bool {
+        let alphabet = b" \t\n\rab\x0C";
+        let corpus: Vec<u8> = corpus
+            .into_iter()
+            .map(|b| alphabet[b as usize % alphabet.len()])
+            .collect();
+        trim_ascii_whitespace(&corpus) == naive_trim(&corpus)
+    }
+
+    fn qc_collapse_runs_matches_naive(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 3).collect();
+        collapse_runs(0, &corpus) == naive_collapse_runs(0, &corpus)
+    }
+
+    fn qc_replace_byte_matches_naive(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 3).collect();
+        let expected: Vec<u8> =
+            corpus.iter().map(|&b| if b == 0 { 2 } else { b }).collect();
+        let replaced = replace_byte(0, 2, &corpus);
+        *replaced == *expected
+            && is_borrowed(replaced) != corpus.contains(&0)
+    }
+
+    fn qc_unpad_record_matches_naive(corpus: Vec<u8>) -> bool {
+        let corpus: Vec<u8> = corpus.into_iter().map(|b| b % 3).collect();
+        let end = corpus.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        unpad_record(0, &corpus) == &corpus[..end]
+    }
+}
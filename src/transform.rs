@@ -0,0 +1,190 @@
+// This module defines routines that build a modified copy of a haystack, or
+// that narrow a haystack down to the part of it that matters.
+
+use core::slice;
+#[cfg(feature = "use_std")]
+use std::borrow::Cow;
+
+use fallback;
+use memrchr_not;
+#[cfg(feature = "use_std")]
+use {Memchr, memchr, memchr_not};
+
+/// Return a copy of `haystack` in which every run of consecutive `byte`s is
+/// replaced by a single `byte`.
+///
+/// The start of each run is found with `memchr`, and its end with
+/// `memchr_not`, so the bytes in between runs are copied in bulk. A
+/// haystack without any runs is copied unchanged.
+///
+/// # Example
+///
+/// ```
+/// use memchr::collapse_runs;
+///
+/// assert_eq!(collapse_runs(b' ', b"  a   b c  "), b" a b c ");
+/// ```
+#[cfg(feature = "use_std")]
+pub fn collapse_runs(byte: u8, haystack: &[u8]) -> Vec<u8> {
+    let mut collapsed = Vec::with_capacity(haystack.len());
+    let mut rest = haystack;
+    while let Some(i) = memchr(byte, rest) {
+        collapsed.extend_from_slice(&rest[..i + 1]);
+        rest = &rest[i + 1..];
+        let run = memchr_not(byte, rest).unwrap_or(rest.len());
+        rest = &rest[run..];
+    }
+    collapsed.extend_from_slice(rest);
+    collapsed
+}
+
+/// Return `haystack` with every occurrence of `from` replaced by `to`.
+///
+/// When `from` doesn't occur in `haystack`, which is checked with `memchr`,
+/// or when `from` and `to` are the same byte, nothing needs to change, so
+/// this returns `Cow::Borrowed(haystack)` without allocating. Otherwise,
+/// this returns `Cow::Owned` with a copy of `haystack` in which each
+/// occurrence of `from`, from the first one on, is found with `memchr_iter`
+/// and replaced.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use memchr::replace_byte;
+///
+/// assert_eq!(replace_byte(b'\t', b' ', b"a\tb\tc"), &b"a b c"[..]);
+/// match replace_byte(b'\t', b' ', b"a b c") {
+///     Cow::Borrowed(unchanged) => assert_eq!(unchanged, b"a b c"),
+///     Cow::Owned(_) => unreachable!(),
+/// }
+/// ```
+#[cfg(feature = "use_std")]
+pub fn replace_byte<'a>(
+    from: u8,
+    to: u8,
+    haystack: &'a [u8],
+) -> Cow<'a, [u8]> {
+    let first = match memchr(from, haystack) {
+        Some(first) if from != to => first,
+        _ => return Cow::Borrowed(haystack),
+    };
+    let mut replaced = haystack.to_vec();
+    for i in Memchr::new(from, &haystack[first..]) {
+        replaced[first + i] = to;
+    }
+    Cow::Owned(replaced)
+}
+
+/// Return `record` without its trailing run of `sentinel` bytes.
+///
+/// This is meant for fixed-width records that are padded out to their width
+/// with a sentinel byte, like a NUL or a space. The end of the record's
+/// contents is found with `memrchr_not`. A record that is entirely
+/// sentinel bytes becomes empty, and a record that doesn't end with a
+/// sentinel byte is returned unchanged. Sentinel bytes anywhere else in the
+/// record are kept.
+///
+/// # Example
+///
+/// ```
+/// use memchr::unpad_record;
+///
+/// assert_eq!(unpad_record(0, b"foo\x00\x00\x00"), b"foo");
+/// assert_eq!(unpad_record(0, b"\x00\x00"), b"");
+/// assert_eq!(unpad_record(0, b"f\x00o"), b"f\x00o");
+/// ```
+#[inline]
+pub fn unpad_record(sentinel: u8, record: &[u8]) -> &[u8] {
+    match memrchr_not(sentinel, record) {
+        None => &record[..0],
+        Some(i) => &record[..i + 1],
+    }
+}
+
+/// Return `haystack` without its leading and trailing ASCII whitespace.
+///
+/// The whitespace bytes are `\t`, `\n`, `\r` and ` `. Unlike
+/// `u8::is_ascii_whitespace`, form feed (`\x0C`) isn't included. Whitespace
+/// in the middle of `haystack` is kept, and a haystack that is entirely
+/// whitespace becomes empty.
+///
+/// Both ends are found a word at a time, so long runs of whitespace are
+/// skipped quickly.
+///
+/// # Example
+///
+/// ```
+/// use memchr::trim_ascii_whitespace;
+///
+/// assert_eq!(trim_ascii_whitespace(b"\t foo bar\r\n"), b"foo bar");
+/// assert_eq!(trim_ascii_whitespace(b" \n "), b"");
+/// ```
+pub fn trim_ascii_whitespace(haystack: &[u8]) -> &[u8] {
+    let coarse = |x| !fallback::all_ascii_whitespace(x);
+    let fine = |b| b != b' ' && b != b'\t' && b != b'\n' && b != b'\r';
+    let start = match fallback::memchr_pred(coarse, fine, haystack) {
+        None => return &haystack[..0],
+        Some(start) => start,
+    };
+    // The byte at `start` isn't whitespace, so the reverse search always
+    // succeeds.
+    let rest = &haystack[start..];
+    let end = fallback::memrchr_pred(coarse, fine, rest).map_or(0, |i| i + 1);
+    &rest[..end]
+}
+
+/// Return an iterator over the fixed-width records in `buf`, each without
+/// its trailing run of `sentinel` bytes.
+///
+/// `buf` is split into consecutive records of `width` bytes, and each one is
+/// passed through `unpad_record`. If the length of `buf` isn't a multiple of
+/// `width`, then the last record is shorter than `width`, just like with
+/// `slice::chunks`.
+///
+/// # Panics
+///
+/// This panics if `width` is `0`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::unpad_records;
+///
+/// let buf = b"foo  barbaz  ";
+/// let records: Vec<&[u8]> = unpad_records(b' ', buf, 5).collect();
+/// assert_eq!(records, vec![&b"foo"[..], b"barba", b"z"]);
+/// ```
+#[inline]
+pub fn unpad_records<'a>(
+    sentinel: u8,
+    buf: &'a [u8],
+    width: usize,
+) -> UnpadRecords<'a> {
+    assert!(width > 0, "record width must be greater than zero");
+    UnpadRecords { sentinel, records: buf.chunks(width) }
+}
+
+/// An iterator over fixed-width records without their padding.
+///
+/// This is created by `unpad_records`.
+#[derive(Clone, Debug)]
+pub struct UnpadRecords<'a> {
+    sentinel: u8,
+    records: slice::Chunks<'a, u8>,
+}
+
+impl<'a> Iterator for UnpadRecords<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        self.records.next().map(|r| unpad_record(self.sentinel, r))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.records.size_hint()
+    }
+}
@@ -16,6 +16,11 @@ const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
 // from the loop unrolling.
 const LOOP_SIZE2: usize = 2 * VECTOR_SIZE;
 
+// The number of iterations of the loop in count after which its per-byte
+// counters are added up. Each iteration adds at most 4 to each counter, so
+// this is the most that fit in a byte without overflowing.
+const COUNT_FLUSH: usize = 255 / 4;
+
 #[target_feature(enable = "avx2")]
 pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     // For a high level explanation for how this algorithm works, see the
@@ -98,6 +103,196 @@ pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     None
 }
 
+#[target_feature(enable = "avx2")]
+pub unsafe fn contains_byte(n1: u8, haystack: &[u8]) -> bool {
+    // See the sse2 implementation for why this is distinct from memchr.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::contains_byte(n1, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    if forward_any1(ptr, vn1) {
+        return true;
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_load_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_load_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_cmpeq_epi8(vn1, a);
+        let eqb = _mm256_cmpeq_epi8(vn1, b);
+        let eqc = _mm256_cmpeq_epi8(vn1, c);
+        let eqd = _mm256_cmpeq_epi8(vn1, d);
+        let or1 = _mm256_or_si256(eqa, eqb);
+        let or2 = _mm256_or_si256(eqc, eqd);
+        if _mm256_movemask_epi8(_mm256_or_si256(or1, or2)) != 0 {
+            return true;
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        debug_assert!(sub(end_ptr, ptr) >= VECTOR_SIZE);
+
+        if forward_any1(ptr, vn1) {
+            return true;
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_any1(ptr, vn1);
+    }
+    false
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn count(n1: u8, haystack: &[u8]) -> usize {
+    // See the sse2 implementation for why this only uses unaligned loads,
+    // and for how the main loop counts matches.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::count(n1, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let zero = _mm256_setzero_si256();
+    while sub(end_ptr, ptr) >= LOOP_SIZE {
+        let chunks = cmp::min(COUNT_FLUSH, sub(end_ptr, ptr) / LOOP_SIZE);
+        let mut acc = zero;
+        for _ in 0..chunks {
+            let a = _mm256_loadu_si256(ptr as *const __m256i);
+            let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+            let c =
+                _mm256_loadu_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+            let d =
+                _mm256_loadu_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+            let eqab = _mm256_add_epi8(
+                _mm256_cmpeq_epi8(vn1, a),
+                _mm256_cmpeq_epi8(vn1, b),
+            );
+            let eqcd = _mm256_add_epi8(
+                _mm256_cmpeq_epi8(vn1, c),
+                _mm256_cmpeq_epi8(vn1, d),
+            );
+            acc = _mm256_sub_epi8(acc, _mm256_add_epi8(eqab, eqcd));
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        count += horizontal_sum(_mm256_sad_epu8(acc, zero));
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        count += forward_count1(ptr, vn1);
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        // Only the last `rem` bytes in this final (overlapping) vector
+        // haven't been counted yet. They correspond to the most significant
+        // bits of the mask, so shift the rest out.
+        let rem = sub(end_ptr, ptr);
+        debug_assert!(rem < VECTOR_SIZE);
+        let tail = end_ptr.sub(VECTOR_SIZE);
+        let chunk = _mm256_loadu_si256(tail as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(chunk, vn1)) as u32;
+        count += (mask >> (VECTOR_SIZE - rem)).count_ones() as usize;
+    }
+    count
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    // See the sse2 implementation for how this differs from memchr. Since
+    // an AVX2 movemask covers all 32 bits, no lanes need to be masked off
+    // after negating it.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::memchr_not(n1, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    if let Some(i) = forward_search_not1(start_ptr, end_ptr, ptr, vn1) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_load_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_load_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_cmpeq_epi8(vn1, a);
+        let eqb = _mm256_cmpeq_epi8(vn1, b);
+        let eqc = _mm256_cmpeq_epi8(vn1, c);
+        let eqd = _mm256_cmpeq_epi8(vn1, d);
+        let and1 = _mm256_and_si256(eqa, eqb);
+        let and2 = _mm256_and_si256(eqc, eqd);
+        let and3 = _mm256_and_si256(and1, and2);
+        if _mm256_movemask_epi8(and3) != -1 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = !_mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = !_mm256_movemask_epi8(eqb);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = !_mm256_movemask_epi8(eqc);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = !_mm256_movemask_epi8(eqd);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        debug_assert!(sub(end_ptr, ptr) >= VECTOR_SIZE);
+
+        if let Some(i) = forward_search_not1(start_ptr, end_ptr, ptr, vn1) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_not1(start_ptr, end_ptr, ptr, vn1);
+    }
+    None
+}
+
 #[target_feature(enable = "avx2")]
 pub unsafe fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
     let vn1 = _mm256_set1_epi8(n1 as i8);
@@ -477,6 +672,111 @@ pub unsafe fn memrchr3(
     None
 }
 
+/// Search for the first occurrence of a short needle, with at least 2 bytes,
+/// in `haystack`.
+///
+/// This compares every position in a vector of the haystack against the
+/// first byte of the needle, and the position `needle.len() - 1` bytes ahead
+/// against the last byte of the needle. Only positions where both match are
+/// candidates, and each candidate is verified by comparing the bytes in
+/// between. Anchoring on two bytes rejects most positions even when the
+/// first byte of the needle is common, which is the weak spot of searching
+/// for the first byte alone. Verification is cheap for short needles, but
+/// for long needles, a skip table does better.
+#[target_feature(enable = "avx2")]
+pub unsafe fn memmem_short(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    debug_assert!(needle.len() >= 2);
+
+    let last = needle.len() - 1;
+    let middle = &needle[1..last];
+    if haystack.len() < last + VECTOR_SIZE {
+        return haystack.windows(needle.len()).position(|w| w == needle);
+    }
+
+    let vfirst = _mm256_set1_epi8(needle[0] as i8);
+    let vlast = _mm256_set1_epi8(needle[last] as i8);
+    let ptr = haystack.as_ptr();
+    // The position of the last vector of candidates that fits entirely in
+    // the haystack.
+    let max = haystack.len() - last - VECTOR_SIZE;
+    let mut i = 0;
+    loop {
+        let a = _mm256_loadu_si256(ptr.add(i) as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(i + last) as *const __m256i);
+        let eq = _mm256_and_si256(
+            _mm256_cmpeq_epi8(a, vfirst),
+            _mm256_cmpeq_epi8(b, vlast),
+        );
+        let mut mask = _mm256_movemask_epi8(eq) as u32;
+        while mask != 0 {
+            let at = i + mask.trailing_zeros() as usize;
+            if &haystack[at + 1..at + last] == middle {
+                return Some(at);
+            }
+            mask &= mask - 1;
+        }
+        if i == max {
+            return None;
+        }
+        // The last vector overlaps with the previous one, and may find
+        // candidates that were already rejected, which is harmless.
+        i = cmp::min(i + VECTOR_SIZE, max);
+    }
+}
+
+/// The number of strided bytes compared by each gather in `memchr_strided`.
+const GATHER_LANES: usize = 8;
+
+/// Search for the first occurrence of `n1` at a multiple of `stride`.
+///
+/// Each iteration gathers one 32-bit word at each of the next 8 multiples
+/// of `stride` and compares the low byte of each word, which is the byte at
+/// that multiple, against `n1`. Since each word extends 3 bytes beyond the
+/// byte of interest, the gathers stop before they would read past the end of
+/// the haystack and the remaining multiples are checked one at a time.
+///
+/// `stride` must be a power of two that is at least 2 and at most
+/// `STRIDED_MAX`, so that each lane's offset fits in an `i32`.
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_strided(
+    n1: u8,
+    haystack: &[u8],
+    stride: usize,
+) -> Option<usize> {
+    debug_assert!(stride > 1 && stride.is_power_of_two());
+    debug_assert!(stride <= ::x86::STRIDED_MAX);
+
+    let ptr = haystack.as_ptr();
+    let len = haystack.len();
+    let span = GATHER_LANES * stride;
+    let shift = stride.trailing_zeros() as i32;
+    let offsets = _mm256_sll_epi32(
+        _mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7),
+        _mm_cvtsi32_si128(shift),
+    );
+    let low = _mm256_set1_epi32(0xFF);
+    let vn1 = _mm256_set1_epi32(n1 as i32);
+    let mut i = 0;
+    // The last lane reads the 4 bytes starting at `i + 7 * stride`.
+    while len >= 4 && i + span - stride <= len - 4 {
+        let base = ptr.add(i) as *const i32;
+        let words = _mm256_i32gather_epi32(base, offsets, 1);
+        let eq = _mm256_cmpeq_epi32(_mm256_and_si256(words, low), vn1);
+        let mask = _mm256_movemask_ps(_mm256_castsi256_ps(eq));
+        if mask != 0 {
+            return Some(i + (mask.trailing_zeros() as usize) * stride);
+        }
+        i += span;
+    }
+    while i < len {
+        if *ptr.add(i) == n1 {
+            return Some(i);
+        }
+        i += stride;
+    }
+    None
+}
+
 #[target_feature(enable = "avx2")]
 unsafe fn forward_search1(
     start_ptr: *const u8,
@@ -497,6 +797,48 @@ unsafe fn forward_search1(
     }
 }
 
+#[target_feature(enable = "avx2")]
+unsafe fn forward_any1(ptr: *const u8, vn1: __m256i) -> bool {
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    _mm256_movemask_epi8(_mm256_cmpeq_epi8(chunk, vn1)) != 0
+}
+
+/// Add up the four 64-bit lanes of `sums`, as produced by `_mm256_sad_epu8`.
+#[target_feature(enable = "avx2")]
+unsafe fn horizontal_sum(sums: __m256i) -> usize {
+    let lo = _mm256_castsi256_si128(sums);
+    let hi = _mm256_extracti128_si256(sums, 1);
+    let sum = _mm_add_epi64(lo, hi);
+    (_mm_cvtsi128_si64(sum) + _mm_extract_epi64(sum, 1)) as usize
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_count1(ptr: *const u8, vn1: __m256i) -> usize {
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(chunk, vn1));
+    (mask as u32).count_ones() as usize
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_not1(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = !_mm256_movemask_epi8(_mm256_cmpeq_epi8(chunk, vn1));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
 #[target_feature(enable = "avx2")]
 unsafe fn forward_search2(
     start_ptr: *const u8,
@@ -0,0 +1,74 @@
+// This module only depends on `core`, so that the benchmarks can include it
+// directly, like they do with the fallback implementation.
+
+use core::arch::x86_64::*;
+use core::mem::size_of;
+
+const VECTOR_SIZE: usize = size_of::<__m512i>();
+
+// The number of bytes to loop at in one iteration of memchr.
+const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
+
+#[target_feature(enable = "avx512f,avx512bw")]
+pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    // This follows the same approach as the sse2 and avx implementations,
+    // with two simplifications. Firstly, comparisons produce a 64-bit mask
+    // directly, so there's no need for a movemask. Secondly, a masked load
+    // never touches the bytes that are masked off, so the tail (and a
+    // haystack shorter than a single vector) is handled with one masked load
+    // instead of an overlapping unaligned load or a byte-at-a-time loop.
+    // For the same reason, there is no attempt at aligning the loads.
+    let vn1 = _mm512_set1_epi8(n1 as i8);
+    let len = haystack.len();
+    let ptr = haystack.as_ptr();
+    let mut i = 0;
+
+    while len - i >= LOOP_SIZE {
+        let a = _mm512_loadu_si512(ptr.add(i) as *const _);
+        let b = _mm512_loadu_si512(ptr.add(i + VECTOR_SIZE) as *const _);
+        let c = _mm512_loadu_si512(ptr.add(i + 2 * VECTOR_SIZE) as *const _);
+        let d = _mm512_loadu_si512(ptr.add(i + 3 * VECTOR_SIZE) as *const _);
+        let eqa = _mm512_cmpeq_epi8_mask(vn1, a);
+        let eqb = _mm512_cmpeq_epi8_mask(vn1, b);
+        let eqc = _mm512_cmpeq_epi8_mask(vn1, c);
+        let eqd = _mm512_cmpeq_epi8_mask(vn1, d);
+        if eqa | eqb | eqc | eqd != 0 {
+            if eqa != 0 {
+                return Some(i + forward_pos(eqa));
+            }
+            if eqb != 0 {
+                return Some(i + VECTOR_SIZE + forward_pos(eqb));
+            }
+            if eqc != 0 {
+                return Some(i + 2 * VECTOR_SIZE + forward_pos(eqc));
+            }
+            return Some(i + 3 * VECTOR_SIZE + forward_pos(eqd));
+        }
+        i += LOOP_SIZE;
+    }
+    while len - i >= VECTOR_SIZE {
+        let chunk = _mm512_loadu_si512(ptr.add(i) as *const _);
+        let eq = _mm512_cmpeq_epi8_mask(vn1, chunk);
+        if eq != 0 {
+            return Some(i + forward_pos(eq));
+        }
+        i += VECTOR_SIZE;
+    }
+    if i < len {
+        debug_assert!(len - i < VECTOR_SIZE);
+        let mask: __mmask64 = (1 << (len - i)) - 1;
+        let chunk = _mm512_maskz_loadu_epi8(mask, ptr.add(i) as *const _);
+        let eq = _mm512_mask_cmpeq_epi8_mask(mask, vn1, chunk);
+        if eq != 0 {
+            return Some(i + forward_pos(eq));
+        }
+    }
+    None
+}
+
+/// Compute the position of the first matching byte from the given mask,
+/// where bit `i` is set if and only if byte `i` matched.
+#[inline(always)]
+fn forward_pos(mask: __mmask64) -> usize {
+    mask.trailing_zeros() as usize
+}
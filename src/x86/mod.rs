@@ -1,10 +1,19 @@
-use std::mem;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use core::mem;
+use core::sync::atomic::{AtomicPtr, Ordering};
+#[cfg(test)]
+use core::sync::atomic::AtomicUsize;
 
+use byteset;
 use fallback;
+#[cfg(feature = "telemetry")]
+use telemetry::KernelId;
 
 mod avx;
+#[cfg(memchr_runtime_avx512)]
+mod avx512;
 mod sse2;
+mod sse42;
+mod ssse3;
 
 // This macro employs a gcc-like "ifunc" trick where by upon first calling
 // `memchr` (for example), CPU feature detection will be performed at runtime
@@ -27,39 +36,185 @@ mod sse2;
 // probably can't be inlined anyway---unless you've compiled your entire
 // program with AVX2 enabled. However, even then, the various memchr
 // implementations aren't exactly small, so inlining might not help anyway!
+//
+// The selected function pointer is cached in an `AtomicPtr` rather than a
+// `std::sync::Once` or similar, so that the cache only depends on `core`.
+// (CPU feature detection itself still requires `std` for now.) The slot
+// starts out pointing at `detect`, so no branch is needed to check whether
+// detection has happened. If several threads call a routine for the first
+// time simultaneously, they may each run detection, but they all compute and
+// store the same pointer, so the race is benign. The store uses `Release` and
+// the load uses `Acquire`, which pairs them up such that a thread that sees
+// the selected pointer also sees everything that happened before it was
+// stored. The pointer itself doesn't strictly need this, but it keeps the
+// cache correct should detection ever record more state, and on x86 both
+// orderings compile to plain moves, so it costs nothing.
+//
+// When the crate is compiled with AVX2 enabled (e.g., with
+// `-C target-feature=+avx2` or `-C target-cpu=native` on a CPU with AVX2),
+// then we know the AVX2 routines are always safe to call. In that case, we
+// skip the cache and detection entirely and call the AVX2 routine directly,
+// which also permits it to be inlined. Since `cfg!` is a constant, the
+// compiler removes whichever path is unused.
+//
+// Most routines return an `Option<usize>`. Those that return something else
+// use the `returning` form, e.g., `ifunc!(returning bool; fn(...) -> bool,
+// ...)`.
 macro_rules! ifunc {
-    ($fnty:ty, $name:ident, $haystack:ident, $($needle:ident),+) => {{
-        static mut FN: $fnty = detect;
-
-        fn detect($($needle: u8),+, haystack: &[u8]) -> Option<usize> {
-            let fun =
-                if cfg!(memchr_runtime_avx) && is_x86_feature_detected!("avx2") {
-                    avx::$name as usize
-                } else if cfg!(memchr_runtime_sse2) {
-                    sse2::$name as usize
-                } else {
-                    fallback::$name as usize
-                };
-            let slot = unsafe { &*(&FN as *const _ as *const AtomicUsize) };
-            slot.store(fun as usize, Ordering::Relaxed);
+    ($fnty:ty, $name:ident, $haystack:ident, $($needle:ident),+) => {
+        ifunc!(returning Option<usize>; $fnty, $name, $haystack, $($needle),+)
+    };
+    (
+        returning $ret:ty;
+        $fnty:ty, $name:ident, $haystack:ident, $($needle:ident),+
+    ) => {{
+        if cfg!(all(memchr_runtime_avx, target_feature = "avx2")) {
+            return unsafe { avx::$name($($needle),+, $haystack) };
+        }
+
+        static FN: AtomicPtr<()> = AtomicPtr::new(detect as *mut ());
+
+        fn detect($($needle: u8),+, haystack: &[u8]) -> $ret {
+            #[cfg(test)]
+            DETECTIONS.fetch_add(1, Ordering::Relaxed);
+            let fun = if has_avx2() {
+                avx::$name as *mut ()
+            } else if cfg!(memchr_runtime_sse2) {
+                sse2::$name as *mut ()
+            } else {
+                fallback::$name as *mut ()
+            };
+            FN.store(fun, Ordering::Release);
             unsafe {
-                mem::transmute::<usize, $fnty>(fun)($($needle),+, haystack)
+                mem::transmute::<*mut (), $fnty>(fun)($($needle),+, haystack)
             }
         }
 
         unsafe {
-            let slot = &*(&FN as *const _ as *const AtomicUsize);
-            let fun = slot.load(Ordering::Relaxed);
-            mem::transmute::<usize, $fnty>(fun)($($needle),+, $haystack)
+            let fun = FN.load(Ordering::Acquire);
+            mem::transmute::<*mut (), $fnty>(fun)($($needle),+, $haystack)
         }
     }}
 }
 
+/// The number of times runtime CPU feature detection has run for any routine,
+/// which lets tests check when it is skipped.
+#[cfg(test)]
+pub static DETECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The haystack length at which memchr switches to the AVX-512 routine.
+///
+/// Executing 512-bit instructions can lower the clock frequency of the core
+/// on some CPUs, and it takes a while for the frequency to recover, which
+/// slows down all of the code that runs afterwards. That's only worth it
+/// when there's enough haystack to amortize the cost, so AVX2 is used for
+/// everything smaller than this.
+#[cfg(memchr_runtime_avx512)]
+const AVX512_MIN_LEN: usize = 1 << 16;
+
 #[inline(always)]
 pub fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    // This check is only reached for large haystacks, where the cost of
+    // checking `is_x86_feature_detected!` (which caches its result) doesn't
+    // matter. So there's no need to fold AVX-512 into the ifunc below.
+    #[cfg(memchr_runtime_avx512)]
+    {
+        if uses_avx512(haystack) {
+            return unsafe { avx512::memchr(n1, haystack) };
+        }
+    }
     ifunc!(fn(u8, &[u8]) -> Option<usize>, memchr, haystack, n1)
 }
 
+/// Returns true if `memchr` uses the AVX-512 kernel for `haystack`.
+#[cfg(memchr_runtime_avx512)]
+#[inline(always)]
+fn uses_avx512(haystack: &[u8]) -> bool {
+    haystack.len() >= AVX512_MIN_LEN && has_avx512bw()
+}
+
+/// Returns the kernel that `memchr` uses for `haystack`.
+///
+/// This shares its checks with `memchr` and the ifunc's `detect`, and must
+/// make them in the same order.
+#[cfg(feature = "telemetry")]
+pub fn memchr_kernel(haystack: &[u8]) -> KernelId {
+    #[cfg(memchr_runtime_avx512)]
+    {
+        if uses_avx512(haystack) {
+            return KernelId::Avx512;
+        }
+    }
+    if cfg!(all(memchr_runtime_avx, target_feature = "avx2")) || has_avx2() {
+        KernelId::Avx2
+    } else if cfg!(memchr_runtime_sse2) {
+        KernelId::Sse2
+    } else {
+        KernelId::Swar
+    }
+}
+
+/// Run one of the x86 memchr kernels, regardless of which one memchr would
+/// select.
+///
+/// This panics if `has_kernel(kernel)` is false.
+#[cfg(feature = "bench-internals")]
+pub fn memchr_with_kernel(
+    kernel: KernelId,
+    n1: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    assert!(has_kernel(kernel), "{:?} is not available", kernel);
+    match kernel {
+        #[cfg(memchr_runtime_avx512)]
+        KernelId::Avx512 => unsafe { avx512::memchr(n1, haystack) },
+        KernelId::Avx2 => unsafe { avx::memchr(n1, haystack) },
+        KernelId::Sse2 => unsafe { sse2::memchr(n1, haystack) },
+        _ => unreachable!(),
+    }
+}
+
+/// Returns true if `kernel` is an x86 memchr kernel that was compiled and
+/// that the CPU supports.
+#[cfg(feature = "bench-internals")]
+pub fn has_kernel(kernel: KernelId) -> bool {
+    match kernel {
+        #[cfg(memchr_runtime_avx512)]
+        KernelId::Avx512 => has_avx512bw(),
+        KernelId::Avx2 => has_avx2(),
+        KernelId::Sse2 => cfg!(memchr_runtime_sse2),
+        _ => false,
+    }
+}
+
+/// Returns true if the CPU supports the AVX-512 routines.
+///
+/// The routines use both the foundation (`avx512f`) and byte/word
+/// (`avx512bw`) instructions, so both must be present.
+#[cfg(memchr_runtime_avx512)]
+#[inline(always)]
+pub fn has_avx512bw() -> bool {
+    is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512bw")
+}
+
+#[inline(always)]
+pub fn contains_byte(n1: u8, haystack: &[u8]) -> bool {
+    ifunc!(
+        returning bool;
+        fn(u8, &[u8]) -> bool, contains_byte, haystack, n1
+    )
+}
+
+#[inline(always)]
+pub fn count(n1: u8, haystack: &[u8]) -> usize {
+    ifunc!(returning usize; fn(u8, &[u8]) -> usize, count, haystack, n1)
+}
+
+#[inline(always)]
+pub fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    ifunc!(fn(u8, &[u8]) -> Option<usize>, memchr_not, haystack, n1)
+}
+
 #[inline(always)]
 pub fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
     ifunc!(fn(u8, u8, &[u8]) -> Option<usize>, memchr2, haystack, n1, n2)
@@ -84,3 +239,148 @@ pub fn memrchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
 pub fn memrchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
     ifunc!(fn(u8, u8, u8, &[u8]) -> Option<usize>, memrchr3, haystack, n1, n2, n3)
 }
+
+/// Return a mask where bit `i` is set if and only if `block[i] == n1`.
+#[inline(always)]
+pub fn match_mask(n1: u8, block: &[u8; 64]) -> u64 {
+    // SSE2 is part of the x86_64 baseline, so it needs no runtime detection.
+    // With only four vectors to compare, AVX2 wouldn't make up for the cost
+    // of dispatching to it.
+    unsafe { sse2::match_mask(n1, block) }
+}
+
+#[inline(always)]
+pub fn memchr_byteset(table: &[u8; 32], haystack: &[u8]) -> Option<usize> {
+    // We don't bother with the ifunc trick here. Set membership costs a lot
+    // more per byte than the single comparison in memchr, so the branch on
+    // `is_x86_feature_detected!` (which caches its result) is lost in the
+    // noise.
+    if cfg!(memchr_runtime_ssse3) && is_x86_feature_detected!("ssse3") {
+        unsafe { ssse3::memchr_byteset(table, haystack) }
+    } else {
+        byteset::memchr_table_fallback(table, haystack)
+    }
+}
+
+/// The longest needle for which `memmem_short` is used.
+pub const MEMMEM_SHORT_MAX: usize = 16;
+
+/// Search for a needle with between 2 and `MEMMEM_SHORT_MAX` bytes.
+///
+/// This returns `None` if the CPU supports neither the AVX2 nor the SSE 4.2
+/// routine, in which case the caller should search some other way. The
+/// SSE 4.2 routine is about half as fast as the AVX2 one, but still several
+/// times faster than the skip table, so it is used on CPUs without AVX2. As
+/// with `memchr_byteset`, the ifunc trick isn't needed: the branch is small
+/// compared to the work done per call.
+#[inline(always)]
+pub fn memmem_short(needle: &[u8], haystack: &[u8]) -> Option<Option<usize>> {
+    debug_assert!(2 <= needle.len() && needle.len() <= MEMMEM_SHORT_MAX);
+    if cfg!(memchr_runtime_avx) && is_x86_feature_detected!("avx2") {
+        Some(unsafe { avx::memmem_short(needle, haystack) })
+    } else if cfg!(memchr_runtime_sse42) && is_x86_feature_detected!("sse4.2")
+    {
+        Some(unsafe { sse42::memmem_short(needle, haystack) })
+    } else {
+        None
+    }
+}
+
+/// The largest stride for which `memchr_strided` is used.
+pub const STRIDED_MAX: usize = 4096;
+
+/// Search for a byte at a multiple of `stride`, which must be a power of two
+/// between 2 and `STRIDED_MAX`.
+///
+/// This returns `None` if the CPU doesn't support AVX2, in which case the
+/// caller should search some other way. As with `memmem_short`, the ifunc
+/// trick isn't needed.
+#[inline(always)]
+pub fn memchr_strided(
+    n1: u8,
+    haystack: &[u8],
+    stride: usize,
+) -> Option<Option<usize>> {
+    if cfg!(memchr_runtime_avx) && is_x86_feature_detected!("avx2") {
+        Some(unsafe { avx::memchr_strided(n1, haystack, stride) })
+    } else {
+        None
+    }
+}
+
+// Safe wrappers around each SIMD kernel, so that tests can call and compare
+// them directly instead of going through the ifunc dispatch above. Callers
+// are responsible for checking that the CPU supports a kernel (e.g., with
+// `has_avx2`) before calling its wrapper.
+#[cfg(test)]
+macro_rules! kernel_hooks {
+    ($(
+        $hook:ident = $module:ident::$name:ident($($needle:ident),+) -> $ret:ty;
+    )*) => {
+        $(
+            pub fn $hook($($needle: u8),+, haystack: &[u8]) -> $ret {
+                unsafe { $module::$name($($needle),+, haystack) }
+            }
+        )*
+    }
+}
+
+#[cfg(test)]
+kernel_hooks! {
+    memchr_sse2 = sse2::memchr(n1) -> Option<usize>;
+    memchr2_sse2 = sse2::memchr2(n1, n2) -> Option<usize>;
+    memchr3_sse2 = sse2::memchr3(n1, n2, n3) -> Option<usize>;
+    memrchr_sse2 = sse2::memrchr(n1) -> Option<usize>;
+    memrchr2_sse2 = sse2::memrchr2(n1, n2) -> Option<usize>;
+    memrchr3_sse2 = sse2::memrchr3(n1, n2, n3) -> Option<usize>;
+    memchr_not_sse2 = sse2::memchr_not(n1) -> Option<usize>;
+    contains_byte_sse2 = sse2::contains_byte(n1) -> bool;
+    count_sse2 = sse2::count(n1) -> usize;
+
+    memchr_avx2 = avx::memchr(n1) -> Option<usize>;
+    memchr2_avx2 = avx::memchr2(n1, n2) -> Option<usize>;
+    memchr3_avx2 = avx::memchr3(n1, n2, n3) -> Option<usize>;
+    memrchr_avx2 = avx::memrchr(n1) -> Option<usize>;
+    memrchr2_avx2 = avx::memrchr2(n1, n2) -> Option<usize>;
+    memrchr3_avx2 = avx::memrchr3(n1, n2, n3) -> Option<usize>;
+    memchr_not_avx2 = avx::memchr_not(n1) -> Option<usize>;
+    contains_byte_avx2 = avx::contains_byte(n1) -> bool;
+    count_avx2 = avx::count(n1) -> usize;
+}
+
+#[cfg(all(test, memchr_runtime_avx512))]
+kernel_hooks! {
+    memchr_avx512 = avx512::memchr(n1) -> Option<usize>;
+}
+
+#[cfg(test)]
+pub fn memmem_short_avx2(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    unsafe { avx::memmem_short(needle, haystack) }
+}
+
+#[cfg(test)]
+pub fn memmem_short_sse42(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    unsafe { sse42::memmem_short(needle, haystack) }
+}
+
+#[cfg(test)]
+pub fn memchr_strided_avx2(
+    n1: u8,
+    haystack: &[u8],
+    stride: usize,
+) -> Option<usize> {
+    unsafe { avx::memchr_strided(n1, haystack, stride) }
+}
+
+/// Returns true if the SSE 4.2 kernels were compiled and the CPU supports
+/// them.
+#[cfg(test)]
+pub fn has_sse42() -> bool {
+    cfg!(memchr_runtime_sse42) && is_x86_feature_detected!("sse4.2")
+}
+
+/// Returns true if the AVX2 kernels were compiled and the CPU supports them.
+#[inline(always)]
+pub fn has_avx2() -> bool {
+    cfg!(memchr_runtime_avx) && is_x86_feature_detected!("avx2")
+}
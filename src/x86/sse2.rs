@@ -14,6 +14,11 @@ const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
 // the loop unrolling.
 const LOOP_SIZE2: usize = 2 * VECTOR_SIZE;
 
+// The number of iterations of the loop in count after which its per-byte
+// counters are added up. Each iteration adds at most 4 to each counter, so
+// this is the most that fit in a byte without overflowing.
+const COUNT_FLUSH: usize = 255 / 4;
+
 #[target_feature(enable = "sse2")]
 pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     // What follows is a fast SSE2-only algorithm to detect the position of
@@ -186,6 +191,228 @@ pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     None
 }
 
+#[target_feature(enable = "sse2")]
+pub unsafe fn contains_byte(n1: u8, haystack: &[u8]) -> bool {
+    // This is the same as memchr, except we never need to know where the
+    // match is. So once any vector contains a match, we stop immediately
+    // without figuring out which vector (or lane) it came from.
+    let vn1 = _mm_set1_epi8(n1 as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr == n1 {
+                return true;
+            }
+            ptr = ptr.offset(1);
+        }
+        return false;
+    }
+
+    if forward_any1(ptr, vn1) {
+        return true;
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm_load_si128(ptr as *const __m128i);
+        let b = _mm_load_si128(ptr.add(VECTOR_SIZE) as *const __m128i);
+        let c = _mm_load_si128(ptr.add(2 * VECTOR_SIZE) as *const __m128i);
+        let d = _mm_load_si128(ptr.add(3 * VECTOR_SIZE) as *const __m128i);
+        let eqa = _mm_cmpeq_epi8(vn1, a);
+        let eqb = _mm_cmpeq_epi8(vn1, b);
+        let eqc = _mm_cmpeq_epi8(vn1, c);
+        let eqd = _mm_cmpeq_epi8(vn1, d);
+        let or1 = _mm_or_si128(eqa, eqb);
+        let or2 = _mm_or_si128(eqc, eqd);
+        if _mm_movemask_epi8(_mm_or_si128(or1, or2)) != 0 {
+            return true;
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        debug_assert!(sub(end_ptr, ptr) >= VECTOR_SIZE);
+
+        if forward_any1(ptr, vn1) {
+            return true;
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_any1(ptr, vn1);
+    }
+    false
+}
+
+#[target_feature(enable = "sse2")]
+pub unsafe fn count(n1: u8, haystack: &[u8]) -> usize {
+    // Unlike memchr, we can't reach an aligned pointer with an overlapping
+    // unaligned load, since that would count some bytes twice. So we just
+    // use unaligned loads everywhere, which are nearly as fast as aligned
+    // loads on modern CPUs.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr == n1 {
+                count += 1;
+            }
+            ptr = ptr.offset(1);
+        }
+        return count;
+    }
+
+    // In the main loop, a matching lane compares equal to -1, so
+    // subtracting the comparisons from an accumulator counts the matches in
+    // each byte lane without any movemask or popcount. The per-byte
+    // counters are added up with a sum of absolute differences (against
+    // zero) just before they could overflow, which keeps that relatively
+    // expensive step out of the loop, and is much faster than taking the
+    // popcount of each movemask, which matters when counting the lines of
+    // large files.
+    let vn1 = _mm_set1_epi8(n1 as i8);
+    let zero = _mm_setzero_si128();
+    while sub(end_ptr, ptr) >= LOOP_SIZE {
+        let chunks = cmp::min(COUNT_FLUSH, sub(end_ptr, ptr) / LOOP_SIZE);
+        let mut acc = zero;
+        for _ in 0..chunks {
+            let a = _mm_loadu_si128(ptr as *const __m128i);
+            let b = _mm_loadu_si128(ptr.add(VECTOR_SIZE) as *const __m128i);
+            let c =
+                _mm_loadu_si128(ptr.add(2 * VECTOR_SIZE) as *const __m128i);
+            let d =
+                _mm_loadu_si128(ptr.add(3 * VECTOR_SIZE) as *const __m128i);
+            let eqab =
+                _mm_add_epi8(_mm_cmpeq_epi8(vn1, a), _mm_cmpeq_epi8(vn1, b));
+            let eqcd =
+                _mm_add_epi8(_mm_cmpeq_epi8(vn1, c), _mm_cmpeq_epi8(vn1, d));
+            acc = _mm_sub_epi8(acc, _mm_add_epi8(eqab, eqcd));
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        let sums = _mm_sad_epu8(acc, zero);
+        let hi = _mm_unpackhi_epi64(sums, sums);
+        count += (_mm_cvtsi128_si64(sums) + _mm_cvtsi128_si64(hi)) as usize;
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        count += forward_count1(ptr, vn1);
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        // Only the last `rem` bytes in this final (overlapping) vector
+        // haven't been counted yet. They correspond to the most significant
+        // bits of the mask, so shift the rest out.
+        let rem = sub(end_ptr, ptr);
+        debug_assert!(rem < VECTOR_SIZE);
+        let tail = end_ptr.sub(VECTOR_SIZE);
+        let chunk = _mm_loadu_si128(tail as *const __m128i);
+        let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, vn1)) as u32;
+        count += (mask >> (VECTOR_SIZE - rem)).count_ones() as usize;
+    }
+    count
+}
+
+#[target_feature(enable = "sse2")]
+pub unsafe fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    // This has the same structure as memchr, except a lane matches when its
+    // byte is *not* equal to `n1`. So instead of checking whether any bit in
+    // the movemask is set, we check whether any bit is unset. In the
+    // unrolled loop, we AND the comparisons together, so that a single
+    // check tells us whether any lane in any of the four vectors differs.
+    let vn1 = _mm_set1_epi8(n1 as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr != n1 {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) = forward_search_not1(start_ptr, end_ptr, ptr, vn1) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm_load_si128(ptr as *const __m128i);
+        let b = _mm_load_si128(ptr.add(VECTOR_SIZE) as *const __m128i);
+        let c = _mm_load_si128(ptr.add(2 * VECTOR_SIZE) as *const __m128i);
+        let d = _mm_load_si128(ptr.add(3 * VECTOR_SIZE) as *const __m128i);
+        let eqa = _mm_cmpeq_epi8(vn1, a);
+        let eqb = _mm_cmpeq_epi8(vn1, b);
+        let eqc = _mm_cmpeq_epi8(vn1, c);
+        let eqd = _mm_cmpeq_epi8(vn1, d);
+        let and1 = _mm_and_si128(eqa, eqb);
+        let and2 = _mm_and_si128(eqc, eqd);
+        let and3 = _mm_and_si128(and1, and2);
+        if _mm_movemask_epi8(and3) != 0xFFFF {
+            let mut at = sub(ptr, start_ptr);
+            let mask = !_mm_movemask_epi8(eqa) & 0xFFFF;
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = !_mm_movemask_epi8(eqb) & 0xFFFF;
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = !_mm_movemask_epi8(eqc) & 0xFFFF;
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = !_mm_movemask_epi8(eqd) & 0xFFFF;
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        debug_assert!(sub(end_ptr, ptr) >= VECTOR_SIZE);
+
+        if let Some(i) = forward_search_not1(start_ptr, end_ptr, ptr, vn1) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_not1(start_ptr, end_ptr, ptr, vn1);
+    }
+    None
+}
+
 #[target_feature(enable = "sse2")]
 pub unsafe fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
     let vn1 = _mm_set1_epi8(n1 as i8);
@@ -565,6 +792,20 @@ pub unsafe fn memrchr3(
     None
 }
 
+/// Return a mask where bit `i` is set if and only if `block[i] == n1`.
+#[target_feature(enable = "sse2")]
+pub unsafe fn match_mask(n1: u8, block: &[u8; LOOP_SIZE]) -> u64 {
+    let vn1 = _mm_set1_epi8(n1 as i8);
+    let ptr = block.as_ptr();
+    let mut mask = 0;
+    for i in 0..4 {
+        let chunk = _mm_loadu_si128(ptr.add(i * VECTOR_SIZE) as *const _);
+        let eq = _mm_movemask_epi8(_mm_cmpeq_epi8(vn1, chunk));
+        mask |= (eq as u16 as u64) << (i * VECTOR_SIZE);
+    }
+    mask
+}
+
 #[target_feature(enable = "sse2")]
 pub unsafe fn forward_search1(
     start_ptr: *const u8,
@@ -585,6 +826,39 @@ pub unsafe fn forward_search1(
     }
 }
 
+#[target_feature(enable = "sse2")]
+unsafe fn forward_any1(ptr: *const u8, vn1: __m128i) -> bool {
+    let chunk = _mm_loadu_si128(ptr as *const __m128i);
+    _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, vn1)) != 0
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn forward_count1(ptr: *const u8, vn1: __m128i) -> usize {
+    let chunk = _mm_loadu_si128(ptr as *const __m128i);
+    let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, vn1));
+    (mask as u32).count_ones() as usize
+}
+
+#[target_feature(enable = "sse2")]
+unsafe fn forward_search_not1(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: __m128i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm_loadu_si128(ptr as *const __m128i);
+    let mask = !_mm_movemask_epi8(_mm_cmpeq_epi8(chunk, vn1)) & 0xFFFF;
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
 #[target_feature(enable = "sse2")]
 unsafe fn forward_search2(
     start_ptr: *const u8,
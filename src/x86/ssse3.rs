@@ -0,0 +1,148 @@
+// This module implements a search for the first byte that is a member of an
+// arbitrary set of bytes. It uses the "universal" algorithm for SIMD byte
+// lookups described by Wojciech Muła[1], which uses PSHUFB to test 16 bytes
+// for membership in a 256-bit bitmap at once. Its cost is independent of the
+// number of bytes in the set.
+//
+// The bitmap is laid out as two 16 byte tables, one for bytes whose high
+// nibble is in the range 0-7 and one for the range 8-15. In each table, the
+// row at index `lo` (the low nibble of a byte) has bit `hi & 7` set (where
+// `hi` is the high nibble of a byte) if and only if the byte is in the set.
+// This is the layout used by `ByteSet`.
+//
+// With that layout, membership of each byte in a 16 byte chunk is tested as
+// follows:
+//
+//     lo = chunk & 0x0F
+//     hi = (chunk >> 4) & 0x0F
+//     // Look up the row for every byte in each table. Since PSHUFB uses the
+//     // low 4 bits of each byte in its second operand as an index into its
+//     // first operand, this is exactly a 16-way parallel table lookup.
+//     row_0_7 = pshufb(table_0_7, lo)
+//     row_8_15 = pshufb(table_8_15, lo)
+//     // Pick the row from the correct table based on the high nibble.
+//     row = hi < 8 ? row_0_7 : row_8_15
+//     // Turn the high nibble into a single bit. This is yet another table
+//     // lookup: bit[i] = 1 << (i & 7).
+//     bit = pshufb(bits, hi)
+//     // A byte is a member if its bit is set in its row.
+//     members = cmpeq(row & bit, bit)
+//
+// Since PSHUFB was introduced in SSSE3, that's what this kernel requires.
+//
+// [1] http://0x80.pl/articles/simd-byte-lookup.html
+
+use core::arch::x86_64::*;
+use core::mem::size_of;
+
+use byteset::table_contains;
+
+const VECTOR_SIZE: usize = size_of::<__m128i>();
+
+#[target_feature(enable = "ssse3")]
+pub unsafe fn memchr_byteset(
+    table: &[u8; 32],
+    haystack: &[u8],
+) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = haystack[haystack.len()..].as_ptr();
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if table_contains(table, *ptr) {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    let lookup = Lookup::new(table);
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) = forward_search(start_ptr, end_ptr, ptr, &lookup) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search(start_ptr, end_ptr, ptr, &lookup);
+    }
+    None
+}
+
+/// The vectors needed to test membership of a chunk of bytes in a set.
+struct Lookup {
+    table_0_7: __m128i,
+    table_8_15: __m128i,
+    bits: __m128i,
+    nibble: __m128i,
+    seven: __m128i,
+}
+
+impl Lookup {
+    #[target_feature(enable = "ssse3")]
+    unsafe fn new(table: &[u8; 32]) -> Lookup {
+        Lookup {
+            table_0_7: _mm_loadu_si128(table.as_ptr() as *const __m128i),
+            table_8_15:
+                _mm_loadu_si128(table[16..].as_ptr() as *const __m128i),
+            bits: _mm_setr_epi8(
+                1, 2, 4, 8, 16, 32, 64, -128,
+                1, 2, 4, 8, 16, 32, 64, -128,
+            ),
+            nibble: _mm_set1_epi8(0x0F),
+            seven: _mm_set1_epi8(7),
+        }
+    }
+
+    /// Returns a mask whose bit `i` is set if and only if the byte at index
+    /// `i` in `chunk` is in the set.
+    #[target_feature(enable = "ssse3")]
+    unsafe fn members(&self, chunk: __m128i) -> i32 {
+        let lo = _mm_and_si128(chunk, self.nibble);
+        let hi = _mm_and_si128(_mm_srli_epi16(chunk, 4), self.nibble);
+        let row_0_7 = _mm_shuffle_epi8(self.table_0_7, lo);
+        let row_8_15 = _mm_shuffle_epi8(self.table_8_15, lo);
+        // All of our nibbles are in the range 0-15, so a signed comparison
+        // is fine here.
+        let is_8_15 = _mm_cmpgt_epi8(hi, self.seven);
+        let row = _mm_or_si128(
+            _mm_andnot_si128(is_8_15, row_0_7),
+            _mm_and_si128(is_8_15, row_8_15),
+        );
+        let bit = _mm_shuffle_epi8(self.bits, hi);
+        _mm_movemask_epi8(_mm_cmpeq_epi8(_mm_and_si128(row, bit), bit))
+    }
+}
+
+#[target_feature(enable = "ssse3")]
+unsafe fn forward_search(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    lookup: &Lookup,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm_loadu_si128(ptr as *const __m128i);
+    let mask = lookup.members(chunk);
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + mask.trailing_zeros() as usize)
+    } else {
+        None
+    }
+}
+
+/// Subtract `b` from `a` and return the difference. `a` should be greater than
+/// or equal to `b`.
+fn sub(a: *const u8, b: *const u8) -> usize {
+    debug_assert!(a >= b);
+    (a as usize) - (b as usize)
+}